@@ -0,0 +1,256 @@
+use std::fmt::Display;
+
+/// A byte range `start..end` in the input text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+}
+
+impl Display for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
+
+/// Everything that can go wrong between reading the input and printing the result.
+///
+/// The `span` is `None` when the error comes from a place that has no idea
+/// of the source text (e.g. the number module).
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum CalcError {
+    /// Characters that do not form a token.
+    Lex { message: String, span: Option<Span> },
+    /// Tokens that do not form an expression.
+    Parse { message: String, span: Option<Span> },
+    /// A division or remainder by zero, or zero to a negative power.
+    DivisionByZero { message: String, span: Option<Span> },
+    /// A result with more digits than the limit, or too large for the number representation.
+    Overflow { message: String, span: Option<Span> },
+    /// An exponent that the number representation cannot raise to.
+    UnsupportedExponent { message: String, span: Option<Span> },
+    /// An operation undefined for its operands, e.g. the square root of a negative number.
+    Domain { message: String, span: Option<Span> },
+    /// A name that is not defined, e.g. a variable used before it is assigned.
+    Undefined { message: String, span: Option<Span> },
+    /// A function called with a wrong number of arguments.
+    Argument { message: String, span: Option<Span> },
+    /// Functions calling each other deeper than the limit.
+    Recursion { message: String, span: Option<Span> },
+    /// Units that do not fit together, e.g. `1 m + 1 s`.
+    Dimension { message: String, span: Option<Span> },
+    /// A boolean where a number is needed, or the other way around, e.g. `1 + true`.
+    Type { message: String, span: Option<Span> },
+}
+
+impl CalcError {
+    pub fn lex(message: impl Into<String>, span: Span) -> Self {
+        CalcError::Lex { message: message.into(), span: Some(span) }
+    }
+
+    pub fn parse(message: impl Into<String>, span: Option<Span>) -> Self {
+        CalcError::Parse { message: message.into(), span }
+    }
+
+    pub fn division_by_zero() -> Self {
+        CalcError::DivisionByZero { message: "Cannot divide by zero".to_string(), span: None }
+    }
+
+    pub fn overflow() -> Self {
+        CalcError::Overflow { message: "Number is too large".to_string(), span: None }
+    }
+
+    pub fn unsupported_exponent(message: impl Into<String>) -> Self {
+        CalcError::UnsupportedExponent { message: message.into(), span: None }
+    }
+
+    pub fn domain(message: impl Into<String>) -> Self {
+        CalcError::Domain { message: message.into(), span: None }
+    }
+
+    pub fn undefined(message: impl Into<String>) -> Self {
+        CalcError::Undefined { message: message.into(), span: None }
+    }
+
+    pub fn argument(message: impl Into<String>) -> Self {
+        CalcError::Argument { message: message.into(), span: None }
+    }
+
+    pub fn dimension(message: impl Into<String>) -> Self {
+        CalcError::Dimension { message: message.into(), span: None }
+    }
+
+    pub fn wrong_type(message: impl Into<String>) -> Self {
+        CalcError::Type { message: message.into(), span: None }
+    }
+
+    pub fn recursion(limit: usize) -> Self {
+        CalcError::Recursion { message: format!("Functions are nested deeper than {} calls", limit), span: None }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            CalcError::Lex { message, .. }
+            | CalcError::Parse { message, .. }
+            | CalcError::DivisionByZero { message, .. }
+            | CalcError::Overflow { message, .. }
+            | CalcError::UnsupportedExponent { message, .. }
+            | CalcError::Domain { message, .. }
+            | CalcError::Undefined { message, .. }
+            | CalcError::Argument { message, .. }
+            | CalcError::Recursion { message, .. }
+            | CalcError::Dimension { message, .. }
+            | CalcError::Type { message, .. } => message
+        }
+    }
+
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            CalcError::Lex { span, .. }
+            | CalcError::Parse { span, .. }
+            | CalcError::DivisionByZero { span, .. }
+            | CalcError::Overflow { span, .. }
+            | CalcError::UnsupportedExponent { span, .. }
+            | CalcError::Domain { span, .. }
+            | CalcError::Undefined { span, .. }
+            | CalcError::Argument { span, .. }
+            | CalcError::Recursion { span, .. }
+            | CalcError::Dimension { span, .. }
+            | CalcError::Type { span, .. } => *span
+        }
+    }
+
+    /// Attach `span` if the error does not know its location yet.
+    pub fn with_span(mut self, new_span: Span) -> Self {
+        let span = self.span_mut();
+        if span.is_none() { *span = Some(new_span); }
+        self
+    }
+
+    /// Point the error at `span`, wherever it pointed before, e.g. from the body of
+    /// a function to where the function is called.
+    pub fn at(mut self, new_span: Span) -> Self {
+        *self.span_mut() = Some(new_span);
+        self
+    }
+
+    fn span_mut(&mut self) -> &mut Option<Span> {
+        match self {
+            CalcError::Lex { span, .. }
+            | CalcError::Parse { span, .. }
+            | CalcError::DivisionByZero { span, .. }
+            | CalcError::Overflow { span, .. }
+            | CalcError::UnsupportedExponent { span, .. }
+            | CalcError::Domain { span, .. }
+            | CalcError::Undefined { span, .. }
+            | CalcError::Argument { span, .. }
+            | CalcError::Recursion { span, .. }
+            | CalcError::Dimension { span, .. }
+            | CalcError::Type { span, .. } => span
+        }
+    }
+
+    /// Show the error under the line of `source` it points into:
+    ///
+    /// ```text
+    /// 1 + * 2
+    ///     ^
+    /// parse error: Unexpected '*'
+    /// ```
+    pub fn render(&self, source: &str) -> String {
+        let headline = format!("{}: {}", self.kind(), self.message());
+        let span = match self.span() {
+            Some(span) => span,
+            None => return headline,
+        };
+        let start = char_boundary(source, span.start);
+        let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = source[start..].find('\n').map_or(source.len(), |i| start + i);
+        let end = char_boundary(source, span.end).clamp(start, line_end);
+
+        let line = source[line_start..line_end].replace('\t', " ");
+        let column = display_width(&source[line_start..start]);
+        let width = display_width(&source[start..end]).max(1);
+        format!("{}\n{}^{}\n{}", line, " ".repeat(column), "~".repeat(width - 1), headline)
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            CalcError::Lex { .. } => "lex error",
+            CalcError::Parse { .. } => "parse error",
+            CalcError::DivisionByZero { .. } => "division by zero",
+            CalcError::Overflow { .. } => "overflow",
+            CalcError::UnsupportedExponent { .. } => "unsupported exponent",
+            CalcError::Domain { .. } => "domain error",
+            CalcError::Undefined { .. } => "undefined name",
+            CalcError::Argument { .. } => "argument error",
+            CalcError::Recursion { .. } => "recursion error",
+            CalcError::Dimension { .. } => "dimension error",
+            CalcError::Type { .. } => "type error",
+        }
+    }
+}
+
+/// How many columns `text` takes in a terminal, where East Asian wide and full-width characters,
+/// e.g. `１` or `＋`, take two.
+fn display_width(text: &str) -> usize {
+    text.chars().map(|c| match c as u32 {
+        0x1100..=0x115F | 0x2E80..=0x303E | 0x3041..=0x33FF | 0x3400..=0x4DBF | 0x4E00..=0x9FFF
+        | 0xA000..=0xA4CF | 0xAC00..=0xD7A3 | 0xF900..=0xFAFF | 0xFE30..=0xFE4F | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6 | 0x1F300..=0x1F64F | 0x1F900..=0x1F9FF | 0x20000..=0x3FFFD => 2,
+        _ => 1,
+    }).sum()
+}
+
+/// The nearest char boundary at or before `index`.
+fn char_boundary(source: &str, index: usize) -> usize {
+    let mut index = index.min(source.len());
+    while !source.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+impl Display for CalcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.span() {
+            Some(span) => write!(f, "{} at {}: {}", self.kind(), span, self.message()),
+            None => write!(f, "{}: {}", self.kind(), self.message()),
+        }
+    }
+}
+
+impl std::error::Error for CalcError {}
+
+#[test]
+fn test_error_with_span() {
+    let err = CalcError::division_by_zero().with_span(Span::new(2, 5));
+    assert_eq!(err.span(), Some(Span::new(2, 5)));
+    let err = err.with_span(Span::new(0, 1));  // keeps the first one
+    assert_eq!(err.span(), Some(Span::new(2, 5)));
+    assert_eq!(err.to_string(), "division by zero at 2..5: Cannot divide by zero");
+    assert_eq!(err.at(Span::new(0, 1)).span(), Some(Span::new(0, 1)));
+}
+
+#[test]
+fn test_error_render() {
+    let err = CalcError::parse("Unexpected '*'", Some(Span::new(4, 5)));
+    assert_eq!(err.render("1 + * 2"), "1 + * 2\n    ^\nparse error: Unexpected '*'");
+    let err = CalcError::division_by_zero().with_span(Span::new(4, 11));
+    assert_eq!(err.render("1 / (2 - 2)"), "1 / (2 - 2)\n    ^~~~~~~\ndivision by zero: Cannot divide by zero");
+    let err = CalcError::parse("Expected ')', found end of input", Some(Span::new(9, 9)));
+    assert_eq!(err.render("1\n(2 + 3"), "(2 + 3\n      ^\nparse error: Expected ')', found end of input");
+    assert_eq!(CalcError::overflow().render("1"), "overflow: Number is too large");
+    // a full-width character takes two columns
+    let err = CalcError::lex("Unexpected character '$'", Span::new(14, 15));
+    assert_eq!(err.render("１２３ ＋ $"), "１２３ ＋ $\n          ^\nlex error: Unexpected character '$'");
+    let err = CalcError::parse("Expected an operator", Some(Span::new(0, 9)));
+    assert_eq!(err.render("１２３ 4"), "１２３ 4\n^~~~~~\nparse error: Expected an operator");
+}
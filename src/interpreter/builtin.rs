@@ -0,0 +1,174 @@
+use std::{cmp::Ordering, ops::RangeInclusive};
+
+use crate::{
+    error::CalcError,
+    number::{context::{Context, RoundingMode}, decimal::Decimal, math, numeric::Numeric, unit::Quantity}
+};
+
+use Implementation::{Abs, Arg, Conj, Im, Max, Min, Re, Real, Round, Sqrt, ToInteger};
+
+/// How a built-in function computes its result: most of them by the number type itself,
+/// which keeps fractions exact where it can.
+pub(crate) enum Implementation {
+    Sqrt,
+    Abs,
+    /// The parts of a complex number.
+    Arg,
+    Conj,
+    Re,
+    Im,
+    /// `floor`, `ceil` and `trunc`.
+    ToInteger(RoundingMode),
+    Round,
+    Min,
+    Max,
+    /// On decimals, for results that are irrational in general,
+    /// unless the number type has a faster way (see `Numeric::function`).
+    Real(fn(&[Decimal], &Context) -> Result<Decimal, CalcError>),
+}
+
+/// A function known by name, e.g. `sqrt` in `sqrt(2)`.
+pub(crate) struct Builtin {
+    pub name: &'static str,
+    /// How many arguments it takes.
+    pub arity: RangeInclusive<usize>,
+    pub function: Implementation,
+}
+
+impl Builtin {
+    /// The result on `args`, which keeps their unit where that makes sense, e.g. `abs(-3 m)` is `3 m`;
+    /// the other functions take plain numbers, or angles for the trigonometric ones.
+    pub fn call<N: Numeric>(&self, args: &[Quantity<N>], ctx: &Context) -> Result<Quantity<N>, CalcError> {
+        self.check_arity(args.len())?;
+        let same_unit = |value: N| Quantity::new(value, args[0].unit().clone());
+        // each in its own unit, e.g. `max(1 km, 300 m)` is `1 km`
+        let pick = |better: Ordering| {
+            args.iter().try_fold(&args[0], |best, arg| match arg.compare(best, ctx)? {
+                Some(order) => Ok(if order == better { arg } else { best }),
+                None => Err(CalcError::domain("Complex numbers cannot be compared")),
+            }).cloned()
+        };
+        match self.function {
+            Sqrt => args[0].sqrt(ctx),
+            Abs => args[0].value().abs(ctx).map(same_unit),
+            Conj => Ok(same_unit(args[0].value().conj())),
+            Re => Ok(same_unit(args[0].value().re())),
+            Im => args[0].value().im(ctx).map(same_unit),
+            ToInteger(mode) => Ok(same_unit(args[0].value().round(0, mode))),
+            Round => {
+                let digits = args.get(1).map(|n| n.number("Digits to round to", ctx)).transpose()?;
+                round(args[0].value(), digits.as_ref()).map(same_unit)
+            },
+            Min => pick(Ordering::Less),
+            Max => pick(Ordering::Greater),
+            Arg => args[0].value().arg(ctx).map(Quantity::from),
+            Real(function) => {
+                let what = format!("The argument of '{}'", self.name);
+                let values = args.iter().map(|arg| arg.number(&what, ctx)).collect::<Result<Vec<_>, _>>()?;
+                N::function(self.name, function, &values, ctx).map(Quantity::from)
+            },
+        }
+    }
+
+    fn check_arity(&self, found: usize) -> Result<(), CalcError> {
+        if !self.arity.contains(&found) {
+            let expected = match (self.arity.start(), self.arity.end()) {
+                (min, &usize::MAX) => format!("at least {}", min),
+                (min, max) if min == max => min.to_string(),
+                (min, max) => format!("{} to {}", min, max),
+            };
+            return Err(CalcError::argument(format!(
+                "'{}' takes {} argument(s), found {}", self.name, expected, found
+            )));
+        }
+        Ok(())
+    }
+}
+
+const BUILTINS: &[Builtin] = &[
+    Builtin { name: "sqrt", arity: 1..=1, function: Sqrt },
+    Builtin { name: "abs", arity: 1..=1, function: Abs },
+    Builtin { name: "arg", arity: 1..=1, function: Arg },
+    Builtin { name: "conj", arity: 1..=1, function: Conj },
+    Builtin { name: "re", arity: 1..=1, function: Re },
+    Builtin { name: "im", arity: 1..=1, function: Im },
+    Builtin { name: "floor", arity: 1..=1, function: ToInteger(RoundingMode::Floor) },
+    Builtin { name: "ceil", arity: 1..=1, function: ToInteger(RoundingMode::Ceiling) },
+    Builtin { name: "trunc", arity: 1..=1, function: ToInteger(RoundingMode::TowardZero) },
+    Builtin { name: "round", arity: 1..=2, function: Round },
+    Builtin { name: "min", arity: 1..=usize::MAX, function: Min },
+    Builtin { name: "max", arity: 1..=usize::MAX, function: Max },
+    Builtin { name: "ln", arity: 1..=1, function: Real(|x, ctx| math::ln(&x[0], ctx)) },
+    Builtin { name: "log10", arity: 1..=1, function: Real(|x, ctx| math::log10(&x[0], ctx)) },
+    Builtin { name: "log", arity: 2..=2, function: Real(|x, ctx| math::log(&x[0], &x[1], ctx)) },
+    Builtin { name: "exp", arity: 1..=1, function: Real(|x, ctx| math::exp(&x[0], ctx)) },
+    Builtin { name: "sin", arity: 1..=1, function: Real(|x, ctx| math::sin(&x[0], ctx)) },
+    Builtin { name: "cos", arity: 1..=1, function: Real(|x, ctx| math::cos(&x[0], ctx)) },
+    Builtin { name: "tan", arity: 1..=1, function: Real(|x, ctx| math::tan(&x[0], ctx)) },
+    Builtin { name: "asin", arity: 1..=1, function: Real(|x, ctx| math::asin(&x[0], ctx)) },
+    Builtin { name: "acos", arity: 1..=1, function: Real(|x, ctx| math::acos(&x[0], ctx)) },
+    Builtin { name: "atan", arity: 1..=1, function: Real(|x, ctx| math::atan(&x[0], ctx)) },
+];
+
+/// `round(x)` to an integer, or `round(x, n)` to `n` digits after the point, ties away from zero.
+fn round<N: Numeric>(x: &N, digits: Option<&N>) -> Result<N, CalcError> {
+    let digits = match digits {
+        None => 0,
+        Some(n) => n.to_integer().and_then(|n| n.to_u64()).filter(|n| *n <= u32::MAX as u64)
+            .ok_or_else(|| CalcError::domain("Digits to round to must be a non-negative integer"))?,
+    };
+    Ok(x.round(digits as usize, RoundingMode::HalfUp))
+}
+
+pub(crate) fn find(name: &str) -> Option<&'static Builtin> {
+    BUILTINS.iter().find(|builtin| builtin.name == name)
+}
+
+/// Named constants, as functions without arguments.
+const CONSTANTS: &[Builtin] = &[
+    Builtin { name: "pi", arity: 0..=0, function: Real(|_, ctx| math::pi(ctx)) },
+    Builtin { name: "π", arity: 0..=0, function: Real(|_, ctx| math::pi(ctx)) },
+    Builtin { name: "e", arity: 0..=0, function: Real(|_, ctx| math::exp(&Decimal::one(), ctx)) },
+];
+
+/// The value of a named constant, `pi` (or `π`) or `e`, to `ctx.precision`, or the imaginary unit `i`.
+pub(crate) fn constant<N: Numeric>(name: &str, ctx: &Context) -> Option<Result<N, CalcError>> {
+    if name == "i" { return Some(N::from_imaginary(Decimal::one(), ctx)); }
+    CONSTANTS.iter().find(|constant| constant.name == name).and_then(|constant| match constant.function {
+        Real(function) => Some(N::function(constant.name, function, &[], ctx)),
+        _ => None,
+    })
+}
+
+#[test]
+fn test_builtin() {
+    let ctx = Context::default();
+    let call = |name: &str, args: &[&str]| {
+        let args: Vec<Quantity<Decimal>> = args.iter().map(|a| match a.split_once(' ') {
+            Some((n, unit)) => Quantity::new(n.parse().unwrap(), crate::Unit::find(unit).unwrap()),
+            None => Quantity::from(a.parse::<Decimal>().unwrap()),
+        }).collect();
+        find(name).unwrap().call(&args, &ctx).map(|r| r.to_string())
+    };
+    assert_eq!(call("floor", &["2.5"]).unwrap(), "2.0");
+    assert_eq!(call("ceil", &["2.1"]).unwrap(), "3.0");
+    assert_eq!(call("round", &["2.5"]).unwrap(), "3.0");
+    assert_eq!(call("round", &["2.345", "2"]).unwrap(), "2.35");
+    assert_eq!(call("max", &["1", "3.5", "2"]).unwrap(), "3.5");
+    assert_eq!(call("min", &["1", "3.5", "2"]).unwrap(), "1.0");
+    assert!(matches!(call("round", &["2.5", "0.5"]), Err(CalcError::Domain { .. })));
+    let err = call("log", &["2"]).unwrap_err();
+    assert_eq!(err.message(), "'log' takes 2 argument(s), found 1");
+    let err = call("max", &[]).unwrap_err();
+    assert_eq!(err.message(), "'max' takes at least 1 argument(s), found 0");
+    assert_eq!(call("abs", &["2.5 m"]).unwrap(), "2.5 m");
+    assert_eq!(call("max", &["1 km", "300 m"]).unwrap(), "1.0 km");
+    assert_eq!(call("min", &["1 km", "300 m"]).unwrap(), "300.0 m");
+    assert_eq!(call("sin", &["90 deg"]).unwrap(), "1.0");
+    assert!(matches!(call("max", &["1 km", "3 s"]), Err(CalcError::Dimension { .. })));
+    let err = call("ln", &["2 m"]).unwrap_err();
+    assert_eq!(err.message(), "The argument of 'ln' must be a number without a unit, found 'm'");
+    assert!(find("nope").is_none());
+    assert_eq!(constant::<Decimal>("pi", &ctx).unwrap().unwrap().to_string(), "3.14159265358979323846");
+    assert!(constant::<Decimal>("x", &ctx).is_none());
+}
@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+use crate::{
+    interpreter::{operator::OperatorTable, parsing::Expression, value::Value},
+    number::{context::Dialect, numeric::{Number, Numeric}}
+};
+
+/// A function defined by the user, e.g. `f(x, y) = x ^ 2 + y`.
+#[derive(Debug, Clone)]
+pub(crate) struct Function {
+    pub params: Vec<String>,
+    pub body: Expression
+}
+
+/// The variables, functions and operators that live longer than one calculation,
+/// e.g. across the lines of the shell, for calculations with numbers of type `N`.
+#[derive(Debug, Clone)]
+pub struct Environment<N = Number> {
+    variables: HashMap<String, Value<N>>,
+    functions: HashMap<String, Function>,
+    operators: OperatorTable
+}
+
+impl<N: Numeric> Default for Environment<N> {
+    fn default() -> Self {
+        Self { variables: HashMap::new(), functions: HashMap::new(), operators: OperatorTable::default() }
+    }
+}
+
+impl<N: Numeric> Environment<N> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Option<&Value<N>> {
+        self.variables.get(name)
+    }
+
+    pub(crate) fn set(&mut self, name: &str, value: Value<N>) {
+        self.variables.insert(name.to_string(), value);
+    }
+
+    /// The printed value of the variable `name`, if it is defined.
+    pub fn value(&self, name: &str) -> Option<String> {
+        self.get(name).map(|value| value.to_string())
+    }
+
+    /// Forget the variable `name`, returning whether it was defined.
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.variables.remove(name).is_some()
+    }
+
+    pub(crate) fn function(&self, name: &str) -> Option<&Function> {
+        self.functions.get(name)
+    }
+
+    /// Define the function `name`, replacing an older one of the same name.
+    pub(crate) fn define(&mut self, name: &str, function: Function) {
+        self.functions.insert(name.to_string(), function);
+    }
+
+    /// The definition of the function `name` as it could be typed in again,
+    /// e.g. `f(x, y) = x ^ 2 + y`.
+    pub fn definition(&self, name: &str) -> Option<String> {
+        self.definition_in(name, Dialect::default())
+    }
+
+    /// `definition`, written in `dialect`, e.g. `f(x) = x ** 2` in `Dialect::C`.
+    pub fn definition_in(&self, name: &str, dialect: Dialect) -> Option<String> {
+        self.function(name).map(|f| format!("{}({}) = {}", name, f.params.join(", "), f.body.infix_in(dialect)))
+    }
+
+    /// Forget the function `name`, returning whether it was defined.
+    pub fn undefine(&mut self, name: &str) -> bool {
+        self.functions.remove(name).is_some()
+    }
+
+    /// The operators of the calculations, the built-in ones unless changed with `operators_mut`.
+    pub fn operators(&self) -> &OperatorTable {
+        &self.operators
+    }
+
+    /// The operators to change, e.g. `env.operators_mut().infix("<>", 95, Associativity::Left, "avg")`
+    /// for `1 <> 3`, with `avg` a function built in or defined in `env`.
+    pub fn operators_mut(&mut self) -> &mut OperatorTable {
+        &mut self.operators
+    }
+
+    /// The definitions of all functions, sorted by name.
+    pub fn functions(&self) -> Vec<String> {
+        let mut names: Vec<&String> = self.functions.keys().collect();
+        names.sort();
+        names.into_iter().filter_map(|name| self.definition(name)).collect()
+    }
+
+    /// All variables as `(name, value)`, sorted by name.
+    pub fn variables(&self) -> Vec<(String, String)> {
+        let mut variables: Vec<(String, String)> = self.variables.iter()
+            .map(|(name, value)| (name.clone(), value.to_string()))
+            .collect();
+        variables.sort();
+        variables
+    }
+}
+
+#[test]
+fn test_environment() {
+    use crate::number::unit::Quantity;
+    let mut env: Environment = Environment::new();
+    assert_eq!(env.value("x"), None);
+    let num = |s: &str| Value::from(Quantity::from(Number::Decimal(s.parse().unwrap())));
+    env.set("x", num("1.5"));
+    env.set("a", num("2"));
+    env.set("ok", Value::from(true));
+    env.set("x", num("2.5"));
+    assert_eq!(env.value("x").as_deref(), Some("2.5"));
+    assert_eq!(env.variables(), vec![
+        ("a".to_string(), "2.0".to_string()), ("ok".to_string(), "true".to_string()),
+        ("x".to_string(), "2.5".to_string())
+    ]);
+    assert!(env.remove("x"));
+    assert!(!env.remove("x"));
+}
@@ -1,31 +1,550 @@
 use crate::{
+    error::CalcError,
     interpreter::{
-        parsing::Expression, lexeme::Token
+        builtin,
+        environment::{Environment, Function},
+        parsing::{Expression, ExprKind, Statement},
+        lexeme::TokenKind,
+        value::Value
     },
-    number::decimal::Decimal
+    number::{
+        bigint::BigInt, context::Context, decimal::Decimal, literal::parse_literal, numeric::Numeric,
+        unit::{Quantity, Unit}
+    }
 };
 
-pub fn eval(expr: Expression) -> Decimal {
-    match expr {
-        Expression::Number(n) => {
-            Decimal::from(n)
+/// Run a statement, binding the variable of an assignment or the function of a definition in `env`.
+/// The value of an assignment is the value assigned, a definition has none.
+pub fn execute<N: Numeric>(
+    statement: &Statement, env: &mut Environment<N>, ctx: &Context
+) -> Result<Option<Value<N>>, CalcError> {
+    match statement {
+        Statement::Expression(expr) => eval(expr, env, ctx).map(Some),
+        Statement::Assign { name, value } => {
+            let value = eval(value, env, ctx)?;
+            env.set(name, value.clone());
+            Ok(Some(value))
+        },
+        Statement::Define { name, params, body } => {
+            env.define(name, Function { params: params.clone(), body: body.clone() });
+            Ok(None)
+        },
+    }
+}
+
+pub fn eval<N: Numeric>(expr: &Expression, env: &Environment<N>, ctx: &Context) -> Result<Value<N>, CalcError> {
+    evaluate(expr, &Scope { env, locals: vec![], depth: 0 }, ctx)
+}
+
+/// Where names are looked up: the parameters of the function being run, then `env`.
+///
+/// Scoping is lexical: a function body sees its own parameters and the environment,
+/// never the parameters of the function that called it.
+struct Scope<'a, N> {
+    env: &'a Environment<N>,
+    locals: Vec<(&'a str, Value<N>)>,
+    /// How many user functions are running.
+    depth: usize
+}
+
+impl<N: Numeric> Scope<'_, N> {
+    fn variable(&self, name: &str) -> Option<&Value<N>> {
+        match self.locals.iter().find(|(local, _)| *local == name) {
+            Some((_, value)) => Some(value),
+            None => self.env.get(name),
+        }
+    }
+}
+
+fn evaluate<N: Numeric>(expr: &Expression, scope: &Scope<N>, ctx: &Context) -> Result<Value<N>, CalcError> {
+    match &expr.kind {
+        ExprKind::Boolean(b) => Ok(Value::from(*b)),
+        ExprKind::Variable(name) => match scope.variable(name) {
+            Some(value) => Ok(value.clone()),
+            None => quantity(expr, scope, ctx).map(Value::from),
+        },
+        ExprKind::Call { name, args } if scope.env.function(name).is_some() => {
+            // a user function hides a built-in one of the same name, and takes booleans too
+            let values = args.iter().map(|arg| evaluate(arg, scope, ctx)).collect::<Result<Vec<_>, _>>()?;
+            call(name, scope.env.function(name).unwrap(), values, scope, ctx).map_err(|e| e.at(expr.span))
+        },
+        ExprKind::Unary { op: TokenKind::Not, expr: operand } => Ok(Value::from(!boolean(operand, scope, ctx)?)),
+        ExprKind::Binary { op: TokenKind::And, left, right } => {
+            Ok(Value::from(boolean(left, scope, ctx)? && boolean(right, scope, ctx)?))
+        },
+        ExprKind::Binary { op: TokenKind::Or, left, right } => {
+            Ok(Value::from(boolean(left, scope, ctx)? || boolean(right, scope, ctx)?))
+        },
+        ExprKind::Binary { op, left, right } if op.is_comparison() => {
+            let (lhs, rhs) = (evaluate(left, scope, ctx)?, evaluate(right, scope, ctx)?);
+            compare(op, &lhs, &rhs, ctx).map(Value::from).map_err(|e| e.with_span(expr.span))
+        },
+        ExprKind::Conditional { condition, then, otherwise } => {
+            evaluate(if boolean(condition, scope, ctx)? { then } else { otherwise }, scope, ctx)
+        },
+        _ => quantity(expr, scope, ctx).map(Value::from),
+    }
+}
+
+/// The number `expr` comes to, which must not be a boolean.
+fn number<N: Numeric>(expr: &Expression, scope: &Scope<N>, ctx: &Context) -> Result<Quantity<N>, CalcError> {
+    evaluate(expr, scope, ctx)?.into_number().map_err(|e| e.with_span(expr.span))
+}
+
+/// The boolean `expr` comes to, which must not be a number.
+fn boolean<N: Numeric>(expr: &Expression, scope: &Scope<N>, ctx: &Context) -> Result<bool, CalcError> {
+    evaluate(expr, scope, ctx)?.into_boolean().map_err(|e| e.with_span(expr.span))
+}
+
+/// The expressions that come to numbers.
+fn quantity<N: Numeric>(expr: &Expression, scope: &Scope<N>, ctx: &Context) -> Result<Quantity<N>, CalcError> {
+    match &expr.kind {
+        ExprKind::Number(n) => match n.strip_suffix('i') {
+            Some(n) => parse_literal(n, ctx).and_then(|value| N::from_imaginary(value, ctx)),
+            None => parse_literal(n, ctx).and_then(|value| N::from_decimal(value, ctx)),
+        }.map(Quantity::from).map_err(|e| e.with_span(expr.span)),
+        // a unit stands for one of itself, unless a constant has its name
+        ExprKind::Variable(name) => match (builtin::constant::<N>(name, ctx), Unit::find(name)) {
+            (Some(value), _) => value.map(Quantity::from),
+            (None, Some(unit)) => N::from_decimal(Decimal::one(), ctx).map(|one| Quantity::new(one, unit)),
+            (None, None) => Err(CalcError::undefined(format!("Unknown variable '{}'", name))),
+        }.map_err(|e| e.with_span(expr.span)),
+        ExprKind::WithUnit { expr: number, unit } => {
+            Ok(Quantity::new(quantity(number, scope, ctx)?.into_value(), unit.clone()))
+        },
+        ExprKind::Convert { expr: operand, unit } => {
+            number(operand, scope, ctx)?.to(unit, ctx).map_err(|e| e.with_span(expr.span))
         },
-        Expression::Unary { op, expr } => {
+        ExprKind::Call { name, args } => {
+            let values = args.iter().map(|arg| number(arg, scope, ctx)).collect::<Result<Vec<_>, _>>()?;
+            match builtin::find(name) {
+                Some(builtin) => builtin.call(&values, ctx),
+                None => Err(CalcError::undefined(format!("Unknown function '{}'", name))),
+            }.map_err(|e| e.with_span(expr.span))
+        },
+        ExprKind::Unary { op, expr: operand } => {
+            let value = number(operand, scope, ctx)?;
             match op {
-                Token::Plus => eval(*expr),
-                Token::Minus => -eval(*expr),
-                _ => panic!("Wrong of signed number")
-            }
+                TokenKind::Plus => Ok(value),
+                TokenKind::Minus => Ok(-value),
+                TokenKind::BitNot => value.integer("~", ctx).and_then(|n| Quantity::from_integer(!&n, ctx)),
+                other => Err(CalcError::parse(format!("{} is not a unary operator", other.describe()), None))
+            }.map_err(|e| e.with_span(expr.span))
         },
-        Expression::Binary { op, left, right } => {
+        ExprKind::Postfix { op, expr: operand } => {
+            let value = number(operand, scope, ctx)?;
             match op {
-                Token::Plus => eval(*left) + eval(*right),
-                Token::Minus => eval(*left) - eval(*right),
-                Token::Multiply => eval(*left) * eval(*right),
-                Token::Divide => eval(*left) / eval(*right),
-                Token::Power => eval(*left).pow(&eval(*right)),
-                _ => panic!("Wrong on calculating")
+                TokenKind::Percent => N::from_decimal(Decimal::from_parts(BigInt::from(100), 0), ctx)
+                    .and_then(|hundred| value.checked_div(&Quantity::from(hundred), ctx)),
+                _ => factorial(op, &value, ctx),
+            }.map_err(|e| e.with_span(expr.span))
+        },
+        // only in the tree of a parser that went on after an error
+        ExprKind::Error => Err(CalcError::parse("Cannot evaluate what could not be parsed", Some(expr.span))),
+        ExprKind::Implicit { left, right } => {
+            let (lhs, rhs) = (number(left, scope, ctx)?, number(right, scope, ctx)?);
+            lhs.checked_mul(&rhs, ctx).map_err(|e| e.with_span(expr.span))
+        },
+        ExprKind::Binary { op, left, right } => {
+            let lhs = number(left, scope, ctx)?;
+            if let (TokenKind::Power, Some((numerator, denominator))) = (op, fraction(right, scope, ctx)?) {
+                // keep `8 ^ (1 / 3)` exact, instead of rounding the exponent first
+                return lhs.pow_fraction(&numerator, &denominator, ctx).map_err(|e| e.with_span(expr.span));
             }
+            let rhs = number(right, scope, ctx)?;
+            match op {
+                TokenKind::Plus => lhs.checked_add(&rhs, ctx),
+                TokenKind::Minus => lhs.checked_sub(&rhs, ctx),
+                TokenKind::Multiply => lhs.checked_mul(&rhs, ctx),
+                TokenKind::Divide | TokenKind::IntegerDivide | TokenKind::Modulo => match op {
+                    TokenKind::Divide => lhs.checked_div(&rhs, ctx),
+                    TokenKind::IntegerDivide => lhs.div_floor(&rhs, ctx),
+                    _ => lhs.rem_floor(&rhs, ctx),
+                }.map_err(|e| match e {
+                    CalcError::DivisionByZero { .. } => e.with_span(right.span),
+                    e => e,
+                }),
+                TokenKind::Power => lhs.pow(&rhs, ctx),
+                TokenKind::BitAnd | TokenKind::BitOr | TokenKind::BitXor | TokenKind::ShiftLeft
+                | TokenKind::ShiftRight => bitwise(op, &lhs, &rhs, ctx),
+                other => Err(CalcError::parse(format!("{} is not a binary operator", other.describe()), None))
+            }.map_err(|e| e.with_span(expr.span))
+        },
+        _ => number(expr, scope, ctx),
+    }
+}
+
+/// `n!`, or `n!!` for a double factorial, the product of every second number down from `n`.
+fn factorial<N: Numeric>(op: &TokenKind, value: &Quantity<N>, ctx: &Context) -> Result<Quantity<N>, CalcError> {
+    let n = value.integer(op.symbol(), ctx)?;
+    if n.is_negative() {
+        return Err(CalcError::domain(format!("Factorial of a negative number, {}", n)));
+    }
+    let step = if *op == TokenKind::DoubleFactorial { 2 } else { 1 };
+    let (mut result, mut k) = (BigInt::one(), n.to_u64().unwrap_or(u64::MAX));
+    while k > 1 {
+        result = &result * &BigInt::from(k as i128);
+        // stop early, the numbers only grow
+        if result.digits() > ctx.max_digits {
+            return Err(CalcError::Overflow {
+                message: format!("Result has more than {} digits", ctx.max_digits),
+                span: None
+            });
+        }
+        k -= step;
+    }
+    Quantity::from_integer(result, ctx)
+}
+
+/// `lhs op rhs` for a bitwise operator or a shift `op`, on plain integers.
+fn bitwise<N: Numeric>(
+    op: &TokenKind, lhs: &Quantity<N>, rhs: &Quantity<N>, ctx: &Context
+) -> Result<Quantity<N>, CalcError> {
+    let (a, b) = (&lhs.integer(op.symbol(), ctx)?, &rhs.integer(op.symbol(), ctx)?);
+    let shift = || b.to_u64().and_then(|n| u32::try_from(n).ok());
+    let limit = ctx.max_digits as f64;
+    let result = match op {
+        TokenKind::BitAnd => a & b,
+        TokenKind::BitOr => a | b,
+        TokenKind::BitXor => a ^ b,
+        _ if b.is_negative() => return Err(CalcError::domain(format!("Cannot shift by a negative number, {}", b))),
+        TokenKind::ShiftRight => a >> shift().unwrap_or(u32::MAX),
+        // estimate first, so that we never build a number far above the limit
+        _ => match shift().filter(|n| a.is_zero() || a.approx_log10() + *n as f64 * 2f64.log10() <= limit) {
+            Some(n) => a << n,
+            None => return Err(CalcError::Overflow {
+                message: format!("Result has more than {} digits", ctx.max_digits),
+                span: None
+            }),
+        },
+    };
+    Quantity::from_integer(result, ctx)
+}
+
+/// `lhs op rhs` for a comparison `op`: numbers in any order, if their units measure the same thing,
+/// and booleans only for being equal or not.
+fn compare<N: Numeric>(op: &TokenKind, lhs: &Value<N>, rhs: &Value<N>, ctx: &Context) -> Result<bool, CalcError> {
+    let order = match (lhs, rhs) {
+        (Value::Boolean(a), Value::Boolean(b)) => match op {
+            TokenKind::Equal => return Ok(a == b),
+            TokenKind::NotEqual => return Ok(a != b),
+            _ => return Err(CalcError::wrong_type(format!("Booleans cannot be compared by {}", op.describe()))),
         },
+        (Value::Number(a), Value::Number(b)) => match op {
+            TokenKind::Equal => return a.equals(b, ctx),
+            TokenKind::NotEqual => return a.equals(b, ctx).map(|equal| !equal),
+            _ => a.compare(b, ctx)?.ok_or_else(|| CalcError::domain("Complex numbers cannot be compared"))?,
+        },
+        _ => return Err(CalcError::wrong_type("Cannot compare a number with a boolean")),
+    };
+    Ok(match op {
+        TokenKind::Less => order.is_lt(),
+        TokenKind::LessEqual => order.is_le(),
+        TokenKind::Greater => order.is_gt(),
+        _ => order.is_ge(),
+    })
+}
+
+/// Run the body of a user function with `args` bound to its parameters.
+/// Errors from the body point into its definition, so the caller moves them to the call.
+fn call<N: Numeric>(
+    name: &str, function: &Function, args: Vec<Value<N>>, scope: &Scope<N>, ctx: &Context
+) -> Result<Value<N>, CalcError> {
+    if args.len() != function.params.len() {
+        return Err(CalcError::argument(format!(
+            "'{}' takes {} argument(s), found {}", name, function.params.len(), args.len()
+        )));
+    }
+    if scope.depth >= ctx.max_depth {
+        return Err(CalcError::recursion(ctx.max_depth));
+    }
+    let locals = function.params.iter().map(String::as_str).zip(args).collect();
+    evaluate(&function.body, &Scope { env: scope.env, locals, depth: scope.depth + 1 }, ctx)
+}
+
+/// `Some((p, q))` if `expr` is a division of the integers `p` and `q`, with `q` not zero.
+fn fraction<N: Numeric>(
+    expr: &Expression, scope: &Scope<N>, ctx: &Context
+) -> Result<Option<(BigInt, BigInt)>, CalcError> {
+    if let ExprKind::Binary { op: TokenKind::Divide, left, right } = &expr.kind {
+        let numerator = number(left, scope, ctx)?.plain().and_then(N::to_integer);
+        let denominator = number(right, scope, ctx)?.plain().and_then(N::to_integer);
+        if let (Some(p), Some(q)) = (numerator, denominator) {
+            if !q.is_zero() { return Ok(Some((p, q))); }
+        }
+    }
+    Ok(None)
+}
+
+/// What `input` comes to, as an expression alone with the default settings.
+#[cfg(test)]
+fn calc(input: &str) -> Result<String, CalcError> {
+    use crate::{interpreter::{lexeme::get_tokens, parsing::Parser}, number::numeric::Number};
+    let mut parser = Parser::new(get_tokens(input)?);
+    eval(&parser.parse()?, &Environment::<Number>::new(), &Context::default()).map(|value| value.to_string())
+}
+
+#[test]
+fn test_eval() {
+    use crate::error::Span;
+    assert_eq!(calc("1 + 2 * 3").unwrap(), "7.0");
+    let err = calc("1 + 1 / (2 - 2)").unwrap_err();
+    assert!(matches!(err, CalcError::DivisionByZero { .. }));
+    assert_eq!(err.span(), Some(Span::new(8, 15)));
+    assert_eq!(calc("3 * 2 ^ -1").unwrap(), "1.5");
+    assert_eq!(calc("-2 ^ 2").unwrap(), "-4.0");
+    assert_eq!(calc("(-2) ^ 2").unwrap(), "4.0");
+    assert_eq!(calc("2 ^ 3 ^ 2").unwrap(), "512.0");
+    assert_eq!(calc("8 ^ (1 / 3)").unwrap(), "2.0");
+    assert_eq!(calc("(-27) ^ (2 / 3)").unwrap(), "9.0");
+    assert_eq!(calc("2 ^ 0.5").unwrap(), "1.4142135623730950488");
+    assert_eq!(calc("3 * (-4) ^ (1 / 2)").unwrap(), "6.0i");
+    let err = calc("3 * 0 ^ (-1 / 2)").unwrap_err();
+    assert!(matches!(err, CalcError::DivisionByZero { .. }));
+    assert_eq!(err.span(), Some(Span::new(4, 16)));
+    assert_eq!(
+        calc("99999999999 ^ 5").unwrap(),
+        "9999999999500000000009999999999900000000000499999999999.0"
+    );
+    assert!(matches!(calc("9 ^ 99999999"), Err(CalcError::Overflow { .. })));
+    assert_eq!(calc("2 * sqrt(16) + abs(-1)").unwrap(), "9.0");
+    assert_eq!(calc("cos(pi)").unwrap(), "-1.0");
+    assert_eq!(calc("log(2, 1024)").unwrap(), "10.0");
+    assert_eq!(calc("1 + sqrt(1 - 5)").unwrap(), "1.0 + 2.0i");
+    let err = calc("1 + asin(1 + 1)").unwrap_err();
+    assert!(matches!(err, CalcError::Domain { .. }));
+    assert_eq!(err.span(), Some(Span::new(4, 15)));
+    let err = calc("1 + foo(2)").unwrap_err();
+    assert!(matches!(err, CalcError::Undefined { .. }));
+    assert_eq!(err.span(), Some(Span::new(4, 10)));
+    assert!(matches!(calc("sin(1, 2)"), Err(CalcError::Argument { .. })));
+}
+
+#[test]
+fn test_eval_unit() {
+    use crate::error::Span;
+    assert_eq!(calc("3 m * 4 s^-1").unwrap(), "12.0 m/s");
+    assert_eq!(calc("5 km to mi").unwrap(), "3.10685596118666984809 mi");
+    assert_eq!(calc("100 kg * 9.81 m/s^2 in N").unwrap(), "981.0 N");
+    assert_eq!(calc("1 km + 300 m").unwrap(), "1.3 km");
+    assert_eq!(calc("3 km / 1 m").unwrap(), "3000.0");
+    assert_eq!(calc("2 * km").unwrap(), "2.0 km");
+    assert_eq!(calc("sqrt(16 m^2)").unwrap(), "4.0 m");
+    assert_eq!(calc("sin(90 deg)").unwrap(), "1.0");
+    assert_eq!(calc("(8 m^3) ^ (1 / 3)").unwrap(), "2.0 m");
+    let err = calc("1 m + 1 s").unwrap_err();
+    assert!(matches!(err, CalcError::Dimension { .. }));
+    assert_eq!(err.message(), "Cannot add 'm' and 's'");
+    assert_eq!(err.span(), Some(Span::new(0, 9)));
+    let err = calc("5 km to s").unwrap_err();
+    assert_eq!(err.message(), "Cannot convert 'km' to 's'");
+    assert!(matches!(calc("2 ^ (1 m)"), Err(CalcError::Dimension { .. })));
+    assert!(matches!(calc("(2 m) ^ 0.5"), Err(CalcError::Dimension { .. })));
+}
+
+#[test]
+fn test_eval_integer() {
+    use crate::error::Span;
+    assert_eq!(calc("7 // 2 + -7 // 2").unwrap(), "-1.0");
+    assert_eq!(calc("-7 % 3").unwrap(), "2.0");
+    assert_eq!(calc("7 mod -3").unwrap(), "-2.0");
+    assert_eq!(calc("5.5 % 2").unwrap(), "1.5");
+    // exact, where the quotient would round up to 3
+    assert_eq!(calc("2.99999999999999999999999 // 1").unwrap(), "2.0");
+    assert_eq!(calc("1 h // 25 min").unwrap(), "2.0");
+    assert_eq!(calc("1 m mod 30 cm").unwrap(), "0.1 m");
+    assert_eq!(calc("12 & 10 | 1 xor 3").unwrap(), "10.0");
+    assert_eq!(calc("~5 & 0xFF").unwrap(), "250.0");
+    assert_eq!(calc("1 + 1 << 2 + 1").unwrap(), "16.0");
+    assert_eq!(calc("-7 >> 1").unwrap(), "-4.0");
+    assert_eq!(calc("6 & 3 == 2").unwrap(), "true");
+    let err = calc("1 + 7 % (2 - 2)").unwrap_err();
+    assert!(matches!(err, CalcError::DivisionByZero { .. }));
+    assert_eq!(err.span(), Some(Span::new(8, 15)));
+    let err = calc("2.5 & 1").unwrap_err();
+    assert_eq!(err.message(), "An operand of '&' must be an integer, found 2.5");
+    assert_eq!(err.span(), Some(Span::new(0, 7)));
+    assert!(matches!(calc("~0.5"), Err(CalcError::Domain { .. })));
+    assert!(matches!(calc("1 << -1"), Err(CalcError::Domain { .. })));
+    assert!(matches!(calc("1 << 99999999"), Err(CalcError::Overflow { .. })));
+    assert!(matches!(calc("3 m | 1"), Err(CalcError::Dimension { .. })));
+    assert!(matches!(calc("i % 2"), Err(CalcError::Domain { .. })));
+}
+
+#[test]
+fn test_eval_postfix() {
+    use crate::error::Span;
+    assert_eq!(calc("0! + 1!").unwrap(), "2.0");
+    assert_eq!(calc("25!").unwrap(), "15511210043330985984000000.0");
+    assert_eq!(calc("7!! + 8!! + 0!!").unwrap(), "490.0");
+    assert_eq!(calc("3!!!").unwrap(), "6.0");
+    assert_eq!(calc("2 ^ 3! - 3!^2").unwrap(), "28.0");
+    assert_eq!(calc("200 * 15%").unwrap(), "30.00");
+    assert_eq!(calc("(1 + 8%) * 250 kg").unwrap(), "270.00 kg");
+    assert_eq!(calc("10 % 4 + 10%").unwrap(), "2.1");
+    assert_eq!(calc("1/3%").unwrap(), "33.33333333333333333333");
+    let err = calc("1 + (-3)!").unwrap_err();
+    assert_eq!(err.message(), "Factorial of a negative number, -3");
+    assert_eq!(err.span(), Some(Span::new(4, 9)));
+    assert!(matches!(calc("2.5!"), Err(CalcError::Domain { .. })));
+    assert!(matches!(calc("3 m!"), Err(CalcError::Dimension { .. })));
+    assert!(matches!(calc("5000!"), Err(CalcError::Overflow { .. })));
+    assert!(matches!(calc("99999999999999999999!!"), Err(CalcError::Overflow { .. })));
+    assert!(matches!(calc("true%"), Err(CalcError::Type { .. })));
+    assert_eq!(calc("~0.5").unwrap_err().span(), Some(Span::new(0, 4)));
+}
+
+#[test]
+fn test_implicit_multiplication() {
+    assert_eq!(calc("2(3 + 4) + (1 + 2)(3 + 4)").unwrap(), "35.0");
+    assert_eq!(calc("1/2pi").unwrap(), "0.15915494309189533577");
+    assert_eq!(calc("4 sqrt(4) (3!)").unwrap(), "48.0");
+    assert_eq!(calc("2 (3 km) to m").unwrap(), "6000.0 m");
+    assert!(matches!(calc("2 (1 < 2)"), Err(CalcError::Type { .. })));
+}
+
+#[test]
+fn test_eval_logic() {
+    use crate::error::Span;
+    assert_eq!(calc("1 + 2 >= 3").unwrap(), "true");
+    assert_eq!(calc("0.1 + 0.2 == 0.3").unwrap(), "true");
+    assert_eq!(calc("1 km == 1000 m && 1 mi > 1 km").unwrap(), "true");
+    assert_eq!(calc("2 != 2 || !(1 < 2)").unwrap(), "false");
+    assert_eq!(calc("true == (1 > 0)").unwrap(), "true");
+    assert_eq!(calc("if 2 > 1 then 10 else 1 / 0").unwrap(), "10.0");
+    assert_eq!(calc("1 > 2 ? 1 / 0 : 3 > 4 ? 5 : 6").unwrap(), "6.0");
+    // the right side is not evaluated once the left one decides
+    assert_eq!(calc("false && 1 / 0 > 1").unwrap(), "false");
+    assert_eq!(calc("true || x").unwrap(), "true");
+    let err = calc("1 + (2 > 1)").unwrap_err();
+    assert!(matches!(err, CalcError::Type { .. }));
+    assert_eq!(err.span(), Some(Span::new(4, 11)));
+    let err = calc("if 1 then 2 else 3").unwrap_err();
+    assert_eq!(err.message(), "Expected a boolean, found a number");
+    assert_eq!(err.span(), Some(Span::new(3, 4)));
+    assert!(matches!(calc("true < false"), Err(CalcError::Type { .. })));
+    assert!(matches!(calc("1 == true"), Err(CalcError::Type { .. })));
+    assert!(matches!(calc("sqrt(true)"), Err(CalcError::Type { .. })));
+    assert!(matches!(calc("1 m < 1 s"), Err(CalcError::Dimension { .. })));
+    assert!(matches!(calc("i > 0"), Err(CalcError::Domain { .. })));
+}
+
+#[test]
+fn test_execute() {
+    use crate::interpreter::{lexeme::get_tokens, parsing::Parser};
+    let mut env: Environment = Environment::new();
+    let mut run = |input: &str| {
+        let statement = Parser::new(get_tokens(input)?).parse_statement()?;
+        execute(&statement, &mut env, &Context::default()).map(|value| value.unwrap().to_string())
+    };
+    assert_eq!(run("rate = 0.07").unwrap(), "0.07");
+    assert_eq!(run("price = 200").unwrap(), "200.0");
+    assert_eq!(run("price * (1 + rate)").unwrap(), "214.00");
+    assert_eq!(run("rate = rate + 0.01").unwrap(), "0.08");
+    assert_eq!(run("price * (1 + rate)").unwrap(), "216.00");
+    let err = run("2 * tax").unwrap_err();
+    assert!(matches!(err, CalcError::Undefined { .. }));
+    assert_eq!(err.span(), Some(crate::error::Span::new(4, 7)));
+    // a failed assignment leaves the old value
+    assert!(run("rate = 1 / 0").is_err());
+    assert_eq!(run("rate").unwrap(), "0.08");
+}
+
+#[test]
+fn test_execute_function() {
+    use crate::{error::Span, interpreter::{lexeme::get_tokens, parsing::Parser}};
+    let mut env: Environment = Environment::new();
+    let mut run = |input: &str| {
+        let statement = Parser::new(get_tokens(input)?).parse_statement()?;
+        execute(&statement, &mut env, &Context::default()).map(|value| value.map(|v| v.to_string()))
+    };
+    assert_eq!(run("f(x, y) = x ^ 2 + y").unwrap(), None);
+    assert_eq!(run("f(3, 1)").unwrap().unwrap(), "10.0");
+    // parameters hide variables, and do not leak into other functions
+    assert!(run("x = 100").is_ok());
+    assert!(run("g(x) = h(1) + x").is_ok());
+    assert!(run("h(y) = x + y").is_ok());
+    assert_eq!(run("g(5)").unwrap().unwrap(), "106.0");
+    assert_eq!(run("f(x, 0)").unwrap().unwrap(), "10000.0");
+    // a user function hides a built-in one
+    assert!(run("sqrt(x) = x / 2").is_ok());
+    assert_eq!(run("sqrt(16)").unwrap().unwrap(), "8.0");
+
+    let err = run("f(1)").unwrap_err();
+    assert!(matches!(err, CalcError::Argument { .. }));
+    assert_eq!(err.span(), Some(Span::new(0, 4)));
+    assert!(run("loop(n) = loop(n + 1)").is_ok());
+    let err = run("1 + loop(0)").unwrap_err();
+    assert!(matches!(err, CalcError::Recursion { .. }));
+    assert_eq!(err.span(), Some(Span::new(4, 11)));
+    assert!(run("fact(n) = if n <= 1 then 1 else n * fact(n - 1)").is_ok());
+    assert_eq!(run("fact(20)").unwrap().unwrap(), "2432902008176640000.0");
+    assert!(run("either(a, b) = a || b").is_ok());
+    assert_eq!(run("either(false, 1 < 2)").unwrap().unwrap(), "true");
+    assert!(run("bad(x) = x / 0").is_ok());
+    let err = run("bad(1) * 2").unwrap_err();
+    assert!(matches!(err, CalcError::DivisionByZero { .. }));
+    assert_eq!(err.span(), Some(Span::new(0, 6)));
+}
+
+#[test]
+fn test_execute_operator() {
+    use crate::{error::Span, interpreter::{lexeme::Tokenizer, operator::Associativity, parsing::Parser}};
+    let run = |input: &str, env: &mut Environment| {
+        let tokens = Tokenizer::new(input).symbols(env.operators().symbol_chars()).tokens()?;
+        let statement = Parser::with_operators(tokens, env.operators().clone()).parse_statement()?;
+        execute(&statement, env, &Context::default()).map(|value| value.map(|v| v.to_string()))
+    };
+    let mut env: Environment = Environment::new();
+    env.operators_mut().infix("<>", 95, Associativity::Left, "avg").unwrap();
+    env.operators_mut().postfix("squared", 140, "sq").unwrap();
+    env.operators_mut().prefix("√", 120, "sqrt").unwrap();
+    // the function is looked up when the operator is evaluated
+    let err = run("1 <> 2", &mut env).unwrap_err();
+    assert!(matches!(err, CalcError::Undefined { .. }));
+    assert_eq!(err.span(), Some(Span::new(0, 6)));
+    assert!(run("avg(a, b) = (a + b) / 2", &mut env).is_ok());
+    assert!(run("sq(x) = x * x", &mut env).is_ok());
+    assert_eq!(run("1 <> 2 * 3", &mut env).unwrap().unwrap(), "3.5");
+    assert_eq!(run("1<>3 squared + 1", &mut env).unwrap().unwrap(), "6.0");
+    assert_eq!(run("√16 squared + 1", &mut env).unwrap().unwrap(), "17.0");
+}
+
+#[test]
+fn test_execute_rational() {
+    use crate::{interpreter::{lexeme::get_tokens, parsing::Parser}, number::context::Arithmetic};
+    let ctx = Context { arithmetic: Arithmetic::Rational, ..Context::default() };
+    let mut env: Environment = Environment::new();
+    let mut run = |input: &str| {
+        let statement = Parser::new(get_tokens(input)?).parse_statement()?;
+        execute(&statement, &mut env, &ctx).map(|value| value.unwrap().to_string())
+    };
+    assert_eq!(run("1/3 + 1/6").unwrap(), "1/2");
+    assert_eq!(run("x = 0.75").unwrap(), "3/4");
+    assert_eq!(run("(x - 1) ^ -3").unwrap(), "-64");
+    assert_eq!(run("(4/9) ^ (1/2) * 3").unwrap(), "2");
+    assert_eq!(run("abs(-x) + floor(7/2) + min(x, 2/3)").unwrap(), "53/12");
+    // irrational results are decimals, and so is anything computed from them
+    assert_eq!(run("sqrt(2)").unwrap(), "1.4142135623730950488");
+    assert_eq!(run("pi * 0").unwrap(), "0.0");
+    assert_eq!(run("2 ^ (1/2) / 2").unwrap(), "0.7071067811865475244");
+    assert!(matches!(run("x / (x - 3/4)"), Err(CalcError::DivisionByZero { .. })));
+}
+
+#[test]
+fn test_eval_numeric() {
+    use crate::{interpreter::{lexeme::get_tokens, parsing::Parser}, number::decimal::Decimal};
+    fn calc<N: Numeric>(input: &str) -> Result<N, CalcError> {
+        let mut parser = Parser::new(get_tokens(input)?);
+        eval(&parser.parse()?, &Environment::new(), &Context::default())?.into_number().map(Quantity::into_value)
     }
-}
\ No newline at end of file
+    assert_eq!(calc::<Decimal>("1 / 4 + 2 ^ 3").unwrap().to_string(), "8.25");
+    assert_eq!(calc::<f64>("1 / 4 + 2 ^ 3").unwrap(), 8.25);
+    assert!((calc::<f64>("(-8) ^ (1 / 3) * max(1, 2.5)").unwrap() + 5.0).abs() < 1e-12);
+    assert!((calc::<f64>("sin(pi / 6)").unwrap() - 0.5).abs() < 1e-15);
+    assert!(matches!(calc::<f64>("1 / (2 - 2)"), Err(CalcError::DivisionByZero { .. })));
+    assert!(matches!(calc::<f64>("sqrt(-1)"), Err(CalcError::Domain { .. })));
+    // integers round every result, by half-even by default
+    assert_eq!(calc::<BigInt>("7 / 2 + 2 ^ 70").unwrap().to_string(), "1180591620717411303428");
+    assert_eq!(calc::<BigInt>("sqrt(17) * 10 / 4").unwrap().to_string(), "10");
+}
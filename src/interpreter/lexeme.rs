@@ -1,21 +1,98 @@
 use std::fmt::Display;
 
-const END: char = '\0';
+use crate::{error::{CalcError, Span}, number::context::Dialect};
 
 #[derive(Debug)]
 #[derive(Clone)]
-pub enum Token {
+pub enum TokenKind {
     Number(String),
+    Identifier(String),
+    /// A character of an operator that was added to the `OperatorTable`, e.g. `@`.
+    Operator(String),
     Plus, Minus, Multiply, Divide,
     Power,
+    IntegerDivide, Modulo,
+    BitAnd, BitOr, BitXor, BitNot, ShiftLeft, ShiftRight,
     OpenParenthesis, CloseParenthesis,
+    Comma,
+    /// `;` between arguments in `Dialect::Spreadsheet`, where it stands for `,`.
+    Separator,
+    Assign,
+    Equal, NotEqual, Less, LessEqual, Greater, GreaterEqual,
+    And, Or, Not,
+    Question, Colon,
+    /// `;` or a line break, between statements.
+    Semicolon, Newline,
+    /// Postfix operators, which the parser makes of `!`, `!!` and `%` after an operand.
+    Factorial, DoubleFactorial, Percent,
     Fin
 }
 
-impl PartialEq for Token {
+impl TokenKind {
+    /// How the token is called in error messages.
+    pub fn describe(&self) -> String {
+        match self {
+            TokenKind::Number(n) => format!("number {}", n),
+            TokenKind::Identifier(name) => format!("name '{}'", name),
+            TokenKind::Fin => "end of input".to_string(),
+            TokenKind::Newline => "end of line".to_string(),
+            other => format!("'{}'", other.symbol()),
+        }
+    }
+
+    pub fn is_comparison(&self) -> bool {
+        matches!(self, TokenKind::Equal | TokenKind::NotEqual | TokenKind::Less | TokenKind::LessEqual
+            | TokenKind::Greater | TokenKind::GreaterEqual)
+    }
+
+    /// How the token is written, e.g. `+`.
+    pub fn symbol(&self) -> &str {
+        match self {
+            TokenKind::Number(n) | TokenKind::Identifier(n) | TokenKind::Operator(n) => n,
+            TokenKind::Fin => "",
+            TokenKind::Plus => "+",
+            TokenKind::Minus => "-",
+            TokenKind::Multiply => "*",
+            TokenKind::Divide => "/",
+            TokenKind::Power => "^",
+            TokenKind::IntegerDivide => "//",
+            TokenKind::Modulo => "%",
+            TokenKind::BitAnd => "&",
+            TokenKind::BitOr => "|",
+            TokenKind::BitXor => "xor",
+            TokenKind::BitNot => "~",
+            TokenKind::ShiftLeft => "<<",
+            TokenKind::ShiftRight => ">>",
+            TokenKind::OpenParenthesis => "(",
+            TokenKind::CloseParenthesis => ")",
+            TokenKind::Comma => ",",
+            TokenKind::Separator => ";",
+            TokenKind::Assign => "=",
+            TokenKind::Equal => "==",
+            TokenKind::NotEqual => "!=",
+            TokenKind::Less => "<",
+            TokenKind::LessEqual => "<=",
+            TokenKind::Greater => ">",
+            TokenKind::GreaterEqual => ">=",
+            TokenKind::And => "&&",
+            TokenKind::Or => "||",
+            TokenKind::Not => "!",
+            TokenKind::Question => "?",
+            TokenKind::Colon => ":",
+            TokenKind::Semicolon => ";",
+            TokenKind::Newline => "\n",
+            TokenKind::Factorial => "!",
+            TokenKind::DoubleFactorial => "!!",
+            TokenKind::Percent => "%",
+        }
+    }
+}
+
+impl PartialEq for TokenKind {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (Self::Number(_), Self::Number(_)) => true,
+            (Self::Identifier(_), Self::Identifier(_)) => true,
             _ => core::mem::discriminant(self) == core::mem::discriminant(other),
         }
     }
@@ -23,134 +100,303 @@ impl PartialEq for Token {
 
 #[test]
 fn test_token_partial_eq() {
-    assert_eq!(Token::Number(0.0.to_string()), Token::Number(0.0.to_string()));
-    assert!(Token::Number(114.514.to_string()) == Token::Number(233.3.to_string()));
-    assert_ne!(Token::Number(0.0.to_string()), Token::Fin);
+    assert_eq!(TokenKind::Number(0.0.to_string()), TokenKind::Number(0.0.to_string()));
+    assert!(TokenKind::Number(114.514.to_string()) == TokenKind::Number(233.3.to_string()));
+    assert_ne!(TokenKind::Number(0.0.to_string()), TokenKind::Fin);
 }
 
-impl Display for Token {
+impl Display for TokenKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let display = match self {
-            Token::Number(n) => format!("Number(\"{}\")", n),
+            TokenKind::Number(n) => format!("Number(\"{}\")", n),
+            TokenKind::Identifier(name) => format!("Identifier(\"{}\")", name),
             other => format!("{:#?}", other),
         };
         write!(f, "{}", display)
     }
 }
 
+/// A token together with where it was found in the input.
+#[derive(Debug, Clone)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Span
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.kind)
+    }
+}
+
 #[test]
 fn test_token_display() {
-    println!("{}", Token::Number("114514".to_string()));
-    println!("{}", Token::Plus)
+    println!("{}", TokenKind::Number("114514".to_string()));
+    println!("{}", Token { kind: TokenKind::Plus, span: Span::new(0, 1) })
 }
 
 #[derive(Clone)]
 pub struct Tokenizer {
-    text: String,
+    /// The characters of the input, each with its byte offset.
+    chars: Vec<(usize, char)>,
+    /// The byte length of the input.
+    end: usize,
     position: usize,
-    ch: char
+    full_width: bool,
+    /// More characters that are read as operators.
+    symbols: Vec<char>,
+    dialect: Dialect
 }
 
 impl Tokenizer {
     pub fn new(input: &str) -> Self {
-        let first_char = *input.as_bytes().first().unwrap() as char;
-        Self {
-            text: input.to_string(), position: 0, ch: first_char
-        }
+        let chars = input.char_indices().collect();
+        Self { chars, end: input.len(), position: 0, full_width: true, symbols: vec![], dialect: Dialect::default() }
+    }
+
+    /// Whether full-width forms, e.g. `１２＋３`, are read as their ASCII counterparts (the default).
+    pub fn full_width(mut self, normalize: bool) -> Self {
+        self.full_width = normalize;
+        self
+    }
+
+    /// Read each of `symbols` as an operator, unless it already is part of one, e.g. `@` for `a @ b`.
+    pub fn symbols(mut self, symbols: Vec<char>) -> Self {
+        self.symbols = symbols;
+        self
+    }
+
+    /// The syntax of the input, e.g. `**` for `^` in `Dialect::C`, `Standard` by default.
+    pub fn dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    /// The current character, or `None` at the end of the input.
+    fn ch(&self) -> Option<char> {
+        self.peek(0)
+    }
+
+    /// The character `n` after the current one.
+    fn peek(&self, n: usize) -> Option<char> {
+        let (_, ch) = *self.chars.get(self.position + n)?;
+        Some(if self.full_width { half_width(ch) } else { ch })
+    }
+
+    /// An error about the current character.
+    fn error_here(&self, message: String) -> CalcError {
+        let start = self.offset();
+        let len = self.chars.get(self.position).map_or(0, |(_, ch)| ch.len_utf8());
+        CalcError::lex(message, Span::new(start, start + len))
+    }
+
+    /// The byte offset of the current character.
+    fn offset(&self) -> usize {
+        self.chars.get(self.position).map_or(self.end, |(offset, _)| *offset)
     }
 
     fn advance(&mut self) {
         self.position += 1;
-        let current_chars = self.text.as_bytes();
-        if self.is_end() {
-            self.ch = END  // I think it's not a good design
+    }
+
+    pub(crate) fn next_token(&mut self) -> Result<Token, CalcError> {
+        while self.ch().is_some_and(|c| c.is_whitespace() && c != '\n') {
+            self.advance();
+        }
+        let start = self.offset();
+        let kind = self.next_kind()?;
+        Ok(Token { kind, span: Span::new(start, self.offset()) })
+    }
+
+    fn next_kind(&mut self) -> Result<TokenKind, CalcError> {
+        let kind = match self.ch() {
+            None => return Ok(TokenKind::Fin),
+            Some(c) if is_digit(&c) => {
+                return Ok(TokenKind::Number(self.read_number()?));
+            },
+            Some(c) if is_name_start(&c) => {
+                return Ok(TokenKind::Identifier(self.read_name()));
+            },
+            Some('(') => TokenKind::OpenParenthesis,
+            Some(')') => TokenKind::CloseParenthesis,
+            Some(',') => TokenKind::Comma,
+            Some(';') if self.dialect == Dialect::Spreadsheet => TokenKind::Separator,
+            Some('^') if self.dialect == Dialect::C => TokenKind::BitXor,
+            Some('^') => TokenKind::Power,
+            Some('*') if self.dialect == Dialect::C && self.peek(1) == Some('*') => {
+                self.advance();
+                TokenKind::Power
+            },
+            Some('*' | '×' | '·') => TokenKind::Multiply,
+            Some('÷') => TokenKind::Divide,
+            Some('%') => TokenKind::Modulo,
+            Some('~') => TokenKind::BitNot,
+            Some('+') => TokenKind::Plus,
+            Some('-' | '−') => TokenKind::Minus,
+            Some('≠') => TokenKind::NotEqual,
+            Some('≤') => TokenKind::LessEqual,
+            Some('≥') => TokenKind::GreaterEqual,
+            Some('?') => TokenKind::Question,
+            Some(':') => TokenKind::Colon,
+            Some(';') => TokenKind::Semicolon,
+            Some('\n') => TokenKind::Newline,
+            Some(c @ ('/' | '=' | '!' | '<' | '>' | '&' | '|')) => return self.read_operator(c),
+            Some(c) if self.symbols.contains(&c) => TokenKind::Operator(c.to_string()),
+            Some(c) => return Err(self.error_here(format!("Unexpected character '{}'", c))),
+        };
+        self.advance();
+        Ok(kind)
+    }
+
+    /// An operator that may take two characters, e.g. `<` or `<=`.
+    fn read_operator(&mut self, first: char) -> Result<TokenKind, CalcError> {
+        let kind = match (first, self.peek(1)) {
+            ('/', Some('/')) => TokenKind::IntegerDivide,
+            ('<', Some('<')) => TokenKind::ShiftLeft,
+            ('>', Some('>')) => TokenKind::ShiftRight,
+            ('=', Some('=')) => TokenKind::Equal,
+            ('!', Some('=')) => TokenKind::NotEqual,
+            ('<', Some('=')) => TokenKind::LessEqual,
+            ('>', Some('=')) => TokenKind::GreaterEqual,
+            ('&', Some('&')) => TokenKind::And,
+            ('|', Some('|')) => TokenKind::Or,
+            ('=', _) => TokenKind::Assign,
+            ('!', _) => TokenKind::Not,
+            ('<', _) => TokenKind::Less,
+            ('>', _) => TokenKind::Greater,
+            ('&', _) => TokenKind::BitAnd,
+            ('|', _) => TokenKind::BitOr,
+            _ => TokenKind::Divide,
+        };
+        for _ in 0..kind.symbol().len() {
+            self.advance();
+        }
+        Ok(kind)
+    }
+
+    /// A number literal, as it is written: `12.5`, `1_000`, `1.5e-7`, `0x1F`, `0o17` or `0b1011`,
+    /// or an imaginary one like `2i` or `0.5e3i`.
+    fn read_number(&mut self) -> Result<String, CalcError> {
+        let start = self.offset();
+        let mut result = String::new();
+        let radix = match (self.ch(), self.peek(1)) {
+            (Some('0'), Some('x' | 'X')) => Some((16, "hexadecimal")),
+            (Some('0'), Some('o' | 'O')) => Some((8, "octal")),
+            (Some('0'), Some('b' | 'B')) => Some((2, "binary")),
+            _ => None,
+        };
+        if let Some((radix, name)) = radix {
+            for _ in 0..2 {
+                result.extend(self.ch());
+                self.advance();
+            }
+            if !self.read_digits(&mut result, radix)? {
+                let span = Span::new(start, self.offset());
+                return Err(CalcError::lex(format!("Expected {} digits after '{}'", name, result), span));
+            }
+            if let Some(c) = self.ch().filter(|c| c.is_alphanumeric() || *c == '.') {
+                return Err(self.error_here(format!("Invalid digit '{}' in {} number", c, name)));
+            }
+            return Ok(result);
+        }
+
+        let int_digits = self.read_digits(&mut result, 10)?;
+        let mut frac_digits = false;
+        if self.ch() == Some('.') {
+            result.push('.');
+            self.advance();
+            frac_digits = self.read_digits(&mut result, 10)?;
         }
-        else {
-            self.ch = current_chars[self.position] as char;
+        if !int_digits && !frac_digits {
+            return Err(CalcError::lex("Expected digits around '.'", Span::new(start, self.offset())));
         }
+        if self.ch() == Some('.') {
+            return Err(self.error_here("A number cannot have a second '.'".to_string()));
+        }
+        // without digits, it is not an exponent: `2e` is 2 next to the constant `e`
+        let sign = usize::from(matches!(self.peek(1), Some('+' | '-')));
+        if matches!(self.ch(), Some('e' | 'E')) && self.peek(1 + sign).is_some_and(|c| c.is_ascii_digit()) {
+            for _ in 0..1 + sign {
+                result.extend(self.ch());
+                self.advance();
+            }
+            self.read_digits(&mut result, 10)?;
+            if self.ch() == Some('.') {
+                return Err(self.error_here("An exponent must be an integer".to_string()));
+            }
+        }
+        // an imaginary number, but `2in` is 2 next to the name `in`
+        if self.ch() == Some('i') && !self.peek(1).is_some_and(|c| is_name_start(&c) || c.is_alphanumeric()) {
+            result.push('i');
+            self.advance();
+        }
+        Ok(result)
     }
 
-    fn is_end(&self) -> bool {
-        if self.position >= self.text.len() { true } else { false }
-    }
-
-    pub(crate) fn next_token(&mut self) -> Token {
-        while self.ch != END {
-            match self.ch {
-                ' ' | '\t' | '\n' => {
-                    self.advance();
-                    continue;
-                },
-                '(' => {
-                    self.advance();
-                    return Token::OpenParenthesis;
-                },
-                ')' => {
-                    self.advance();
-                    return Token::CloseParenthesis;
-                },
-                c if is_digit(&c) => {
-                    return Token::Number(self.read_number());
-                },
-                '^' => {
-                    self.advance();
-                    return Token::Power;
-                },
-                '*' => {
-                    self.advance();
-                    return Token::Multiply;
-                },
-                '/' => {
-                    self.advance();
-                    return Token::Divide;
-                },
-                '+' => {
-                    self.advance();
-                    return Token::Plus;
-                },
-                '-' => {
-                    self.advance();
-                    return Token::Minus;
-                },
-                _ => panic!("Wrong in tokenizing")
+    /// Digits in `radix`, with single `_` between them, returning whether there was any.
+    fn read_digits(&mut self, result: &mut String, radix: u32) -> Result<bool, CalcError> {
+        let mut any = false;
+        loop {
+            match self.ch() {
+                Some(c) if c.is_digit(radix) => any = true,
+                Some('_') if any && self.peek(1).is_some_and(|c| c.is_digit(radix)) => { },
+                Some('_') => return Err(self.error_here("'_' must be between two digits".to_string())),
+                _ => return Ok(any),
             }
+            result.extend(self.ch());
+            self.advance();
         }
-        Token::Fin
     }
 
-    fn read_number(&mut self) -> String {
+    fn read_name(&mut self) -> String {
         let mut result = String::new();
-        while self.ch != END && is_digit(&self.ch) {
-            result += &self.ch.to_string();
+        while let Some(c) = self.ch().filter(|c| is_name_start(c) || c.is_alphanumeric()) {
+            result.push(c);
             self.advance();
         }
         result
     }
+
+    /// All tokens up to the end of the input, without `Fin`.
+    pub fn tokens(mut self) -> Result<Vec<Token>, CalcError> {
+        let mut tokens: Vec<Token> = vec![];
+        loop {  // repeat
+            let next_token = self.next_token()?;
+            if next_token.kind == TokenKind::Fin { break; }  // until
+            tokens.push(next_token);
+        }
+        Ok(tokens)
+    }
 }
 
+/// Whether a number starts with `ch`.
 fn is_digit(ch: &char) -> bool {
+    matches!(ch, '0'..='9' | '.')
+}
+
+fn is_name_start(ch: &char) -> bool {
+    ch.is_alphabetic() || *ch == '_' || *ch == '°'
+}
+
+/// The ASCII character of a full-width form, e.g. `1` for `１`, and a space for the ideographic space.
+fn half_width(ch: char) -> char {
     match ch {
-        '0'..='9' | '.' => true,
-        _ => false
+        '\u{FF01}'..='\u{FF5E}' => char::from_u32(ch as u32 - 0xFEE0).unwrap_or(ch),
+        '\u{3000}' => ' ',
+        _ => ch,
     }
 }
 
-pub fn get_tokens(input: &str) -> Vec<Token> {
-    let mut tokenizer = Tokenizer::new(input);
-    let mut tokens: Vec<Token> = vec![];
-    loop {  // repeat
-        let next_token = tokenizer.next_token();
-        if next_token == Token::Fin { break; }  // until
-        tokens.push(next_token);
-    }
-    tokens
+/// The tokens of `input` with the default settings.
+#[cfg(test)]
+pub fn get_tokens(input: &str) -> Result<Vec<Token>, CalcError> {
+    Tokenizer::new(input).tokens()
 }
 
-pub fn show_tokens(tokens: &Vec<Token>) -> String {
+pub fn show_tokens(tokens: &[Token]) -> String {
     let mut display = String::new();
     if tokens.is_empty() { return display; }
-    for i in 0..tokens.len() {
-        let token = &tokens[i];
+    for (i, token) in tokens.iter().enumerate() {
         display += &token.to_string();
         if i < tokens.len() - 1 {
             display += "\n";
@@ -161,11 +407,156 @@ pub fn show_tokens(tokens: &Vec<Token>) -> String {
 
 #[test]
 fn test() {
-    let tokens = get_tokens("114 + .514 - (19.19 * (-8)) / 10");
+    let tokens = get_tokens("114 + .514 - (19.19 * (-8)) / 10").unwrap();
     println!("{:#?}", tokens);
     println!("[\n{}\n]", show_tokens(&tokens));
 
-    let tokens = get_tokens(".1");
+    let tokens = get_tokens(".1").unwrap();
     println!("{:#?}", tokens);
     println!("[\n{}\n]", show_tokens(&tokens));
+}
+
+#[test]
+fn test_token_span() {
+    let tokens = get_tokens(" 12.5 *(3)").unwrap();
+    let spans: Vec<Span> = tokens.iter().map(|t| t.span).collect();
+    assert_eq!(spans, vec![
+        Span::new(1, 5), Span::new(6, 7), Span::new(7, 8), Span::new(8, 9), Span::new(9, 10)
+    ]);
+}
+
+#[test]
+fn test_token_identifier() {
+    let tokens = get_tokens("rate_2 = x1*.5").unwrap();
+    let kinds: Vec<String> = tokens.iter().map(|t| t.kind.describe()).collect();
+    assert_eq!(kinds, vec!["name 'rate_2'", "'='", "name 'x1'", "'*'", "number .5"]);
+    assert_eq!(tokens[0].span, Span::new(0, 6));
+    assert_eq!(tokens[2].span, Span::new(9, 11));
+}
+
+#[test]
+fn test_token_operator() {
+    let tokens = get_tokens("a<=b == !c&&d ||e ? x=1 : 2>3").unwrap();
+    let kinds: Vec<&str> = tokens.iter().map(|t| t.kind.symbol()).collect();
+    assert_eq!(kinds, vec![
+        "a", "<=", "b", "==", "!", "c", "&&", "d", "||", "e", "?", "x", "=", "1", ":", "2", ">", "3"
+    ]);
+    assert_eq!(tokens[1].span, Span::new(1, 3));
+    assert_eq!(tokens[4].span, Span::new(8, 9));
+    let tokens = get_tokens("7//2 % 3 & ~1|2<<3>>4/5").unwrap();
+    let kinds: Vec<&str> = tokens.iter().map(|t| t.kind.symbol()).collect();
+    assert_eq!(kinds, vec!["7", "//", "2", "%", "3", "&", "~", "1", "|", "2", "<<", "3", ">>", "4", "/", "5"]);
+    assert_eq!(tokens[1].span, Span::new(1, 3));
+}
+
+#[test]
+fn test_token_separator() {
+    let tokens = get_tokens("a = 1;\r\n\t a\n").unwrap();
+    let kinds: Vec<String> = tokens.iter().map(|t| t.kind.describe()).collect();
+    assert_eq!(kinds, vec!["name 'a'", "'='", "number 1", "';'", "end of line", "name 'a'", "end of line"]);
+    assert_eq!(tokens[4].span, Span::new(7, 8));
+}
+
+#[test]
+fn test_token_symbols() {
+    let tokens = Tokenizer::new("a @# b<>c").symbols(vec!['#', '@', '>']).tokens().unwrap();
+    let kinds: Vec<String> = tokens.iter().map(|t| t.kind.describe()).collect();
+    assert_eq!(kinds, vec!["name 'a'", "'@'", "'#'", "name 'b'", "'<'", "'>'", "name 'c'"]);
+    assert_eq!(tokens[2].span, Span::new(3, 4));
+    assert!(get_tokens("a @ b").is_err());
+}
+
+#[test]
+fn test_token_dialect() {
+    let describe = |input: &str, dialect: Dialect| -> Vec<String> {
+        let tokens = Tokenizer::new(input).dialect(dialect).tokens().unwrap();
+        tokens.iter().map(|t| t.kind.describe()).collect()
+    };
+    assert_eq!(describe("2**3 ^ 1", Dialect::C), vec!["number 2", "'^'", "number 3", "'xor'", "number 1"]);
+    assert_eq!(describe("2**3 ^ 1", Dialect::Standard), vec!["number 2", "'*'", "'*'", "number 3", "'^'", "number 1"]);
+    let expected = vec!["name 'f'", "'('", "number 1", "';'", "number 2", "')'"];
+    assert_eq!(describe("f(1; 2)", Dialect::Spreadsheet), expected);
+    let tokens = Tokenizer::new("x**2").dialect(Dialect::C).tokens().unwrap();
+    assert_eq!(tokens[1].span, Span::new(1, 3));
+}
+
+#[test]
+fn test_token_unicode() {
+    let tokens = get_tokens("2×π − 6÷3·x").unwrap();
+    let kinds: Vec<String> = tokens.iter().map(|t| t.kind.describe()).collect();
+    assert_eq!(kinds, vec![
+        "number 2", "'*'", "name 'π'", "'-'", "number 6", "'/'", "number 3", "'*'", "name 'x'"
+    ]);
+    let spans: Vec<Span> = tokens.iter().map(|t| t.span).collect();
+    assert_eq!(spans[..4], [Span::new(0, 1), Span::new(1, 3), Span::new(3, 5), Span::new(6, 9)]);
+
+    let tokens = get_tokens("x≠1 ≤ 2≥y").unwrap();
+    let kinds: Vec<String> = tokens.iter().map(|t| t.kind.describe()).collect();
+    assert_eq!(kinds, vec!["name 'x'", "'!='", "number 1", "'<='", "number 2", "'>='", "name 'y'"]);
+
+    let tokens = get_tokens("90° + 5 µm").unwrap();
+    let kinds: Vec<String> = tokens.iter().map(|t| t.kind.describe()).collect();
+    assert_eq!(kinds, vec!["number 90", "name '°'", "'+'", "number 5", "name 'µm'"]);
+
+    let tokens = get_tokens("１２．５　＋（３）").unwrap();
+    let kinds: Vec<String> = tokens.iter().map(|t| t.kind.describe()).collect();
+    assert_eq!(kinds, vec!["number 12.5", "'+'", "'('", "number 3", "')'"]);
+    assert_eq!(tokens[0].span, Span::new(0, 12));
+    let err = Tokenizer::new("１２").full_width(false).tokens().unwrap_err();
+    assert_eq!(err.span(), Some(Span::new(0, 3)));
+}
+
+#[test]
+fn test_token_number() {
+    let examples = vec![
+        ("1.5e-7", "1.5e-7"), ("6.02E23", "6.02E23"), ("0x1F", "0x1F"), ("0o17", "0o17"), ("0b1011", "0b1011"),
+        ("1_000_000", "1_000_000"), ("5.", "5."), (".5", ".5"), ("１２３", "123"),
+        ("2i", "2i"), ("1.5e3i", "1.5e3i"),
+    ];
+    for (input, expected) in examples {
+        let tokens = get_tokens(input).unwrap();
+        assert_eq!(tokens.len(), 1, "{}", input);
+        assert_eq!(tokens[0].kind.describe(), format!("number {}", expected));
+    }
+    // an `e` without digits is a name
+    let tokens = get_tokens("2e + 2E-x").unwrap();
+    let kinds: Vec<String> = tokens.iter().map(|t| t.kind.describe()).collect();
+    assert_eq!(kinds, vec!["number 2", "name 'e'", "'+'", "number 2", "name 'E'", "'-'", "name 'x'"]);
+    let tokens = get_tokens("2in").unwrap();
+    let kinds: Vec<String> = tokens.iter().map(|t| t.kind.describe()).collect();
+    assert_eq!(kinds, vec!["number 2", "name 'in'"]);
+
+    let errors = vec![
+        ("1.2.3", Span::new(3, 4), "A number cannot have a second '.'"),
+        ("0x", Span::new(0, 2), "Expected hexadecimal digits after '0x'"),
+        ("1 + 0b102", Span::new(8, 9), "Invalid digit '2' in binary number"),
+        ("0o8", Span::new(0, 2), "Expected octal digits after '0o'"),
+        ("1__000", Span::new(1, 2), "'_' must be between two digits"),
+        ("1000_", Span::new(4, 5), "'_' must be between two digits"),
+        ("1._5", Span::new(2, 3), "'_' must be between two digits"),
+        ("1e5.5", Span::new(3, 4), "An exponent must be an integer"),
+        (". 5", Span::new(0, 1), "Expected digits around '.'"),
+    ];
+    for (input, span, message) in errors {
+        let err = get_tokens(input).unwrap_err();
+        assert!(matches!(err, CalcError::Lex { .. }), "{}", input);
+        assert_eq!((err.span(), err.message()), (Some(span), message), "{}", input);
+    }
+}
+
+#[test]
+fn test_lex_error() {
+    assert!(get_tokens("").unwrap().is_empty());
+    assert!(get_tokens(" \t\r").unwrap().is_empty());
+    assert!(matches!(get_tokens(" \t\r\n").unwrap()[..], [Token { kind: TokenKind::Newline, .. }]));
+    match get_tokens("3 + €") {
+        Err(CalcError::Lex { span, .. }) => assert_eq!(span, Some(Span::new(4, 7))),
+        other => panic!("unexpected {:?}", other),
+    }
+    // a NUL is a character like any other, not the end of the input
+    assert!(get_tokens("1\0 + 2").is_err());
+    match get_tokens("1 + $") {
+        Err(CalcError::Lex { span, .. }) => assert_eq!(span, Some(Span::new(4, 5))),
+        other => panic!("unexpected {:?}", other),
+    }
 }
\ No newline at end of file
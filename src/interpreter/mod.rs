@@ -1,5 +1,9 @@
-/** mod of interpreter */
+/* mod of interpreter */
 
 pub(crate) mod lexeme;
 pub(crate) mod parsing;
-pub(crate) mod interpret;
\ No newline at end of file
+pub(crate) mod operator;
+pub(crate) mod interpret;
+pub(crate) mod environment;
+pub(crate) mod builtin;
+pub(crate) mod value;
\ No newline at end of file
@@ -0,0 +1,239 @@
+use crate::interpreter::lexeme::TokenKind;
+
+/// Where an operator stands to its operands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fixity {
+    /// Before its operand, e.g. `-x`.
+    Prefix,
+    /// Between its two operands, e.g. `x + y`.
+    Infix,
+    /// After its operand, e.g. `x!`.
+    Postfix,
+}
+
+/// How a row of infix operators of the same precedence is grouped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Associativity {
+    /// `a - b - c` is `(a - b) - c`.
+    Left,
+    /// `a ^ b ^ c` is `a ^ (b ^ c)`.
+    Right,
+    /// `a < b < c` is an error.
+    None,
+}
+
+/// What the parser makes of an operator.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Meaning {
+    /// An operator of the language, e.g. `+`, as the token it is evaluated by.
+    Builtin(TokenKind),
+    /// `to` or `in`, which take a unit on the right.
+    Conversion,
+    /// A call of the function, built in or defined by the user, with the operands as its arguments.
+    Call(String),
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Operator {
+    pub symbol: String,
+    pub fixity: Fixity,
+    pub precedence: u8,
+    pub associativity: Associativity,
+    pub meaning: Meaning,
+}
+
+/// The precedences of the built-in operators, see `OperatorTable`.
+pub(crate) const OR: u8 = 10;
+pub(crate) const AND: u8 = 20;
+pub(crate) const COMPARISON: u8 = 30;
+pub(crate) const CONVERSION: u8 = 40;
+pub(crate) const BIT_OR: u8 = 50;
+pub(crate) const BIT_XOR: u8 = 60;
+pub(crate) const BIT_AND: u8 = 70;
+pub(crate) const SHIFT: u8 = 80;
+pub(crate) const SUM: u8 = 90;
+pub(crate) const PRODUCT: u8 = 100;
+pub(crate) const IMPLICIT: u8 = 110;
+pub(crate) const PREFIX: u8 = 120;
+pub(crate) const POWER: u8 = 130;
+pub(crate) const POSTFIX: u8 = 140;
+
+/// The words that belong to the grammar, which no operator may take.
+pub(crate) const KEYWORDS: [&str; 5] = ["if", "then", "else", "true", "false"];
+
+/// The operators the parser knows, each with its precedence: the higher, the tighter it binds.
+///
+/// | precedence | operators                   | associativity |
+/// |------------|-----------------------------|---------------|
+/// | 10         | `\|\|`                        | left          |
+/// | 20         | `&&`                        | left          |
+/// | 30         | `==` `!=` `<` `<=` `>` `>=` | none          |
+/// | 40         | `to` `in`                   | left          |
+/// | 50         | `\|`                          | left          |
+/// | 60         | `xor`                       | left          |
+/// | 70         | `&`                         | left          |
+/// | 80         | `<<` `>>`                   | left          |
+/// | 90         | `+` `-`                     | left          |
+/// | 100        | `*` `/` `//` `%` `mod`      | left          |
+/// | 110        | implicit `2x`               | left          |
+/// | 120        | prefix `+ - ! ~`            |               |
+/// | 130        | `^`                         | right         |
+/// | 140        | postfix `! !! %`            |               |
+///
+/// Below all of them is the conditional, `if then else` and `? :`. An implicit multiplication
+/// takes 100 instead of 110 when it is `Strict`.
+///
+/// More operators may be added, each standing for a function of one or two arguments,
+/// built in or defined by the user, e.g. `a <> b` for `avg(a, b)` at 95, between `+` and `*`.
+/// A symbol is a word, like `per`, which then cannot name anything else, or made of symbol
+/// characters, like `<>` or `@`. Tokens that touch each other make up the longest symbol
+/// there is, so `a<>b` has `<>` and `a < >b` an error.
+#[derive(Debug, Clone)]
+pub struct OperatorTable {
+    operators: Vec<Operator>
+}
+
+impl Default for OperatorTable {
+    fn default() -> Self {
+        use Associativity::{Left, Right};
+        use TokenKind::*;
+        let mut table = Self { operators: vec![] };
+        let infix = [
+            (Or, OR, Left), (And, AND, Left),
+            (Equal, COMPARISON, Associativity::None), (NotEqual, COMPARISON, Associativity::None),
+            (Less, COMPARISON, Associativity::None), (LessEqual, COMPARISON, Associativity::None),
+            (Greater, COMPARISON, Associativity::None), (GreaterEqual, COMPARISON, Associativity::None),
+            (BitOr, BIT_OR, Left), (BitXor, BIT_XOR, Left), (BitAnd, BIT_AND, Left),
+            (ShiftLeft, SHIFT, Left), (ShiftRight, SHIFT, Left), (Plus, SUM, Left), (Minus, SUM, Left),
+            (Multiply, PRODUCT, Left), (Divide, PRODUCT, Left), (IntegerDivide, PRODUCT, Left),
+            (Modulo, PRODUCT, Left), (Power, POWER, Right),
+        ];
+        for (op, precedence, associativity) in infix {
+            table.insert(op.symbol(), Fixity::Infix, precedence, associativity, Meaning::Builtin(op.clone()));
+        }
+        table.insert("mod", Fixity::Infix, PRODUCT, Left, Meaning::Builtin(Modulo));
+        for word in ["to", "in"] {
+            table.insert(word, Fixity::Infix, CONVERSION, Left, Meaning::Conversion);
+        }
+        for op in [Plus, Minus, Not, BitNot] {
+            table.insert(op.symbol(), Fixity::Prefix, PREFIX, Left, Meaning::Builtin(op.clone()));
+        }
+        for op in [Factorial, DoubleFactorial, Percent] {
+            table.insert(op.symbol(), Fixity::Postfix, POSTFIX, Left, Meaning::Builtin(op.clone()));
+        }
+        table
+    }
+}
+
+impl OperatorTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `a symbol b`, standing for `function(a, b)`, or replace the infix operator `symbol`.
+    pub fn infix(
+        &mut self, symbol: &str, precedence: u8, associativity: Associativity, function: &str
+    ) -> Result<(), String> {
+        check_symbol(symbol)?;
+        self.insert(symbol, Fixity::Infix, precedence, associativity, Meaning::Call(function.to_string()));
+        Ok(())
+    }
+
+    /// Add `symbol a`, standing for `function(a)`; its operand takes the operators of at least `precedence`.
+    pub fn prefix(&mut self, symbol: &str, precedence: u8, function: &str) -> Result<(), String> {
+        check_symbol(symbol)?;
+        self.insert(symbol, Fixity::Prefix, precedence, Associativity::Left, Meaning::Call(function.to_string()));
+        Ok(())
+    }
+
+    /// Add `a symbol`, standing for `function(a)`.
+    pub fn postfix(&mut self, symbol: &str, precedence: u8, function: &str) -> Result<(), String> {
+        check_symbol(symbol)?;
+        self.insert(symbol, Fixity::Postfix, precedence, Associativity::Left, Meaning::Call(function.to_string()));
+        Ok(())
+    }
+
+    /// Forget the operator `symbol` of `fixity`, built in or not, returning whether there was one.
+    pub fn remove(&mut self, symbol: &str, fixity: Fixity) -> bool {
+        let count = self.operators.len();
+        self.operators.retain(|op| op.symbol != symbol || op.fixity != fixity);
+        self.operators.len() < count
+    }
+
+    /// The precedence of the operator `symbol` of `fixity`, if there is one.
+    pub fn precedence(&self, symbol: &str, fixity: Fixity) -> Option<u8> {
+        self.find(symbol, fixity).map(|op| op.precedence)
+    }
+
+    pub(crate) fn insert(
+        &mut self, symbol: &str, fixity: Fixity, precedence: u8, associativity: Associativity, meaning: Meaning
+    ) {
+        self.remove(symbol, fixity);
+        self.operators.push(Operator { symbol: symbol.to_string(), fixity, precedence, associativity, meaning });
+    }
+
+    pub(crate) fn find(&self, symbol: &str, fixity: Fixity) -> Option<&Operator> {
+        self.operators.iter().find(|op| op.symbol == symbol && op.fixity == fixity)
+    }
+
+    /// Whether `word` is an infix or postfix operator, which joins operands instead of being one.
+    pub(crate) fn joins(&self, word: &str) -> bool {
+        self.find(word, Fixity::Infix).is_some() || self.find(word, Fixity::Postfix).is_some()
+    }
+
+    /// The characters of the symbols, which the tokenizer must read even if they mean nothing else, e.g. `@`.
+    pub(crate) fn symbol_chars(&self) -> Vec<char> {
+        let mut chars: Vec<char> = self.operators.iter()
+            .filter(|op| !is_word(&op.symbol))
+            .flat_map(|op| op.symbol.chars())
+            .collect();
+        chars.sort_unstable();
+        chars.dedup();
+        chars
+    }
+
+    /// The number of characters of the longest symbol, which is as far as touching tokens may make up one.
+    pub(crate) fn longest_symbol(&self) -> usize {
+        self.operators.iter().filter(|op| !is_word(&op.symbol)).map(|op| op.symbol.chars().count()).max().unwrap_or(0)
+    }
+}
+
+fn is_word(symbol: &str) -> bool {
+    symbol.starts_with(char::is_alphabetic)
+}
+
+/// A word of letters, digits and `_`, but not a keyword, or only symbol characters.
+fn check_symbol(symbol: &str) -> Result<(), String> {
+    let valid = if is_word(symbol) {
+        symbol.chars().all(|c| c.is_alphanumeric() || c == '_')
+    }
+    else {
+        let reserved = |c: char| c.is_alphanumeric() || c.is_whitespace() || "(),;._°".contains(c);
+        !symbol.is_empty() && !symbol.chars().any(reserved)
+    };
+    if !valid { return Err(format!("Invalid operator '{}', expected a word or symbol characters", symbol)); }
+    if KEYWORDS.contains(&symbol) { return Err(format!("'{}' is a keyword, not an operator", symbol)); }
+    Ok(())
+}
+
+#[test]
+fn test_operator_table() {
+    let mut table = OperatorTable::new();
+    assert_eq!(table.precedence("*", Fixity::Infix), Some(PRODUCT));
+    assert_eq!(table.precedence("-", Fixity::Prefix), Some(PREFIX));
+    assert_eq!(table.precedence("!", Fixity::Infix), None);
+    table.infix("<>", 95, Associativity::Left, "avg").unwrap();
+    table.infix("<>", 85, Associativity::Right, "avg").unwrap();
+    assert_eq!(table.precedence("<>", Fixity::Infix), Some(85));
+    table.postfix("squared", POSTFIX, "sq").unwrap();
+    assert!(table.joins("squared") && table.joins("mod") && !table.joins("x"));
+    assert_eq!(table.symbol_chars().iter().filter(|c| **c == '<' || **c == '>').count(), 2);
+    assert_eq!(table.longest_symbol(), 2);
+    table.infix("<=>", 30, Associativity::None, "compare").unwrap();
+    assert_eq!(table.longest_symbol(), 3);
+    assert!(table.remove("^", Fixity::Infix));
+    assert!(!table.remove("^", Fixity::Infix));
+    for invalid in ["", "a b", "1+", "+(", "then", "x.y"] {
+        assert!(table.prefix(invalid, PREFIX, "f").is_err(), "{}", invalid);
+    }
+}
@@ -1,12 +1,218 @@
 use std::fmt::Display;
 
-use crate::interpreter::lexeme::Token;
+use crate::{
+    error::{CalcError, Span},
+    interpreter::{
+        lexeme::{Token, TokenKind},
+        operator::{self, Associativity, Fixity, Meaning, Operator, OperatorTable, KEYWORDS}
+    },
+    number::{context::{Context, Dialect, ImplicitMultiplication}, unit::Unit}
+};
 
-#[derive(Debug)]
-pub enum Expression {
+#[derive(Debug, Clone)]
+pub enum ExprKind {
     Number(String),
-    Unary { op: Token, expr: Box<Expression> },
-    Binary { op: Token, left: Box<Expression>, right: Box<Expression> },
+    /// `true` or `false`.
+    Boolean(bool),
+    Variable(String),
+    Call { name: String, args: Vec<Expression> },
+    Unary { op: TokenKind, expr: Box<Expression> },
+    /// An operator after its operand, e.g. `5!` or `15%`.
+    Postfix { op: TokenKind, expr: Box<Expression> },
+    Binary { op: TokenKind, left: Box<Expression>, right: Box<Expression> },
+    /// A product written without `*`, e.g. `2x`, `2 pi r` or `(1 + 2)(3 + 4)`.
+    Implicit { left: Box<Expression>, right: Box<Expression> },
+    /// A number written with a unit, e.g. `9.81 m/s^2`.
+    WithUnit { expr: Box<Expression>, unit: Unit },
+    /// `expr to unit` or `expr in unit`.
+    Convert { expr: Box<Expression>, unit: Unit },
+    /// `if condition then then else otherwise`, or `condition ? then : otherwise`.
+    Conditional { condition: Box<Expression>, then: Box<Expression>, otherwise: Box<Expression> },
+    /// What could not be parsed, in the tree of `Parser::parse_recovering`, e.g. the operand of `1 + * 2`.
+    Error,
+}
+
+/// A node of the syntax tree, with the part of the input it was parsed from.
+#[derive(Debug, Clone)]
+pub struct Expression {
+    pub kind: ExprKind,
+    pub span: Span
+}
+
+impl Expression {
+    fn new(kind: ExprKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+
+    fn implicit(left: Expression, right: Expression) -> Self {
+        let span = Span::new(left.span.start, right.span.end);
+        Self::new(ExprKind::Implicit { left: Box::new(left), right: Box::new(right) }, span)
+    }
+}
+
+impl Expression {
+    /// Write the tree back as an expression, with only the parentheses it needs,
+    /// e.g. `x ^ 2 + y` or `(1 + 2) * 3`.
+    #[cfg(test)]
+    pub fn infix(&self) -> String {
+        self.infix_in(Dialect::Standard)
+    }
+
+    /// `infix`, written in `dialect`, e.g. `x ** 2 + y` in `Dialect::C`.
+    pub fn infix_in(&self, dialect: Dialect) -> String {
+        match &self.kind {
+            ExprKind::Number(n) | ExprKind::Variable(n) => n.clone(),
+            ExprKind::Boolean(b) => b.to_string(),
+            ExprKind::Call { name, args } => {
+                let args: Vec<String> = args.iter().map(|arg| arg.infix_in(dialect)).collect();
+                format!("{}({})", name, args.join(", "))
+            },
+            ExprKind::Unary { op, expr } => {
+                format!("{}{}", op.symbol(), expr.infix_within(operator::PREFIX, false, dialect))
+            },
+            ExprKind::Postfix { op, expr } => {
+                format!("{}{}", expr.infix_within(operator::POSTFIX, false, dialect), op.symbol())
+            },
+            ExprKind::Binary { op, left, right } => {
+                let level = binary_level(op);
+                // `^` groups to the right, comparisons not at all, the others to the left
+                let right_associative = matches!(op, TokenKind::Power);
+                let left = left.infix_within(level, right_associative || op.is_comparison(), dialect);
+                let right = right.infix_within(level, !right_associative, dialect);
+                let symbol = spelling(op, dialect);
+                // `x% + 1` would be read as `x % (+1)`
+                if left.ends_with('%') && matches!(op, TokenKind::Plus | TokenKind::Minus) {
+                    return format!("({}) {} {}", left, symbol, right);
+                }
+                format!("{} {} {}", left, symbol, right)
+            },
+            ExprKind::Implicit { left, right } => {
+                let left = match left.kind {
+                    ExprKind::Implicit { .. } => left.infix_in(dialect),
+                    _ => left.infix_within(operator::IMPLICIT, false, dialect),
+                };
+                // the right one must start with a name or `(`, which a name before it would call
+                let mut right = right.infix_within(operator::IMPLICIT, true, dialect);
+                if !right.starts_with(|c: char| c.is_alphabetic() || c == '(') || right == "true" || right == "false" {
+                    right = format!("({})", right);
+                }
+                let last_word = left.rsplit(|c: char| !c.is_alphanumeric() && c != '_').next().unwrap_or_default();
+                if last_word.starts_with(|c: char| c.is_alphabetic() || c == '_') && right.starts_with('(') {
+                    return format!("({}) {}", left, right);
+                }
+                format!("{} {}", left, right)
+            },
+            ExprKind::WithUnit { expr, unit } => format!("{} {}", expr.infix_in(dialect), unit),
+            ExprKind::Convert { expr, unit } => {
+                format!("{} to {}", expr.infix_within(operator::CONVERSION, false, dialect), unit)
+            },
+            // its parts are closed by the keywords, and `else` reaches as far as it can anyway
+            ExprKind::Error => "<error>".to_string(),
+            ExprKind::Conditional { condition, then, otherwise } => {
+                let (condition, then) = (condition.infix_in(dialect), then.infix_in(dialect));
+                format!("if {} then {} else {}", condition, then, otherwise.infix_in(dialect))
+            },
+        }
+    }
+
+    /// `infix`, in parentheses if it binds looser than an operand of an operator at `level`.
+    fn infix_within(&self, level: u8, tie_needs_parentheses: bool, dialect: Dialect) -> String {
+        let own = match &self.kind {
+            ExprKind::Binary { op, .. } => binary_level(op),
+            // as loose as `*`, since it may have been parsed that way
+            ExprKind::Implicit { .. } => operator::PRODUCT,
+            ExprKind::Unary { .. } | ExprKind::WithUnit { .. } => operator::PREFIX,
+            ExprKind::Convert { .. } => operator::CONVERSION,
+            ExprKind::Postfix { .. } => operator::POSTFIX,
+            // below all the operators
+            ExprKind::Conditional { .. } => 0,
+            _ => u8::MAX,
+        };
+        if own < level || (own == level && tie_needs_parentheses) {
+            format!("({})", self.infix_in(dialect))
+        }
+        else {
+            self.infix_in(dialect)
+        }
+    }
+}
+
+/// How `op` is written in `dialect`, e.g. `**` for `^` in `Dialect::C`.
+fn spelling(op: &TokenKind, dialect: Dialect) -> &str {
+    match (op, dialect) {
+        (TokenKind::Power, Dialect::C) => "**",
+        (TokenKind::BitXor, Dialect::C) => "^",
+        // `%` would be a percentage
+        (TokenKind::Modulo, Dialect::Spreadsheet) => "mod",
+        (op, _) => op.symbol(),
+    }
+}
+
+/// Whether a token of `kind` may be part of an operator, e.g. `<` of `<>`, but not a name or `(`.
+fn is_symbol(kind: &TokenKind) -> bool {
+    !matches!(kind, TokenKind::Number(_) | TokenKind::Identifier(_) | TokenKind::OpenParenthesis
+        | TokenKind::CloseParenthesis | TokenKind::Comma | TokenKind::Separator | TokenKind::Semicolon
+        | TokenKind::Newline | TokenKind::Fin)
+}
+
+/// Whether an operand may end with a token of `kind`, e.g. `)` or `!`, but not `+` or `then`.
+fn ends_operand(kind: &TokenKind, operators: &OperatorTable) -> bool {
+    match kind {
+        TokenKind::Identifier(name) => !operators.joins(name) && operators.find(name, Fixity::Prefix).is_none()
+            && !["if", "then", "else"].contains(&name.as_str()),
+        TokenKind::Number(_) | TokenKind::CloseParenthesis => true,
+        kind => operators.find(kind.symbol(), Fixity::Postfix).is_some(),
+    }
+}
+
+/// `tokens` without the line breaks that cannot end a statement: the ones inside parentheses,
+/// after a token that needs more to follow, e.g. `1 +` or `if x then`, and after another one.
+fn statement_breaks(tokens: Vec<Token>, operators: &OperatorTable) -> Vec<Token> {
+    let mut depth = 0usize;
+    let mut kept: Vec<Token> = Vec::with_capacity(tokens.len());
+    for token in tokens {
+        match token.kind {
+            TokenKind::OpenParenthesis => depth += 1,
+            TokenKind::CloseParenthesis => depth = depth.saturating_sub(1),
+            TokenKind::Newline if depth > 0 || !kept.last().is_some_and(|last| ends_operand(&last.kind, operators)) => {
+                continue;
+            },
+            _ => { },
+        }
+        kept.push(token);
+    }
+    if kept.last().is_some_and(|last| last.kind == TokenKind::Newline) { kept.pop(); }
+    kept
+}
+
+fn binary_level(op: &TokenKind) -> u8 {
+    match op {
+        TokenKind::Or => operator::OR,
+        TokenKind::And => operator::AND,
+        op if op.is_comparison() => operator::COMPARISON,
+        TokenKind::BitOr => operator::BIT_OR,
+        TokenKind::BitXor => operator::BIT_XOR,
+        TokenKind::BitAnd => operator::BIT_AND,
+        TokenKind::ShiftLeft | TokenKind::ShiftRight => operator::SHIFT,
+        TokenKind::Plus | TokenKind::Minus => operator::SUM,
+        TokenKind::Multiply | TokenKind::Divide | TokenKind::IntegerDivide | TokenKind::Modulo => operator::PRODUCT,
+        _ => operator::POWER,
+    }
+}
+
+/// `op` applied to its operands, one or two by its fixity, as a node over `span`.
+fn operation(op: Operator, mut operands: Vec<Expression>, span: Span) -> Expression {
+    let kind = match (op.meaning, op.fixity) {
+        (Meaning::Call(name), _) => ExprKind::Call { name, args: operands },
+        (Meaning::Builtin(op), Fixity::Prefix) => ExprKind::Unary { op, expr: Box::new(operands.remove(0)) },
+        (Meaning::Builtin(op), Fixity::Postfix) => ExprKind::Postfix { op, expr: Box::new(operands.remove(0)) },
+        (Meaning::Builtin(op), Fixity::Infix) => {
+            let right = Box::new(operands.remove(1));
+            ExprKind::Binary { op, left: Box::new(operands.remove(0)), right }
+        },
+        (Meaning::Conversion, _) => unreachable!("conversions take a unit, not an operand"),
+    };
+    Expression::new(kind, span)
 }
 
 impl Display for Expression {
@@ -15,114 +221,681 @@ impl Display for Expression {
     }
 }
 
+/// A statement of the input: an expression to show, a variable to bind, or a function to define.
+#[derive(Debug)]
+pub enum Statement {
+    Expression(Expression),
+    Assign { name: String, value: Expression },
+    Define { name: String, params: Vec<String>, body: Expression },
+}
+
+impl Display for Statement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:#?}", self)
+    }
+}
+
+/// A Pratt parser: operands joined by the operators of an `OperatorTable`, which has the
+/// precedence and associativity of each, so that more may be added. Below all of them are the
+/// conditionals `if then else` and `? :`, which group to the right.
+///
+/// An operand followed by a name or `(` is multiplied by it, so `3x^2` is `3 * x^2` and
+/// `4 sqrt(2)` is `4 * sqrt(2)`. This binds tighter than `/`, so `1/2x` is `1/(2x)`, unless
+/// the implicit multiplication is `Strict`, which gives it the precedence of `*`. A name followed
+/// by `(` is a call, `x(1 + 2)`, and two numbers are not a product, `2 3` is an error.
+///
+/// Unary minus binds looser than `^`, so `-2^2` is `-(2^2)`, but the exponent
+/// of `^` may itself start with a sign, so `2^-1` is `2^(-1)`.
+///
+/// `!` after an operand is a factorial, and `!!` a double factorial if nothing is between
+/// the two: `3!^2` is `(3!)^2` and `-3!` is `-(3!)`. `%` after an operand is a percentage,
+/// unless something follows that could start an operand, which makes it the remainder:
+/// `15% * 2` is `0.15 * 2`, but `15% + 2` is `15 % (+2)`. The same goes for any symbol that is
+/// both a postfix and an infix operator.
+///
+/// Comparisons do not chain: `1 < x < 3` is an error, to be written `1 < x && x < 3`.
+///
+/// A number may be followed by a unit, e.g. `9.81 m/s^2`, which is part of the number:
+/// its `/` and `^` belong to the unit. Names known as units are units there, and after `to`,
+/// even if a variable has the same name.
+///
+/// Statements are separated by `;` or line breaks, except where a line cannot end, e.g. inside
+/// parentheses or after `+`, so a long expression may go on on the next line.
 pub struct Parser {
     position: usize,
     token_stream: Vec<Token>,
     current_token: Token,
+    end_token: Token,
+    implicit: ImplicitMultiplication,
+    operators: OperatorTable,
+    dialect: Dialect,
+    /// The errors found so far, which the parser went on after.
+    diagnostics: Vec<CalcError>,
+    /// How many groups, operands of prefix operators and arguments the parser is inside of.
+    depth: usize,
+    max_depth: usize,
+    /// The characters of the longest symbol of `operators`.
+    longest_symbol: usize,
 }
 
 impl Parser {
+    /// A parser of the built-in operators.
+    #[cfg(test)]
     pub fn new(token_stream: Vec<Token>) -> Self {
-        if token_stream.is_empty() { panic!("Empty token stream"); }
-        Self { position: 0, token_stream: token_stream.clone(), current_token: token_stream[0].clone() }
+        Self::with_operators(token_stream, OperatorTable::default())
+    }
+
+    /// A parser of the operators of `operators` instead of the built-in ones.
+    pub fn with_operators(token_stream: Vec<Token>, operators: OperatorTable) -> Self {
+        // the first token is taken by `begin`, once the dialect is known
+        let end_token = Token { kind: TokenKind::Fin, span: Span::new(0, 0) };
+        let current_token = end_token.clone();
+        let (implicit, dialect) = (ImplicitMultiplication::default(), Dialect::default());
+        let diagnostics = vec![];
+        let max_depth = Context::default().max_depth;
+        let longest_symbol = operators.longest_symbol();
+        Self {
+            position: 0, token_stream, current_token, end_token, implicit, operators, dialect, diagnostics, depth: 0,
+            max_depth, longest_symbol
+        }
+    }
+
+    /// The syntax of the input, `Standard` by default. It takes `%` from the operators of `Dialect::C`,
+    /// where it is only the remainder, and of `Dialect::Spreadsheet`, where it is only a percentage.
+    /// The tokens must come from a `Tokenizer` of the same dialect.
+    pub fn dialect(mut self, dialect: Dialect) -> Self {
+        match dialect {
+            Dialect::Standard => { },
+            Dialect::C => { self.operators.remove("%", Fixity::Postfix); },
+            Dialect::Spreadsheet => { self.operators.remove("%", Fixity::Infix); },
+        }
+        self.dialect = dialect;
+        self
     }
 
-    pub fn parse(&mut self) -> Expression { self.expr() }
+    /// How tightly `2x` binds, `Tight` by default.
+    pub fn implicit_multiplication(mut self, implicit: ImplicitMultiplication) -> Self {
+        self.implicit = implicit;
+        self
+    }
+
+    /// How deep expressions may be nested, e.g. `((1))` is 2 deep, so that the recursion of the parser
+    /// cannot overflow the stack. `Context::max_depth` by default.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Parse the whole input as one expression.
+    #[cfg(test)]
+    pub fn parse(&mut self) -> Result<Expression, CalcError> {
+        self.begin();
+        self.not_empty()?;
+        let expr = self.conditional().and_then(|expr| self.end(&[]).map(|_| expr));
+        self.first_error(expr)
+    }
+
+    /// Parse the whole input as `name = expression`, `name(param, ...) = expression`, or an expression alone.
+    #[cfg(test)]
+    pub fn parse_statement(&mut self) -> Result<Statement, CalcError> {
+        self.begin();
+        self.not_empty()?;
+        let statement = self.statement().and_then(|statement| self.end(&[]).map(|_| statement));
+        self.first_error(statement)
+    }
+
+    /// Parse statements separated by `;` or line breaks, e.g. `rate = 0.07; 200 * (1 + rate)`.
+    /// Empty ones are skipped, as in `1;; 2;`.
+    pub fn parse_statements(&mut self) -> Result<Vec<Statement>, CalcError> {
+        let (statements, mut diagnostics) = self.parse_recovering();
+        if diagnostics.is_empty() { Ok(statements) } else { Err(diagnostics.remove(0)) }
+    }
+
+    /// `parse_statements`, going on after each error instead of stopping at the first one, with all of them.
+    ///
+    /// What could not be parsed is an `ExprKind::Error` in the tree: a missing operand, as in `1 + * 2`,
+    /// or the rest of the statement, up to the next `;` or line break. A missing `)` is taken as
+    /// there, after skipping what comes before the `)` that is there, so `(1 2) + 3` is `1 + 3`.
+    pub fn parse_recovering(&mut self) -> (Vec<Statement>, Vec<CalcError>) {
+        self.begin();
+        self.diagnostics.clear();
+        let mut statements = vec![];
+        loop {
+            while matches!(self.current_token.kind, TokenKind::Semicolon | TokenKind::Newline) {
+                self.next_token();
+            }
+            if self.current_token.kind == TokenKind::Fin { break; }
+            let start = self.current_token.span.start;
+            match self.statement() {
+                Ok(statement) => statements.push(statement),
+                Err(error) => {
+                    self.report(error);
+                    self.skip_statement();
+                    let span = Span::new(start, self.last_end().max(start));
+                    statements.push(Statement::Expression(Expression::new(ExprKind::Error, span)));
+                },
+            }
+            if let Err(error) = self.end(&[TokenKind::Semicolon, TokenKind::Newline]) {
+                self.report(error);
+                self.skip_statement();
+            }
+        }
+        if statements.is_empty() {
+            if let Err(error) = self.not_empty() { self.report(error); }
+        }
+        (statements, std::mem::take(&mut self.diagnostics))
+    }
+
+    /// `result`, unless an error was found before it, which comes first.
+    #[cfg(test)]
+    fn first_error<T>(&mut self, result: Result<T, CalcError>) -> Result<T, CalcError> {
+        if self.diagnostics.is_empty() { result } else { Err(self.diagnostics.remove(0)) }
+    }
+
+    /// Keep `error` and go on, unless it is where the last one was, which it would only follow from.
+    fn report(&mut self, error: CalcError) {
+        if self.diagnostics.last().is_some_and(|last| last.span() == error.span()) { return; }
+        self.diagnostics.push(error);
+    }
+
+    fn skip_statement(&mut self) {
+        while !matches!(self.current_token.kind, TokenKind::Semicolon | TokenKind::Newline | TokenKind::Fin) {
+            self.next_token();
+        }
+    }
+
+    /// Where the token before the current one ends.
+    fn last_end(&self) -> usize {
+        self.position.checked_sub(1).and_then(|last| self.token_stream.get(last)).map_or(0, |t| t.span.end)
+    }
+
+    /// Whether the parser may go on at the current token after an error: at an operator, `)`, `,`,
+    /// a part of a conditional, or the end of the statement.
+    fn at_synchronization(&self) -> bool {
+        matches!(self.current_token.kind, TokenKind::CloseParenthesis | TokenKind::Comma | TokenKind::Separator
+            | TokenKind::Question | TokenKind::Colon | TokenKind::Semicolon | TokenKind::Newline | TokenKind::Fin)
+            || self.is_keyword("then") || self.is_keyword("else")
+            || self.next_operator().is_some()
+    }
+
+    /// Drop the line breaks that do not end a statement, now that the operators are known,
+    /// and go to the first token.
+    fn begin(&mut self) {
+        self.token_stream = statement_breaks(std::mem::take(&mut self.token_stream), &self.operators);
+        let end = self.token_stream.last().map_or(0, |t| t.span.end);
+        self.end_token = Token { kind: TokenKind::Fin, span: Span::new(end, end) };
+        self.position = 0;
+        self.current_token = self.token_stream.first().cloned().unwrap_or(self.end_token.clone());
+    }
+
+    fn not_empty(&self) -> Result<(), CalcError> {
+        if self.token_stream.iter().all(|t| t.kind == TokenKind::Semicolon) {
+            return Err(CalcError::parse("Empty expression", Some(self.end_token.span)));
+        }
+        Ok(())
+    }
+
+    /// Check that nothing but one of `separators` or the end of the input follows,
+    /// so that `(1 + 2))` is an error instead of `1 + 2`.
+    fn end(&self, separators: &[TokenKind]) -> Result<(), CalcError> {
+        let kind = &self.current_token.kind;
+        if *kind == TokenKind::Fin || separators.contains(kind) { return Ok(()); }
+        let message = format!("Unexpected {} after the end of the expression", kind.describe());
+        Err(CalcError::parse(message, Some(self.current_token.span)))
+    }
+
+    fn statement(&mut self) -> Result<Statement, CalcError> {
+        // a formula of a spreadsheet, `=A1 * 2`
+        if self.dialect == Dialect::Spreadsheet && self.current_token.kind == TokenKind::Assign {
+            self.next_token();
+        }
+        if self.is_definition() {
+            return self.definition();
+        }
+        let is_assignment = matches!(self.current_token.kind, TokenKind::Identifier(_))
+            && matches!(self.peek_kind(), Some(TokenKind::Assign));
+        if !is_assignment {
+            return self.conditional().map(Statement::Expression);
+        }
+        let name = self.value_name("variable name")?;
+        self.eat(TokenKind::Assign)?;
+        let value = self.conditional()?;
+        Ok(Statement::Assign { name, value })
+    }
+
+    /// Whether the tokens ahead look like `name ( ... ) =`.
+    fn is_definition(&self) -> bool {
+        let rest = &self.token_stream[self.position.min(self.token_stream.len())..];
+        let kinds: Vec<&TokenKind> = rest.iter().map(|t| &t.kind).collect();
+        if !matches!(kinds.as_slice(), [TokenKind::Identifier(_), TokenKind::OpenParenthesis, ..]) {
+            return false;
+        }
+        match kinds.iter().position(|kind| **kind == TokenKind::CloseParenthesis) {
+            Some(close) => matches!(kinds.get(close + 1), Some(TokenKind::Assign)),
+            None => false,
+        }
+    }
+
+    fn definition(&mut self) -> Result<Statement, CalcError> {
+        let name = self.name("function name")?;
+        self.eat(TokenKind::OpenParenthesis)?;
+        let mut params: Vec<String> = vec![];
+        while !matches!(self.current_token.kind, TokenKind::CloseParenthesis) {
+            if !params.is_empty() {
+                self.separator()?;
+            }
+            let span = self.current_token.span;
+            let param = self.value_name("parameter name")?;
+            if params.contains(&param) {
+                return Err(CalcError::parse(format!("Parameter '{}' appears twice", param), Some(span)));
+            }
+            params.push(param);
+        }
+        self.eat(TokenKind::CloseParenthesis)?;
+        self.eat(TokenKind::Assign)?;
+        let body = self.conditional()?;
+        Ok(Statement::Define { name, params, body })
+    }
+
+    /// Eat an identifier, `what` is how it is called if something else is found.
+    /// A keyword cannot be one, since it could never be read back.
+    fn name(&mut self, what: &str) -> Result<String, CalcError> {
+        match self.current_token.kind.clone() {
+            TokenKind::Identifier(name) if KEYWORDS.contains(&name.as_str()) => Err(CalcError::parse(
+                format!("'{}' is a keyword, not a {}", name, what), Some(self.current_token.span)
+            )),
+            TokenKind::Identifier(name) => {
+                self.next_token();
+                Ok(name)
+            },
+            other => Err(CalcError::parse(
+                format!("Expected {}, found {}", what, other.describe()), Some(self.current_token.span)
+            )),
+        }
+    }
+
+    fn peek_kind(&self) -> Option<&TokenKind> {
+        self.token_stream.get(self.position + 1).map(|t| &t.kind)
+    }
 
     fn next_token(&mut self) {
         self.position += 1;
-        if self.position < self.token_stream.len() {
-            self.current_token = self.token_stream[self.position].clone();
-        }
+        self.current_token = match self.token_stream.get(self.position) {
+            Some(token) => token.clone(),
+            None => self.end_token.clone(),
+        };
     }
 
-    fn eat(&mut self, token: Token) {
-        if self.current_token == token {
+    fn eat(&mut self, kind: TokenKind) -> Result<Span, CalcError> {
+        if self.current_token.kind == kind {
+            let span = self.current_token.span;
             self.next_token();
+            Ok(span)
         }
         else {
-            panic!("Wrong at eating")
+            let message = format!("Expected {}, found {}", kind.describe(), self.current_token.kind.describe());
+            Err(CalcError::parse(message, Some(self.current_token.span)))
+        }
+    }
+
+    /// Eat the `,` between arguments or parameters, or the `;` that stands for it.
+    fn separator(&mut self) -> Result<Span, CalcError> {
+        match self.current_token.kind {
+            TokenKind::Separator => self.eat(TokenKind::Separator),
+            _ => self.eat(TokenKind::Comma),
+        }
+    }
+
+    /// Eat the name of a variable or parameter, which cannot be a unit, since `3s` is always read with the unit.
+    fn value_name(&mut self, what: &str) -> Result<String, CalcError> {
+        let span = self.current_token.span;
+        let name = self.name(what)?;
+        if Unit::find(&name).is_some() {
+            return Err(CalcError::parse(format!("'{}' is a unit, not a {}", name, what), Some(span)));
         }
+        Ok(name)
     }
 
-    fn factor(&mut self) -> Expression {
+    fn factor(&mut self) -> Result<Expression, CalcError> {
         let token = self.current_token.clone();
-        match token {
-            Token::OpenParenthesis => {
-                self.eat(Token::OpenParenthesis);
-                let elem = self.expr();
-                self.eat(Token::CloseParenthesis);
-                elem
+        match token.kind {
+            TokenKind::OpenParenthesis => {
+                let open = self.eat(TokenKind::OpenParenthesis)?;
+                let elem = self.nested(open, Self::conditional)?;
+                let close = self.close();
+                Ok(Expression::new(elem.kind, Span::new(open.start, close.end)))
             },
-            Token::Number(n) => {
-                self.eat(Token::Number(String::new()));
-                Expression::Number(n)
+            TokenKind::Number(n) => {
+                self.eat(TokenKind::Number(String::new()))?;
+                let number = Expression::new(ExprKind::Number(n), token.span);
+                if !self.is_unit() { return Ok(number); }
+                let (unit, end) = self.unit()?;
+                let span = Span::new(token.span.start, end.end);
+                Ok(Expression::new(ExprKind::WithUnit { expr: Box::new(number), unit }, span))
             },
-            Token::Plus | Token::Minus => {
-                self.eat(token.clone());
-                Expression::Unary { op: token, expr: Box::new(self.factor()) }
+            TokenKind::Identifier(name) if name == "true" || name == "false" => {
+                self.next_token();
+                Ok(Expression::new(ExprKind::Boolean(name == "true"), token.span))
             },
-            _ => panic!("Wrong in factor")
+            TokenKind::Identifier(name) => {
+                self.eat(TokenKind::Identifier(String::new()))?;
+                if !matches!(self.current_token.kind, TokenKind::OpenParenthesis) {
+                    return Ok(Expression::new(ExprKind::Variable(name), token.span));
+                }
+                let args = self.arguments()?;
+                let span = Span::new(token.span.start, args.1.end);
+                Ok(Expression::new(ExprKind::Call { name, args: args.0 }, span))
+            },
+            other => Err(CalcError::parse(format!("Unexpected {}", other.describe()), Some(token.span)))
         }
     }
 
-    fn power_term(&mut self) -> Expression {
-        let mut node = self.factor();
-        while match self.current_token {  // condition
-            Token::Power => true,
-            _ => false
+    /// `( expr, ... )` after the name of a function, with the span of `)`.
+    fn arguments(&mut self) -> Result<(Vec<Expression>, Span), CalcError> {
+        let open = self.eat(TokenKind::OpenParenthesis)?;
+        let mut args = vec![];
+        if !matches!(self.current_token.kind, TokenKind::CloseParenthesis) {
+            args.push(self.nested(open, Self::conditional)?);
+            while matches!(self.current_token.kind, TokenKind::Comma | TokenKind::Separator) {
+                let comma = self.separator()?;
+                args.push(self.nested(comma, Self::conditional)?);
+            }
         }
-        {  // repeat
-            let token = self.current_token.clone();
-            self.eat(token.clone());
-            node = Expression::Binary {
-                op: token,
-                left: Box::new(node),
-                right: Box::new(self.factor())
-            };
+        let close = self.close();
+        Ok((args, close))
+    }
+
+    /// Eat the `)` that closes a group or the arguments of a call. If something else comes first,
+    /// report it, and skip it up to the `)`, or take the `)` as missing at the end of the statement.
+    fn close(&mut self) -> Span {
+        if self.current_token.kind != TokenKind::CloseParenthesis {
+            let message = format!("Expected ')', found {}", self.current_token.kind.describe());
+            self.report(CalcError::parse(message, Some(self.current_token.span)));
+            let mut depth = 0usize;
+            loop {
+                match self.current_token.kind {
+                    TokenKind::Semicolon | TokenKind::Newline | TokenKind::Fin => {
+                        return Span::new(self.last_end(), self.last_end());
+                    },
+                    TokenKind::CloseParenthesis if depth == 0 => break,
+                    TokenKind::CloseParenthesis => depth -= 1,
+                    TokenKind::OpenParenthesis => depth += 1,
+                    _ => { },
+                }
+                self.next_token();
+            }
         }
-        node
+        let span = self.current_token.span;
+        self.next_token();
+        span
     }
 
-    fn term(&mut self) -> Expression {
-        let mut node = self.power_term();
-        while match self.current_token {  // condition
-            Token::Multiply | Token::Divide => true,
-            _ => false
+    /// An operand: a prefix operator with its own operand, or a factor.
+    fn operand(&mut self) -> Result<Expression, CalcError> {
+        let (op, count) = match self.operator_at(self.position, Fixity::Prefix) {
+            Some(found) => found,
+            None => return self.factor(),
+        };
+        let start = self.current_token.span;
+        self.skip(count);
+        // the operand takes the operators that bind at least as tight, so `-2^2` is `-(2^2)`
+        let expr = self.nested(start, |parser| parser.expression(op.precedence))?;
+        let span = Span::new(start.start, expr.span.end);
+        Ok(operation(op, vec![expr], span))
+    }
+
+    /// `parse` one level deeper, or an error at `span` past `max_depth`.
+    fn nested<T>(&mut self, span: Span, parse: impl FnOnce(&mut Self) -> Result<T, CalcError>) -> Result<T, CalcError> {
+        if self.depth >= self.max_depth {
+            return Err(CalcError::parse("Expression is nested too deeply", Some(span)));
         }
-        {  // repeat
-            let token = self.current_token.clone();
-            self.eat(token.clone());
-            node = Expression::Binary {
-                op: token,
-                left: Box::new(node),
-                right: Box::new(self.power_term())
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    /// Operands joined by the infix and postfix operators of at least `min` precedence,
+    /// the right operand of each taking the ones that bind tighter, e.g. `1 + 2 * 3`.
+    fn expression(&mut self, min: u8) -> Result<Expression, CalcError> {
+        let mut node = self.recovered_operand();
+        loop {
+            if self.is_juxtaposed() {
+                let precedence = match self.implicit {
+                    ImplicitMultiplication::Tight => operator::IMPLICIT,
+                    ImplicitMultiplication::Strict => operator::PRODUCT,
+                };
+                if precedence < min { return Ok(node); }
+                node = Expression::implicit(node, self.expression(precedence.saturating_add(1))?);
+                continue;
+            }
+            let (op, count) = match self.next_operator() {
+                Some((op, count)) if op.precedence >= min => (op, count),
+                _ => return Ok(node),
             };
+            let end = self.token_stream[self.position + count - 1].span;
+            self.skip(count);
+            if op.fixity == Fixity::Postfix {
+                let span = Span::new(node.span.start, end.end);
+                node = operation(op, vec![node], span);
+                continue;
+            }
+            if op.meaning == Meaning::Conversion {
+                let (unit, end) = self.unit()?;
+                let span = Span::new(node.span.start, end.end);
+                node = Expression::new(ExprKind::Convert { expr: Box::new(node), unit }, span);
+                continue;
+            }
+            // `2 ^ 2 ^ 2 ...` goes deeper for each `^`
+            let right = match op.associativity {
+                Associativity::Right => self.nested(end, |parser| parser.expression(op.precedence))?,
+                _ => self.expression(op.precedence.saturating_add(1))?,
+            };
+            let span = Span::new(node.span.start, right.span.end);
+            let chained = op.associativity == Associativity::None;
+            node = operation(op.clone(), vec![node, right], span);
+            if chained { self.not_chained(&op); }
+        }
+    }
+
+    /// An operand, or after reporting why there is none, an `Error` node in its place, with the tokens
+    /// up to where the parser may go on, e.g. at `*` in `1 + * 2`.
+    fn recovered_operand(&mut self) -> Expression {
+        let start = self.current_token.span.start;
+        match self.operand() {
+            Ok(operand) => return operand,
+            Err(error) => self.report(error),
+        }
+        while !self.at_synchronization() {
+            self.next_token();
+        }
+        Expression::new(ExprKind::Error, Span::new(start, self.last_end().max(start)))
+    }
+
+    /// The infix or postfix operator at the current token, with the number of its tokens.
+    /// A symbol that may be both, like `%`, is postfix unless an operand follows it.
+    fn next_operator(&self) -> Option<(Operator, usize)> {
+        let infix = self.operator_at(self.position, Fixity::Infix);
+        match self.operator_at(self.position, Fixity::Postfix) {
+            Some(postfix) if infix.is_none() || !self.starts_operand(self.position + postfix.1) => Some(postfix),
+            _ => infix,
+        }
+    }
+
+    /// The operator of `fixity` at the token at `position`, with the number of its tokens: a name,
+    /// or the longest symbol in the table made of tokens that touch each other, e.g. `<>` of `<` and `>`.
+    fn operator_at(&self, position: usize, fixity: Fixity) -> Option<(Operator, usize)> {
+        let first = self.token_stream.get(position)?;
+        if let TokenKind::Identifier(name) = &first.kind {
+            return self.operators.find(name, fixity).map(|op| (op.clone(), 1));
+        }
+        // no further than the longest symbol, so that a long run like `------1` costs no more than a short one
+        let mut symbols: Vec<&str> = vec![];
+        let (mut end, mut length) = (first.span.start, 0);
+        for token in &self.token_stream[position..] {
+            if !is_symbol(&token.kind) || token.span.start != end { break; }
+            length += token.kind.symbol().chars().count();
+            if !symbols.is_empty() && length > self.longest_symbol { break; }
+            symbols.push(token.kind.symbol());
+            end = token.span.end;
+        }
+        (1..=symbols.len()).rev().find_map(|count| {
+            self.operators.find(&symbols[..count].concat(), fixity).map(|op| (op.clone(), count))
+        })
+    }
+
+    /// Report the next operator if it has the precedence of `op`, which does not chain, e.g. `1 < x < 3`.
+    /// It is parsed as if it did.
+    fn not_chained(&mut self, op: &Operator) {
+        let next = match self.operator_at(self.position, Fixity::Infix) {
+            Some((next, _)) if next.precedence == op.precedence => next,
+            _ => return,
+        };
+        let message = match &op.meaning {
+            Meaning::Builtin(kind) if kind.is_comparison() => {
+                format!("Comparisons cannot be chained, use '&&' before {}", self.current_token.kind.describe())
+            },
+            _ => format!("'{}' cannot follow '{}' without parentheses", next.symbol, op.symbol),
+        };
+        self.report(CalcError::parse(message, Some(self.current_token.span)));
+    }
+
+    /// Whether the current token starts another operand right after one, which is then multiplied by it.
+    fn is_juxtaposed(&self) -> bool {
+        match &self.current_token.kind {
+            TokenKind::OpenParenthesis => true,
+            TokenKind::Identifier(name) => !KEYWORDS.contains(&name.as_str()) && !self.operators.joins(name),
+            _ => false,
+        }
+    }
+
+    /// Whether an operand may start with the token at `position`, e.g. `(` or `-`, but not `to`.
+    fn starts_operand(&self, position: usize) -> bool {
+        match self.token_stream.get(position).map(|t| &t.kind) {
+            Some(TokenKind::Identifier(name)) => !self.operators.joins(name) && name != "then" && name != "else",
+            Some(TokenKind::Number(_) | TokenKind::OpenParenthesis) => true,
+            _ => self.operator_at(position, Fixity::Prefix).is_some(),
+        }
+    }
+
+    fn skip(&mut self, count: usize) {
+        for _ in 0..count {
+            self.next_token();
         }
-        node
     }
 
-    fn expr(&mut self) -> Expression {
-        let mut node = self.term();
-        while match self.current_token {  // condition
-            Token::Plus | Token::Minus => true,
-            _ => false
+    /// `if condition then a else b`, or `condition ? a : b`, where `b` may be another conditional.
+    fn conditional(&mut self) -> Result<Expression, CalcError> {
+        let start = self.current_token.span;
+        let is_if = self.is_keyword("if");
+        if is_if { self.next_token(); }
+        let condition = if is_if { self.nested(start, Self::conditional)? } else { self.expression(0)? };
+        let then = if is_if { self.branch("then")? }
+        else if matches!(self.current_token.kind, TokenKind::Question) {
+            let question = self.current_token.span;
+            self.next_token();
+            self.nested(question, Self::conditional)?
         }
-        {  // repeat
-            let token = self.current_token.clone();
-            self.eat(token.clone());
-            node = Expression::Binary {
-                op: token,
-                left: Box::new(node),
-                right: Box::new(self.term())
+        else { return Ok(condition); };
+        let otherwise = self.branch(if is_if { "else" } else { ":" })?;
+        let span = Span::new(start.start, otherwise.span.end);
+        let (condition, then, otherwise) = (Box::new(condition), Box::new(then), Box::new(otherwise));
+        Ok(Expression::new(ExprKind::Conditional { condition, then, otherwise }, span))
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(&self.current_token.kind, TokenKind::Identifier(name) if name == keyword)
+    }
+
+    /// The part of a conditional after `separator`, `then`, `else` or `:`. A missing separator is
+    /// reported, and the part parsed all the same, unless nothing that follows could start it.
+    fn branch(&mut self, separator: &str) -> Result<Expression, CalcError> {
+        let found = match separator {
+            ":" => self.current_token.kind == TokenKind::Colon,
+            keyword => self.is_keyword(keyword),
+        };
+        let at = self.current_token.span;
+        if found {
+            self.next_token();
+            return self.nested(at, Self::conditional);
+        }
+        let message = format!("Expected '{}', found {}", separator, self.current_token.kind.describe());
+        self.report(CalcError::parse(message, Some(self.current_token.span)));
+        if self.at_synchronization() {
+            return Ok(Expression::new(ExprKind::Error, Span::new(at.start, at.start)));
+        }
+        self.nested(at, Self::conditional)
+    }
+
+    /// Whether the current token is the name of a unit.
+    fn is_unit(&self) -> bool {
+        matches!(&self.current_token.kind, TokenKind::Identifier(name) if Unit::find(name).is_some())
+    }
+
+    /// A unit, e.g. `km`, `m/s^2`, `kg m^2` or `kg/(m s^2)`, with the span of its last token.
+    ///
+    /// `*` and `/` are part of it only if a unit follows them, so `2 m * x` is `(2 m) * x`.
+    fn unit(&mut self) -> Result<(Unit, Span), CalcError> {
+        let (mut unit, mut end) = self.unit_power()?;
+        loop {
+            let op = self.current_token.kind.clone();
+            let next = self.peek_kind();
+            let next_is_unit = matches!(next, Some(TokenKind::Identifier(name)) if Unit::find(name).is_some());
+            let divided_group = op == TokenKind::Divide && matches!(next, Some(TokenKind::OpenParenthesis))
+                && matches!(self.token_stream.get(self.position + 2).map(|t| &t.kind),
+                    Some(TokenKind::Identifier(name)) if Unit::find(name).is_some());
+            let (power, span) = if self.is_unit() {
+                self.unit_power()?
+            }
+            else if matches!(op, TokenKind::Multiply | TokenKind::Divide) && next_is_unit {
+                self.next_token();
+                let (power, span) = self.unit_power()?;
+                if op == TokenKind::Divide { (power.powi(-1)?, span) } else { (power, span) }
+            }
+            else if divided_group {
+                self.next_token();
+                self.eat(TokenKind::OpenParenthesis)?;
+                let (mut group, _) = self.unit_power()?;
+                while self.is_unit() {
+                    group = group.checked_mul(&self.unit_power()?.0)?;
+                }
+                (group.powi(-1)?, self.eat(TokenKind::CloseParenthesis)?)
+            }
+            else {
+                return Ok((unit, end));
             };
+            unit = unit.checked_mul(&power)?;
+            end = span;
         }
-        node
+    }
+
+    /// A unit with an optional integer power, e.g. `s` or `s^-2`.
+    fn unit_power(&mut self) -> Result<(Unit, Span), CalcError> {
+        let token = self.current_token.clone();
+        let unit = match &token.kind {
+            TokenKind::Identifier(name) => Unit::find(name),
+            _ => None,
+        }.ok_or_else(|| {
+            CalcError::parse(format!("Expected a unit, found {}", token.kind.describe()), Some(token.span))
+        })?;
+        self.next_token();
+        if !matches!(self.current_token.kind, TokenKind::Power) { return Ok((unit, token.span)); }
+        self.next_token();
+        let negative = matches!(self.current_token.kind, TokenKind::Minus);
+        if matches!(self.current_token.kind, TokenKind::Minus | TokenKind::Plus) { self.next_token(); }
+        let exponent = self.current_token.clone();
+        let power = match &exponent.kind {
+            TokenKind::Number(n) => n.replace('_', "").parse::<i32>().ok(),
+            _ => None,
+        }.ok_or_else(|| CalcError::parse(
+            format!("Expected an integer power of a unit, found {}", exponent.kind.describe()), Some(exponent.span)
+        ))?;
+        self.next_token();
+        Ok((unit.powi(if negative { -power } else { power })?, Span::new(token.span.start, exponent.span.end)))
     }
 }
 
 #[test]
-#[should_panic]
 fn test_parse_empty() {
     use crate::interpreter::lexeme;
     let examples: Vec<&str> = vec![
@@ -131,10 +904,425 @@ fn test_parse_empty() {
         "\n",
         ];
     for example in examples {
-        let ts = lexeme::get_tokens(example);
+        let ts = lexeme::get_tokens(example).unwrap();
         println!("{:?}", &ts);
         let mut p = Parser::new(ts);
-        println!("Text: {:#?}\nExpr: {}\n", example, p.expr());
+        assert!(matches!(p.parse(), Err(CalcError::Parse { .. })));
+    }
+}
+
+#[test]
+fn test_parse_nesting() {
+    use crate::interpreter::lexeme;
+    // as deep as the stack of a main thread allows unoptimized, where a test thread only has 2 MiB
+    let test = std::thread::Builder::new().stack_size(8 << 20).spawn(|| {
+        let parse = |input: &str| Parser::new(lexeme::get_tokens(input).unwrap()).parse_statements();
+        let nested = |depth: usize| format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(parse(&nested(256)).is_ok());
+        // an error instead of overflowing the stack
+        let err = parse(&nested(10_000)).unwrap_err();
+        assert_eq!(err.message(), "Expression is nested too deeply");
+        assert_eq!(err.span(), Some(Span::new(256, 257)));
+        assert!(parse(&format!("{}1", "- ".repeat(10_000))).is_err());
+        assert!(parse(&format!("{}1", "2^".repeat(10_000))).is_err());
+        assert!(parse(&format!("{}1{}", "f(".repeat(10_000), ")".repeat(10_000))).is_err());
+        assert!(parse(&format!("{}1{}", "if true then ".repeat(10_000), " else 2".repeat(10_000))).is_err());
+        let parser = |input: &str| Parser::new(lexeme::get_tokens(input).unwrap()).max_depth(2);
+        assert!(parser(&nested(2)).parse_statements().is_ok());
+        assert!(parser(&nested(3)).parse_statements().is_err());
+    });
+    test.unwrap().join().unwrap();
+}
+
+#[test]
+fn test_parse_error() {
+    use crate::interpreter::lexeme;
+    let examples: Vec<&str> = vec![
+        "1 +",
+        "1 + * 2",
+        "(1 + 2",
+        "-",
+        ")",
+        "5 km to",
+        "5 km in 3",
+        "2 m^x",
+        "1 < x < 3",
+        "a ? b",
+        "if a then b",
+        "if a b else c",
+        "1 2 3",
+        "(1 + 2))",
+        "f(1) = 2) + 3",
+        "1; 2",
+        "1\n2",
+        ];
+    for example in examples {
+        let ts = lexeme::get_tokens(example).unwrap();
+        let mut p = Parser::new(ts);
+        assert!(matches!(p.parse(), Err(CalcError::Parse { .. })), "{}", example);
+    }
+}
+
+#[test]
+fn test_parse_recovering() {
+    use crate::interpreter::lexeme;
+    let recover = |input: &str| {
+        let (statements, errors) = Parser::new(lexeme::get_tokens(input).unwrap()).parse_recovering();
+        let shapes: Vec<String> = statements.iter().map(|statement| match statement {
+            Statement::Expression(expr) => shape(expr),
+            Statement::Assign { name, value } => format!("(= {} {})", name, shape(value)),
+            other => panic!("unexpected {:?}", other),
+        }).collect();
+        (shapes, errors.iter().map(|e| (e.message().to_string(), e.span().unwrap())).collect::<Vec<_>>())
+    };
+    let examples = [
+        ("1 + * 2", vec!["(+ 1 (* error 2))"], vec![("Unexpected '*'", Span::new(4, 5))]),
+        ("(1 2) + 3", vec!["(+ 1 3)"], vec![("Expected ')', found number 2", Span::new(3, 4))]),
+        ("f(1, , 2", vec!["(f 1 error 2)"], vec![
+            ("Unexpected ','", Span::new(5, 6)), ("Expected ')', found end of input", Span::new(8, 8)),
+        ]),
+        ("x = ; 2 3; y = 1 +\n4", vec!["(= x error)", "2", "(= y (+ 1 4))"], vec![
+            ("Unexpected ';'", Span::new(4, 5)),
+            ("Unexpected number 3 after the end of the expression", Span::new(8, 9)),
+        ]),
+        ("1 < 2 < 3", vec!["(< (< 1 2) 3)"], vec![
+            ("Comparisons cannot be chained, use '&&' before '<'", Span::new(6, 7)),
+        ]),
+        ("if a 1 else 2", vec!["(if a 1 2)"], vec![("Expected 'then', found number 1", Span::new(5, 6))]),
+        ("a ? b", vec!["(if a b error)"], vec![("Expected ':', found end of input", Span::new(5, 5))]),
+        ("5 km to 3; 2 m^x", vec!["error", "error"], vec![
+            ("Expected a unit, found number 3", Span::new(8, 9)),
+            ("Expected an integer power of a unit, found name 'x'", Span::new(15, 16)),
+        ]),
+    ];
+    for (input, shapes, errors) in examples {
+        let errors: Vec<(String, Span)> = errors.into_iter().map(|(m, s)| (m.to_string(), s)).collect();
+        assert_eq!(recover(input), (shapes.into_iter().map(String::from).collect(), errors), "{}", input);
+    }
+    // the first error is the one of `parse_statements`
+    let err = Parser::new(lexeme::get_tokens("1 + ) - (2").unwrap()).parse_statements().unwrap_err();
+    assert_eq!(err.span(), Some(Span::new(4, 5)));
+}
+
+#[test]
+fn test_parse_span() {
+    use crate::interpreter::lexeme;
+    let ts = lexeme::get_tokens("-(1 + 2) * 3").unwrap();
+    let expr = Parser::new(ts).parse().unwrap();
+    assert_eq!(expr.span, Span::new(0, 12));
+    match expr.kind {
+        ExprKind::Binary { left, right, .. } => {
+            assert_eq!(left.span, Span::new(0, 8));
+            assert_eq!(right.span, Span::new(11, 12));
+            match left.kind {
+                ExprKind::Unary { expr, .. } => assert_eq!(expr.span, Span::new(1, 8)),
+                other => panic!("unexpected {:?}", other),
+            }
+        },
+        other => panic!("unexpected {:?}", other),
+    }
+
+    let ts = lexeme::get_tokens("1 + * 2").unwrap();
+    assert_eq!(Parser::new(ts).parse().unwrap_err().span(), Some(Span::new(4, 5)));
+    let ts = lexeme::get_tokens("(1 + 2").unwrap();
+    assert_eq!(Parser::new(ts).parse().unwrap_err().span(), Some(Span::new(6, 6)));
+}
+
+/// The tree in prefix form, e.g. `(^ 2 (- 1))`, to compare shapes in tests.
+#[cfg(test)]
+fn shape(expr: &Expression) -> String {
+    match &expr.kind {
+        ExprKind::Number(n) | ExprKind::Variable(n) => n.clone(),
+        ExprKind::Unary { op, expr } => format!("({} {})", op.symbol(), shape(expr)),
+        ExprKind::Postfix { op, expr } => format!("({} {})", shape(expr), op.symbol()),
+        ExprKind::Binary { op, left, right } => format!("({} {} {})", op.symbol(), shape(left), shape(right)),
+        ExprKind::Implicit { left, right } => format!("(implicit {} {})", shape(left), shape(right)),
+        ExprKind::Call { name, args } => {
+            let parts: Vec<String> = std::iter::once(name.clone()).chain(args.iter().map(shape)).collect();
+            format!("({})", parts.join(" "))
+        },
+        ExprKind::WithUnit { expr, unit } => format!("({} {})", shape(expr), unit),
+        ExprKind::Convert { expr, unit } => format!("(to {} {})", shape(expr), unit),
+        ExprKind::Boolean(b) => b.to_string(),
+        ExprKind::Error => "error".to_string(),
+        ExprKind::Conditional { condition, then, otherwise } => {
+            format!("(if {} {} {})", shape(condition), shape(then), shape(otherwise))
+        },
+    }
+}
+
+#[test]
+fn test_parse_precedence() {
+    use crate::interpreter::lexeme;
+    let examples: Vec<(&str, &str)> = vec![
+        ("2 ^ 3 ^ 2", "(^ 2 (^ 3 2))"),
+        ("-2 ^ 2", "(- (^ 2 2))"),
+        ("2 ^ -1", "(^ 2 (- 1))"),
+        ("2 ^ -3 ^ 2", "(^ 2 (- (^ 3 2)))"),
+        ("(-2) ^ 2", "(^ (- 2) 2)"),
+        ("--2", "(- (- 2))"),
+        ("-2 * 3", "(* (- 2) 3)"),
+        ("1 - 2 - 3", "(- (- 1 2) 3)"),
+        ("8 / 4 / 2", "(/ (/ 8 4) 2)"),
+        ("1 + 2 * 3 ^ 2", "(+ 1 (* 2 (^ 3 2)))"),
+        ("2 * 3 ^ 2 ^ -1", "(* 2 (^ 3 (^ 2 (- 1))))"),
+        ("-sqrt(2) ^ 2", "(- (^ (sqrt 2) 2))"),
+        ("log(2, x + 1) * 3", "(* (log 2 (+ x 1)) 3)"),
+        ("max(1, min(2, 3), 4)", "(max 1 (min 2 3) 4)"),
+        ("3 m * 4 s^-1", "(* (3 m) (4 s^-1))"),
+        ("100 kg * 9.81 m/s^2 in N", "(to (* (100 kg) (9.81 m/s^2)) N)"),
+        ("2 kg m / s to N", "(to (2 kg m/s) N)"),
+        ("1 / (2 kg/(m s)) ^ 2", "(/ 1 (^ (2 kg/(m s)) 2))"),
+        ("x to km to mi", "(to (to x km) mi)"),
+        ("1 + 2 < 3 * 4", "(< (+ 1 2) (* 3 4))"),
+        ("a || b && !c == d", "(|| a (&& b (== (! c) d)))"),
+        ("x > 1 km to m", "(> x (to (1 km) m))"),
+        ("a ? b : c ? d : e", "(if a b (if c d e))"),
+        ("if a || b then 1 else if c then 2 else 3 + 4", "(if (|| a b) 1 (if c 2 (+ 3 4)))"),
+        ("if if a then b else c then d else e", "(if (if a b c) d e)"),
+        ("true == !false", "(== true (! false))"),
+        ("a | b xor c & d << e + f * g", "(| a (xor b (& c (<< d (+ e (* f g))))))"),
+        ("1 << 2 >> 3", "(>> (<< 1 2) 3)"),
+        ("-a mod b // c % d", "(% (// (% (- a) b) c) d)"),
+        ("~x ^ 2 & 1 == 0", "(== (& (~ (^ x 2)) 1) 0)"),
+        ("5! + 3!!", "(+ (5 !) (3 !!))"),
+        ("-3! ^ 2", "(- (^ (3 !) 2))"),
+        ("2 ^ 3!", "(^ 2 (3 !))"),
+        ("5! !", "((5 !) !)"),
+        ("!!a", "(! (! a))"),
+        ("200 * 15%", "(* 200 (15 %))"),
+        ("15% + 2", "(% 15 (+ 2))"),
+        ("a % % b", "(% (a %) b)"),
+        ("x% to m", "(to (x %) m)"),
+        ("5 m%", "((5 m) %)"),
+        ("2(3 + 4)", "(implicit 2 (+ 3 4))"),
+        ("(1 + 2)(3 + 4)", "(implicit (+ 1 2) (+ 3 4))"),
+        ("3x^2", "(implicit 3 (^ x 2))"),
+        ("2 pi r", "(implicit (implicit 2 pi) r)"),
+        ("4 sqrt(2) / 2x", "(/ (implicit 4 (sqrt 2)) (implicit 2 x))"),
+        ("-2x", "(implicit (- 2) x)"),
+        ("2x! y", "(implicit (implicit 2 (x !)) y)"),
+        ("2 km x + a m", "(+ (implicit (2 km) x) (implicit a m))"),
+        ("2x in km", "(to (implicit 2 x) km)"),
+        ("if a then 2b else c", "(if a (implicit 2 b) c)"),
+        ("15% x", "(% 15 x)"),
+        ];
+    for (example, expected) in examples {
+        let ts = lexeme::get_tokens(example).unwrap();
+        assert_eq!(shape(&Parser::new(ts).parse().unwrap()), expected, "{}", example);
+    }
+}
+
+#[test]
+fn test_parse_implicit_strict() {
+    use crate::interpreter::lexeme;
+    let parse = |input: &str| {
+        Parser::new(lexeme::get_tokens(input).unwrap()).implicit_multiplication(ImplicitMultiplication::Strict).parse()
+    };
+    assert_eq!(shape(&parse("1/2x").unwrap()), "(implicit (/ 1 2) x)");
+    assert_eq!(shape(&parse("2x * 3y").unwrap()), "(implicit (* (implicit 2 x) 3) y)");
+    assert_eq!(shape(&parse("-2x^2 + 1").unwrap()), "(+ (implicit (- 2) (^ x 2)) 1)");
+    // written back the same in either way
+    assert_eq!(parse("1/2x").unwrap().infix(), "(1 / 2) x");
+    assert!(matches!(parse("2 3"), Err(CalcError::Parse { .. })));
+}
+
+#[test]
+fn test_parse_operators() {
+    use crate::interpreter::lexeme::Tokenizer;
+    let mut table = OperatorTable::default();
+    table.infix("<>", 95, Associativity::Left, "avg").unwrap();
+    table.infix("@", operator::POWER, Associativity::Right, "pow").unwrap();
+    table.infix("per", operator::PRODUCT, Associativity::Left, "div").unwrap();
+    table.infix("~~", operator::COMPARISON, Associativity::None, "near").unwrap();
+    table.prefix("neg", operator::PREFIX, "negate").unwrap();
+    table.postfix("squared", operator::POSTFIX, "sq").unwrap();
+    let parse_with = |input: &str, table: &OperatorTable| {
+        let tokens = Tokenizer::new(input).symbols(table.symbol_chars()).tokens().unwrap();
+        Parser::with_operators(tokens, table.clone()).parse()
+    };
+    let parse = |input: &str| parse_with(input, &table);
+    let examples = [
+        ("a <> b * 2", "(avg a (* b 2))"),
+        ("a<>b + c <> d <> e", "(+ (avg a b) (avg (avg c d) e))"),
+        ("2 @ 3 @ 2", "(pow 2 (pow 3 2))"),
+        ("a per b per c", "(div (div a b) c)"),
+        ("neg x squared + 1", "(+ (negate (sq x)) 1)"),
+        ("2 neg x", "(implicit 2 (negate x))"),
+        ("3!! + 4! % 2", "(+ (3 !!) (% (4 !) 2))"),
+        ("a < -b", "(< a (- b))"),
+        ("1 ~~ 2 && 3 ~~ 4", "(&& (near 1 2) (near 3 4))"),
+    ];
+    for (input, expected) in examples {
+        assert_eq!(shape(&parse(input).unwrap()), expected, "{}", input);
+    }
+    for input in ["a < > b", "1 ~~ 2 ~~ 3", "1 < 2 == 3", "neg"] {
+        assert!(matches!(parse(input), Err(CalcError::Parse { .. })), "{}", input);
+    }
+    // without the operator, the name is a variable again
+    let mut table = table.clone();
+    table.remove("per", Fixity::Infix);
+    assert_eq!(shape(&parse_with("a per b", &table).unwrap()), "(implicit (implicit a per) b)");
+}
+
+#[test]
+fn test_parse_dialect() {
+    use crate::interpreter::lexeme::Tokenizer;
+    let parse = |input: &str, dialect: Dialect| {
+        let tokens = Tokenizer::new(input).dialect(dialect).tokens().unwrap();
+        Parser::new(tokens).dialect(dialect).parse_statements()
+    };
+    let shapes = |input: &str, dialect: Dialect| -> Vec<String> {
+        parse(input, dialect).unwrap().iter().map(|statement| match statement {
+            Statement::Expression(expr) => shape(expr),
+            other => panic!("unexpected {:?}", other),
+        }).collect()
+    };
+    assert_eq!(shapes("-2**3**2 ^ 1 // 2", Dialect::C), ["(xor (- (^ 2 (^ 3 2))) (// 1 2))"]);
+    assert_eq!(shapes("7 % 2 * 3", Dialect::C), ["(* (% 7 2) 3)"]);
+    assert!(parse("7 % * 3", Dialect::C).is_err());
+    assert_eq!(shapes("=15% + 2", Dialect::Spreadsheet), ["(+ (15 %) 2)"]);
+    assert_eq!(shapes("=max(1; 2^2)\n=3", Dialect::Spreadsheet), ["(max 1 (^ 2 2))", "3"]);
+    assert!(parse("7 % 3", Dialect::Spreadsheet).is_err());
+    // `;` is reported as written, not as the `,` it stands for
+    let err = parse("=1; 2", Dialect::Spreadsheet).unwrap_err();
+    assert_eq!(err.message(), "Unexpected ';' after the end of the expression");
+    assert_eq!(err.span(), Some(Span::new(2, 3)));
+    assert!(parse("=1 + 2", Dialect::Standard).is_err());
+    // written back in the same dialect
+    let expr = |input: &str, dialect: Dialect| match parse(input, dialect).unwrap().remove(0) {
+        Statement::Expression(expr) => expr.infix_in(dialect),
+        other => panic!("unexpected {:?}", other),
+    };
+    assert_eq!(expr("x**2 ^ y", Dialect::C), "x ** 2 ^ y");
+    assert_eq!(expr("7 mod 2 + 15%", Dialect::Spreadsheet), "7 mod 2 + 15%");
+}
+
+#[test]
+fn test_parse_statement() {
+    use crate::interpreter::lexeme;
+    let parse = |input: &str| Parser::new(lexeme::get_tokens(input).unwrap()).parse_statement();
+    match parse("rate = 0.07 * x").unwrap() {
+        Statement::Assign { name, value } => {
+            assert_eq!(name, "rate");
+            assert_eq!(value.span, Span::new(7, 15));
+            assert_eq!(shape(&value), "(* 0.07 x)");
+        },
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(matches!(parse("rate * 2").unwrap(), Statement::Expression(_)));
+    assert!(matches!(parse("rate ="), Err(CalcError::Parse { .. })));
+    // a keyword could never be read back
+    let err = parse("if = 1").unwrap_err();
+    assert_eq!(err.message(), "'if' is a keyword, not a variable name");
+    assert_eq!(err.span(), Some(Span::new(0, 2)));
+    assert_eq!(parse("true = 0").unwrap_err().span(), Some(Span::new(0, 4)));
+    assert_eq!(parse("  then = 2").unwrap_err().span(), Some(Span::new(2, 6)));
+    // nor would a unit, as `3s` is 3 seconds
+    let err = parse("s = 2").unwrap_err();
+    assert_eq!(err.message(), "'s' is a unit, not a variable name");
+    assert_eq!(err.span(), Some(Span::new(0, 1)));
+    assert_eq!(parse("f(x, km) = x").unwrap_err().message(), "'km' is a unit, not a parameter name");
+    assert!(parse("sum = 2").is_ok());
+    assert!(matches!(parse("max(1,)"), Err(CalcError::Parse { .. })));
+    assert!(matches!(parse("max(1 2)"), Err(CalcError::Parse { .. })));
+    match parse("f() + 1").unwrap() {
+        Statement::Expression(expr) => assert_eq!(shape(&expr), "(+ (f) 1)"),
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_statements() {
+    use crate::interpreter::lexeme;
+    let parse = |input: &str| Parser::new(lexeme::get_tokens(input).unwrap()).parse_statements();
+    let shapes = |input: &str| -> Vec<String> {
+        parse(input).unwrap().iter().map(|statement| match statement {
+            Statement::Expression(expr) => shape(expr),
+            Statement::Assign { name, value } => format!("(= {} {})", name, shape(value)),
+            Statement::Define { name, .. } => format!("(define {})", name),
+        }).collect()
+    };
+    assert_eq!(shapes("rate = 0.07; 200 * rate"), vec!["(= rate 0.07)", "(* 200 rate)"]);
+    assert_eq!(shapes(";\n1;; 2;\n\n"), vec!["1", "2"]);
+    assert_eq!(shapes("f(x) = x\nf(2)"), vec!["(define f)", "(f 2)"]);
+    // a line goes on inside parentheses and after an operator, but not before one
+    assert_eq!(shapes("max(1,\n2) *\n3\n- 4"), vec!["(* (max 1 2) 3)", "(- 4)"]);
+    assert_eq!(shapes("if x then\n1 else\n2 to\nkm"), vec!["(if x 1 (to 2 km))"]);
+    assert_eq!(shapes("15%\n2"), vec!["(15 %)", "2"]);
+
+    let err = parse("1; (1 + 2)) * 3").unwrap_err();
+    assert_eq!(err.message(), "Unexpected ')' after the end of the expression");
+    assert_eq!(err.span(), Some(Span::new(10, 11)));
+    assert_eq!(parse("1 + 2 3").unwrap_err().span(), Some(Span::new(6, 7)));
+    assert_eq!(parse(" ;\n; ").unwrap_err().message(), "Empty expression");
+    assert!(matches!(parse("1 +\n"), Err(CalcError::Parse { .. })));
+}
+
+#[test]
+fn test_parse_definition() {
+    use crate::interpreter::lexeme;
+    let parse = |input: &str| Parser::new(lexeme::get_tokens(input).unwrap()).parse_statement();
+    match parse("f(x, y) = x ^ 2 + y").unwrap() {
+        Statement::Define { name, params, body } => {
+            assert_eq!(name, "f");
+            assert_eq!(params, vec!["x", "y"]);
+            assert_eq!(shape(&body), "(+ (^ x 2) y)");
+        },
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(matches!(parse("one() = 1").unwrap(), Statement::Define { .. }));
+    assert!(matches!(parse("f(x) + 1").unwrap(), Statement::Expression(_)));
+    let err = parse("f(x, 1) = x").unwrap_err();
+    assert_eq!(err.message(), "Expected parameter name, found number 1");
+    assert_eq!(err.span(), Some(Span::new(5, 6)));
+    assert_eq!(parse("f(x, x) = x").unwrap_err().span(), Some(Span::new(5, 6)));
+    let err = parse("if(x) = 1").unwrap_err();
+    assert_eq!(err.message(), "'if' is a keyword, not a function name");
+    assert_eq!(err.span(), Some(Span::new(0, 2)));
+    assert_eq!(parse("f(x, else) = x").unwrap_err().span(), Some(Span::new(5, 9)));
+}
+
+#[test]
+fn test_expression_infix() {
+    use crate::interpreter::lexeme;
+    let examples: Vec<(&str, &str)> = vec![
+        ("x^2+y", "x ^ 2 + y"),
+        ("(1 + 2) * 3", "(1 + 2) * 3"),
+        ("1 - (2 - 3)", "1 - (2 - 3)"),
+        ("(1 - 2) - 3", "1 - 2 - 3"),
+        ("(2 ^ 3) ^ 2", "(2 ^ 3) ^ 2"),
+        ("2 ^ 3 ^ 2", "2 ^ 3 ^ 2"),
+        ("-2 ^ 2", "-2 ^ 2"),
+        ("(-2) ^ 2", "(-2) ^ 2"),
+        ("2 ^ -x", "2 ^ (-x)"),
+        ("-(1 + x)", "-(1 + x)"),
+        ("log(2, x / (y * 3))", "log(2, x / (y * 3))"),
+        ("3 m/s^2*x", "3 m/s^2 * x"),
+        ("(5 km) in mi", "5 km to mi"),
+        ("(x to mi) * 2", "(x to mi) * 2"),
+        ("(1 < 2) == (a && b || c)", "(1 < 2) == (a && b || c)"),
+        ("a ? b : c ? d : e", "if a then b else if c then d else e"),
+        ("(a ? b : c) + 1", "(if a then b else c) + 1"),
+        ("!(x >= 1)", "!(x >= 1)"),
+        ("(a | b) & c xor d", "(a | b) & c xor d"),
+        ("a mod (b // c)", "a % (b // c)"),
+        ("~(x << 1)", "~(x << 1)"),
+        ("(-3)! + (2 ^ 3)!!", "(-3)! + (2 ^ 3)!!"),
+        ("-(3!)", "-3!"),
+        ("(15%) + 2", "(15%) + 2"),
+        ("(a * b%) - c % d", "(a * b%) - c % d"),
+        ("2(3+4)", "2 (3 + 4)"),
+        ("(x)(y)", "x y"),
+        ("(2 x)(y + 1)", "(2 x) (y + 1)"),
+        ("1 / (2x) * 3", "1 / (2 x) * 3"),
+        ("(1 / 2)x", "(1 / 2) x"),
+        ("2(-x)(3)", "2 (-x) (3)"),
+        ("(a)(b c)", "(a) (b c)"),
+        ];
+    for (example, expected) in examples {
+        let ts = lexeme::get_tokens(example).unwrap();
+        assert_eq!(Parser::new(ts).parse().unwrap().infix(), expected, "{}", example);
     }
 }
 
@@ -144,15 +1332,15 @@ fn test() {
     let examples: Vec<&str> = vec![
         "1",
         "1 * 2",
-        "         1.14 \t\n +      5.1 * \t0.4",
+        "         1.14 \t +\n      5.1 * \t0.4",
         "1 / 2 * 3",
         "1 * 2 / 3",
         "-(114 * (5 + 1)) / 4",
         "1 * 2 ^ (3 + 4)"
         ];
     for example in examples {
-        let ts = lexeme::get_tokens(example);
+        let ts = lexeme::get_tokens(example).unwrap();
         let mut p = Parser::new(ts);
-        println!("Text: {:#?}\nExpr: {}\n", example, p.expr());
+        println!("Text: {:#?}\nExpr: {}\n", example, p.parse().unwrap());
     }
 }
\ No newline at end of file
@@ -0,0 +1,63 @@
+use std::fmt::Display;
+
+use crate::{error::CalcError, number::unit::Quantity};
+
+/// What an expression comes to: a number, with or without a unit, or the truth of a comparison.
+#[derive(Debug, Clone)]
+pub enum Value<N> {
+    Number(Quantity<N>),
+    Boolean(bool),
+}
+
+impl<N> Value<N> {
+    pub fn as_number(&self) -> Option<&Quantity<N>> {
+        match self {
+            Value::Number(number) => Some(number),
+            Value::Boolean(_) => None,
+        }
+    }
+
+    pub fn as_boolean(&self) -> Option<bool> {
+        match self {
+            Value::Number(_) => None,
+            Value::Boolean(b) => Some(*b),
+        }
+    }
+
+    /// The number, or an error for a boolean.
+    pub(crate) fn into_number(self) -> Result<Quantity<N>, CalcError> {
+        match self {
+            Value::Number(number) => Ok(number),
+            Value::Boolean(_) => Err(CalcError::wrong_type("Expected a number, found a boolean")),
+        }
+    }
+
+    /// The boolean, or an error for a number, which is neither true nor false.
+    pub(crate) fn into_boolean(self) -> Result<bool, CalcError> {
+        match self {
+            Value::Number(_) => Err(CalcError::wrong_type("Expected a boolean, found a number")),
+            Value::Boolean(b) => Ok(b),
+        }
+    }
+}
+
+impl<N> From<Quantity<N>> for Value<N> {
+    fn from(number: Quantity<N>) -> Self {
+        Value::Number(number)
+    }
+}
+
+impl<N> From<bool> for Value<N> {
+    fn from(b: bool) -> Self {
+        Value::Boolean(b)
+    }
+}
+
+impl<N: Display> Display for Value<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Number(number) => write!(f, "{}", number),
+            Value::Boolean(b) => write!(f, "{}", b),
+        }
+    }
+}
@@ -1,63 +1,136 @@
-use std::{ffi::{CString, CStr}, os::raw::c_char, panic};
+#[cfg(feature="ffi")]
+use std::{ffi::{CString, CStr}, os::raw::c_char};
 
 use crate::interpreter::{
-    lexeme::{get_tokens, show_tokens},
-    interpret::eval,
-    parsing::Parser
+    lexeme::{show_tokens, Token, Tokenizer},
+    interpret::execute,
+    parsing::{Parser, Statement}
+};
+
+pub use crate::{
+    error::{CalcError, Span},
+    interpreter::{
+        environment::Environment,
+        operator::{Associativity, Fixity, OperatorTable},
+        value::Value
+    },
+    number::{
+        bigint::BigInt,
+        complex::Complex,
+        context::{Arithmetic, Context, Dialect, ImplicitMultiplication, RoundingMode},
+        decimal::Decimal,
+        format::{Digits, FormatOptions, FractionStyle, Notation},
+        numeric::{Number, Numeric},
+        rational::Rational,
+        unit::{Quantity, Unit}
+    }
 };
 
+mod error;
 mod number;
 mod interpreter;
 
 enum Mode { Calculate, Lexeme, Tree }
 
-fn exec(input: &str, mode: Mode) -> String {
-    let result = panic::catch_unwind(|| {
-        let token_stream = get_tokens(input);
-        let mut parser = Parser::new(token_stream.clone());
-        match mode {
-            Mode::Calculate => eval(parser.parse()).to_string(),
-            Mode::Lexeme => show_tokens(&token_stream),
-            Mode::Tree => format!("{:#?}", parser.parse()),
-        }
-    });
-    match result {
-        Ok(s) => s,
-        Err(_) => {
-            format!("@error")  // todo: more user-friendly
-        }
+fn exec(input: &str, mode: Mode, env: &mut Environment, ctx: &Context) -> Result<String, CalcError> {
+    let token_stream = tokens(input, env.operators(), ctx)?;
+    match mode {
+        Mode::Calculate => {
+            let statements = parse(token_stream, env.operators(), ctx)?;
+            Ok(run(&statements, env, ctx)?.pop().unwrap_or_default())
+        },
+        Mode::Lexeme => Ok(show_tokens(&token_stream)),
+        Mode::Tree => Ok(show_tree(&parse(token_stream, env.operators(), ctx)?)),
     }
 }
 
+/// The tree of a single statement alone, or the list of them.
+fn show_tree(statements: &[Statement]) -> String {
+    match statements {
+        [statement] => format!("{:#?}", statement),
+        statements => format!("{:#?}", statements),
+    }
+}
+
+/// The tokens of `input` in the dialect of `ctx`, with the characters of the symbols of `operators`.
+fn tokens(input: &str, operators: &OperatorTable, ctx: &Context) -> Result<Vec<Token>, CalcError> {
+    let tokenizer = Tokenizer::new(input).full_width(ctx.full_width).dialect(ctx.dialect);
+    tokenizer.symbols(operators.symbol_chars()).tokens()
+}
+
+fn parser(token_stream: Vec<Token>, operators: &OperatorTable, ctx: &Context) -> Parser {
+    Parser::with_operators(token_stream, operators.clone())
+        .implicit_multiplication(ctx.implicit)
+        .dialect(ctx.dialect)
+        .max_depth(ctx.max_depth)
+}
+
+fn parse(token_stream: Vec<Token>, operators: &OperatorTable, ctx: &Context) -> Result<Vec<Statement>, CalcError> {
+    parser(token_stream, operators, ctx).parse_statements()
+}
+
+/// Run the statements one after the other, with what each of them comes to.
+fn run(statements: &[Statement], env: &mut Environment, ctx: &Context) -> Result<Vec<String>, CalcError> {
+    let mut results = vec![];
+    for statement in statements {
+        results.push(match (execute(statement, env, ctx)?, statement) {
+            (Some(Value::Number(value)), _) => ctx.format.format_quantity(&value, ctx)?,
+            (Some(Value::Boolean(b)), _) => b.to_string(),
+            // a definition shows itself, as it was understood
+            (None, Statement::Define { name, .. }) => env.definition_in(name, ctx.dialect).unwrap_or_default(),
+            (None, _) => String::new(),
+        });
+    }
+    Ok(results)
+}
+
 // ---- C FFI ----
 
-#[no_mangle]
+/// Run `mode` on a C string, returning a newly allocated C string.
+/// Errors are reported as a string starting with `@error`.
 #[cfg(feature="ffi")]
-pub extern "C" fn calc_calculate(input_ptr: *const c_char) -> *const c_char {
-    let input = unsafe { CStr::from_ptr(input_ptr) }.to_str().unwrap();
-    let result = exec(input, Mode::Calculate);
-    let result_cstr = CString::new(result).unwrap();
+unsafe fn exec_ffi(input_ptr: *const c_char, mode: Mode) -> *const c_char {
+    let result = match unsafe { CStr::from_ptr(input_ptr) }.to_str() {
+        Ok(input) => exec(input, mode, &mut Environment::new(), &Context::default())
+            .unwrap_or_else(|e| format!("@error: {}", e)),
+        Err(_) => "@error: input is not valid UTF-8".to_string(),
+    };
+    // the message may contain a NUL copied from the input, which C cannot represent
+    let result_cstr = CString::new(result).unwrap_or_else(|_| c"@error".to_owned());
     result_cstr.into_raw()
 }
 
+/// # Safety
+///
+/// `input_ptr` must point to a valid NUL-terminated string.
+/// The returned string must be released with `calc_free`.
 #[no_mangle]
 #[cfg(feature="ffi")]
-pub extern "C" fn calc_lexeme(input_ptr: *const c_char) -> *const c_char {
-    let input = unsafe { CStr::from_ptr(input_ptr) }.to_str().unwrap();
-    let result = exec(input, Mode::Lexeme);
-    let result_cstr = CString::new(result).unwrap();
-    result_cstr.into_raw()
+pub unsafe extern "C" fn calc_calculate(input_ptr: *const c_char) -> *const c_char {
+    exec_ffi(input_ptr, Mode::Calculate)
 }
 
+/// # Safety
+///
+/// Same as `calc_calculate`.
 #[no_mangle]
 #[cfg(feature="ffi")]
-pub extern "C" fn calc_tree(input_ptr: *const c_char) -> *const c_char {
-    let input = unsafe { CStr::from_ptr(input_ptr) }.to_str().unwrap();
-    let result = exec(input, Mode::Tree);
-    let result_cstr = CString::new(result).unwrap();
-    result_cstr.into_raw()
+pub unsafe extern "C" fn calc_lexeme(input_ptr: *const c_char) -> *const c_char {
+    exec_ffi(input_ptr, Mode::Lexeme)
 }
 
+/// # Safety
+///
+/// Same as `calc_calculate`.
+#[no_mangle]
+#[cfg(feature="ffi")]
+pub unsafe extern "C" fn calc_tree(input_ptr: *const c_char) -> *const c_char {
+    exec_ffi(input_ptr, Mode::Tree)
+}
+
+/// # Safety
+///
+/// `ptr` must have been returned by one of the functions above, and not freed yet.
 #[no_mangle]
 #[cfg(feature="ffi")]
 pub unsafe extern "C" fn calc_free(ptr: *mut c_char) {
@@ -66,14 +139,88 @@ pub unsafe extern "C" fn calc_free(ptr: *mut c_char) {
 
 // ---- Rust lib ----
 
-pub fn calculate(input: &str) -> String {
-    exec(input, Mode::Calculate)
+pub fn calculate(input: &str) -> Result<String, CalcError> {
+    calculate_with(input, &Context::default())
+}
+
+/// Like `calculate`, with the limits and settings of `ctx`.
+pub fn calculate_with(input: &str, ctx: &Context) -> Result<String, CalcError> {
+    exec(input, Mode::Calculate, &mut Environment::new(), ctx)
+}
+
+/// Like `calculate_with`, reading variables and functions from `env`, and keeping there
+/// the ones assigned or defined by `input`, e.g. `rate = 0.07` or `f(x) = x * rate`.
+pub fn calculate_in(input: &str, env: &mut Environment, ctx: &Context) -> Result<String, CalcError> {
+    exec(input, Mode::Calculate, env, ctx)
+}
+
+/// Like `calculate_in`, with what every statement comes to instead of only the last one:
+///
+/// ```
+/// use calc::{calculate_all, Context, Environment};
+///
+/// let results = calculate_all("rate = 0.07; 200 * (1 + rate)", &mut Environment::new(), &Context::default());
+/// assert_eq!(results.unwrap(), ["0.07", "214.0"]);
+/// ```
+pub fn calculate_all(input: &str, env: &mut Environment, ctx: &Context) -> Result<Vec<String>, CalcError> {
+    let statements = parse(tokens(input, env.operators(), ctx)?, env.operators(), ctx)?;
+    run(&statements, env, ctx)
+}
+
+/// Like `calculate_in`, with numbers of any type `N` instead of the ones of `ctx.arithmetic`,
+/// e.g. `f64` where speed matters more than exact digits. The value is given as it is, a number with
+/// its unit or a boolean, or `None` for a function definition.
+pub fn evaluate<N: Numeric>(
+    input: &str, env: &mut Environment<N>, ctx: &Context
+) -> Result<Option<Value<N>>, CalcError> {
+    Ok(evaluate_all(input, env, ctx)?.pop().flatten())
 }
 
-pub fn lexeme(input: &str) -> String {
-    exec(input, Mode::Lexeme)
+/// Like `evaluate`, with the value of every statement instead of only the last one.
+pub fn evaluate_all<N: Numeric>(
+    input: &str, env: &mut Environment<N>, ctx: &Context
+) -> Result<Vec<Option<Value<N>>>, CalcError> {
+    let token_stream = tokens(input, env.operators(), ctx)?;
+    parse(token_stream, env.operators(), ctx)?.iter().map(|statement| execute(statement, env, ctx)).collect()
 }
 
-pub fn tree(input: &str) -> String {
-    exec(input, Mode::Tree)
-}
\ No newline at end of file
+pub fn lexeme(input: &str) -> Result<String, CalcError> {
+    lexeme_with(input, &Context::default())
+}
+
+/// Like `lexeme`, in the dialect of `ctx`.
+pub fn lexeme_with(input: &str, ctx: &Context) -> Result<String, CalcError> {
+    exec(input, Mode::Lexeme, &mut Environment::new(), ctx)
+}
+
+pub fn tree(input: &str) -> Result<String, CalcError> {
+    tree_with(input, &Context::default())
+}
+
+/// Like `tree`, in the dialect of `ctx`.
+pub fn tree_with(input: &str, ctx: &Context) -> Result<String, CalcError> {
+    exec(input, Mode::Tree, &mut Environment::new(), ctx)
+}
+
+/// Like `tree_with`, with the operators of `env`, and every error at once instead of the first one,
+/// e.g. for an editor to mark them all. The tree has an `Error` node for each part that could not be parsed.
+/// The tokenizer stops at its first error, which is then the only one, without a tree.
+///
+/// ```
+/// use calc::{tree_diagnostics, Associativity, Context, Environment};
+///
+/// let mut env: Environment = Environment::new();
+/// env.operators_mut().infix("<>", 95, Associativity::Left, "avg").unwrap();
+/// let (tree, errors) = tree_diagnostics("1 <> 2 + ) <> 3", &env, &Context::default());
+/// assert!(tree.contains("\"avg\""));
+/// assert_eq!(errors.len(), 1);
+/// ```
+pub fn tree_diagnostics<N: Numeric>(input: &str, env: &Environment<N>, ctx: &Context) -> (String, Vec<CalcError>) {
+    match tokens(input, env.operators(), ctx) {
+        Ok(token_stream) => {
+            let (statements, errors) = parser(token_stream, env.operators(), ctx).parse_recovering();
+            (show_tree(&statements), errors)
+        },
+        Err(error) => (String::new(), vec![error]),
+    }
+}
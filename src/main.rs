@@ -1,50 +1,155 @@
-use crate::interpreter::{
-    lexeme::{get_tokens, show_tokens},
-    interpret::eval,
-    parsing::Parser
+use calc::{
+    calculate_all, calculate_with, lexeme_with, tree_diagnostics, Arithmetic, Context, Environment, FormatOptions,
+    Number
 };
 
-mod number;
-mod interpreter;
-
 const VERSION: &str = "1.2";
 
 fn main() {
     println!("Calculator, version {}\n", VERSION);
-    let args: Vec<String> = std::env::args().collect();
-
-    if args.len() > 2 {
-        let tokens = get_tokens(&args[2]);
-        let mut parser = Parser::new(tokens.clone());
-        println!("Input: {}", args[2]);
-        match args[1].as_str() {
-            "--calc" | "-c" => {
-                    println!("Result: {}", eval(parser.parse()))
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let ctx = match take_options(&mut args) {
+        Ok(ctx) => ctx,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(2);
+        }
+    };
+
+    if args.len() > 1 {
+        println!("Input: {}", args[1]);
+        let result = match args[0].as_str() {
+            "--calc" | "-c" => calculate_with(&args[1], &ctx).map(|r| format!("Result: {}", r)),
+            "--lexeme" | "-l" => lexeme_with(&args[1], &ctx).map(|r| format!("Result:\n{}", r)),
+            "--tree" | "-t" => match tree_diagnostics(&args[1], &Environment::<Number>::new(), &ctx) {
+                (tree, errors) if errors.is_empty() => Ok(format!("Result: {}", tree)),
+                // all the errors, after the tree as far as it goes
+                (tree, errors) => {
+                    if !tree.is_empty() { println!("Result: {}", tree); }
+                    errors.iter().for_each(|e| eprintln!("{}", e.render(&args[1])));
+                    std::process::exit(1);
+                },
             },
-            "--lexeme" | "-l" => println!("Result:\n{}", show_tokens(&tokens)),
-            "--tree" | "-t" => println!("Result: {}", parser.parse()),
-            _ => { /* ignore */ }
+            _ => { /* ignore */ return; }
+        };
+        match result {
+            Ok(output) => println!("{}", output),
+            Err(e) => {
+                eprintln!("{}", e.render(&args[1]));
+                std::process::exit(1);
+            }
         }
     }
     else {
-        shell();
+        shell(&ctx);
+    }
+}
+
+/// Remove the settings options from `args`, and build the context out of them.
+fn take_options(args: &mut Vec<String>) -> Result<Context, String> {
+    let mut ctx = Context::default();
+    let mut group = None;
+    let mut i = 0;
+    while i < args.len() {
+        let option = args[i].clone();
+        match option.as_str() {
+            "--no-full-width" => ctx.full_width = false,
+            "--trim" => ctx.format.trim = true,
+            "--precision" | "-p" | "--rounding" | "-r" | "--arithmetic" | "--implicit" | "--dialect"
+            | "--fixed" | "--significant" | "--notation" | "--group" | "--decimal-mark" | "--fraction" => { },
+            _ => {
+                i += 1;
+                continue;
+            },
+        }
+        args.remove(i);
+        if matches!(option.as_str(), "--no-full-width" | "--trim") { continue; }
+        if i >= args.len() { return Err(format!("Missing value for '{}'", option)); }
+        let value = args.remove(i);
+        match option.as_str() {
+            "--precision" | "-p" => {
+                ctx.precision = value.parse().map_err(|_| format!("Invalid precision '{}'", value))?;
+            },
+            "--rounding" | "-r" => ctx.rounding = value.parse()?,
+            "--arithmetic" => ctx.arithmetic = value.parse()?,
+            "--implicit" => ctx.implicit = value.parse()?,
+            "--dialect" => ctx.dialect = value.parse()?,
+            "--group" => group = Some(value),
+            _ => ctx.format.set(option.trim_start_matches('-'), &value)?,
+        }
+    }
+    // after the decimal mark, so that `--group . --decimal-mark ,` swaps them
+    if let Some(group) = group {
+        ctx.format.set("group", &group)?;
     }
+    Ok(ctx)
 }
 
-fn shell() {
+fn shell(ctx: &Context) {
     println!("Please input the expression in a line, or 'quit'");
+    let mut env = Environment::new();
+    let mut ctx = ctx.clone();
     loop {
         let mut input = String::new();
-        std::io::stdin().read_line(&mut input).unwrap();
+        if std::io::stdin().read_line(&mut input).unwrap_or(0) == 0 { break; }  // EOF
         let cmd = input.trim();
         match cmd {
             "quit" | "exit" => break,
             "" => { /* when empty */ continue; }
+            ":functions" => {
+                env.functions().iter().for_each(|definition| println!(": {}", definition));
+                continue;
+            },
+            ":variables" => {
+                env.variables().iter().for_each(|(name, value)| println!(": {} = {}", name, value));
+                continue;
+            },
+            _ if cmd.starts_with(":arithmetic") => {
+                arithmetic_command(cmd, &mut ctx.arithmetic);
+                continue;
+            },
+            _ if cmd.starts_with(":format") => {
+                format_command(cmd, &mut ctx.format);
+                continue;
+            },
             _ => { /* ignore */ }
         }
 
-        let tokens = get_tokens(cmd);
-        let mut parser = Parser::new(tokens.clone());
-        println!(": {}", eval(parser.parse()))
+        match calculate_all(cmd, &mut env, &ctx) {
+            Ok(results) => results.iter().for_each(|result| println!(": {}", result)),
+            Err(e) => println!("{}", e.render(cmd)),
+        }
+    }
+}
+
+/// `:format` shows the options, `:format reset` restores them, and `:format <name> <value>` changes one.
+fn format_command(cmd: &str, format: &mut FormatOptions) {
+    let words: Vec<&str> = cmd.split_whitespace().skip(1).collect();
+    let result = match words[..] {
+        [] => Ok(()),
+        ["reset"] => {
+            *format = FormatOptions::default();
+            Ok(())
+        },
+        [name, value] => format.set(name, value),
+        _ => Err("Usage: :format [reset | <name> <value>]".to_string()),
+    };
+    match result {
+        Ok(()) => println!(": {}", format),
+        Err(message) => println!("{}", message),
+    }
+}
+
+/// `:arithmetic` shows which numbers are used, and `:arithmetic <decimal | rational>` changes it.
+fn arithmetic_command(cmd: &str, arithmetic: &mut Arithmetic) {
+    let words: Vec<&str> = cmd.split_whitespace().skip(1).collect();
+    let result = match words[..] {
+        [] => Ok(()),
+        [value] => value.parse().map(|value| *arithmetic = value),
+        _ => Err("Usage: :arithmetic [decimal | rational]".to_string()),
+    };
+    match result {
+        Ok(()) => println!(": {}", arithmetic),
+        Err(message) => println!("{}", message),
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,606 @@
+use std::{cmp::Ordering, fmt::Display, ops::{Add, BitAnd, BitOr, BitXor, Mul, Neg, Not, Shl, Shr, Sub}, str::FromStr};
+
+const BASE: u64 = 1_000_000_000;
+const BASE_DIGITS: usize = 9;
+
+/// An integer of any size.
+///
+/// The magnitude is kept in base 10^9 limbs, least significant first,
+/// so that converting from and to decimal strings is cheap.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    negative: bool,
+    limbs: Vec<u32>
+}
+
+impl BigInt {
+    pub fn zero() -> Self {
+        Self::default()
+    }
+
+    pub fn one() -> Self {
+        Self::from(1)
+    }
+
+    fn from_limbs(negative: bool, mut limbs: Vec<u32>) -> Self {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        let negative = negative && !limbs.is_empty();
+        Self { negative, limbs }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn is_even(&self) -> bool {
+        self.limbs.first().is_none_or(|l| l % 2 == 0)
+    }
+
+    pub fn abs(&self) -> Self {
+        Self { negative: false, limbs: self.limbs.clone() }
+    }
+
+    /// Number of decimal digits of the magnitude, `0` has one digit.
+    pub fn digits(&self) -> usize {
+        match self.limbs.last() {
+            None => 1,
+            Some(top) => (self.limbs.len() - 1) * BASE_DIGITS + top.to_string().len(),
+        }
+    }
+
+    /// Number of decimal zeros at the end, `0` has none.
+    pub fn trailing_zeros(&self) -> usize {
+        let mut zeros = 0;
+        for limb in &self.limbs {
+            if *limb == 0 {
+                zeros += BASE_DIGITS;
+                continue;
+            }
+            let mut limb = *limb;
+            while limb % 10 == 0 {
+                limb /= 10;
+                zeros += 1;
+            }
+            break;
+        }
+        zeros
+    }
+
+    pub fn pow(&self, mut exp: u32) -> Self {
+        let mut base = self.clone();
+        let mut result = Self::one();
+        while exp > 0 {
+            if exp % 2 == 1 { result = &result * &base; }
+            exp /= 2;
+            if exp > 0 { base = &base * &base; }
+        }
+        result
+    }
+
+    pub fn gcd(&self, rhs: &Self) -> Self {
+        let (mut a, mut b) = (self.abs(), rhs.abs());
+        while !b.is_zero() {
+            let (_, rem) = a.div_rem(&b).unwrap_or_default();
+            a = b;
+            b = rem;
+        }
+        a
+    }
+
+    /// The integer part of the `k`-th root of `self`, which must not be negative.
+    pub fn nth_root(&self, k: u32) -> Self {
+        if self.is_zero() || k == 1 { return self.clone(); }
+        // Newton's method, starting above the root so that it decreases to it
+        let mut x = Self::one().mul_pow10(self.digits().div_ceil(k as usize));
+        loop {
+            let (quotient, _) = self.div_rem(&x.pow(k - 1)).unwrap_or_default();
+            let (next, _) = (&(&x * &Self::from(k as i128 - 1)) + &quotient).div_rem_small(k);
+            if next >= x { return x; }
+            x = next;
+        }
+    }
+
+    /// `self * 10^n`
+    pub fn mul_pow10(&self, n: usize) -> Self {
+        if self.is_zero() { return Self::zero(); }
+        let mut limbs = vec![0; n / BASE_DIGITS];
+        limbs.extend(mul_small(&self.limbs, 10u32.pow((n % BASE_DIGITS) as u32)));
+        Self::from_limbs(self.negative, limbs)
+    }
+
+    /// `self * 2^n`
+    pub fn mul_pow2(&self, n: u32) -> Self {
+        self * &Self::from(2).pow(n)
+    }
+
+    /// `self / 10^n`, rounded toward zero.
+    pub fn div_pow10(&self, n: usize) -> Self {
+        let skip = n / BASE_DIGITS;
+        if skip >= self.limbs.len() { return Self::zero(); }
+        let (limbs, _) = divrem_small(&self.limbs[skip..], 10u32.pow((n % BASE_DIGITS) as u32));
+        Self::from_limbs(self.negative, limbs)
+    }
+
+    /// Quotient rounded toward zero, and the remainder with the sign of `self`.
+    /// `None` when dividing by zero.
+    pub fn div_rem(&self, rhs: &Self) -> Option<(Self, Self)> {
+        if rhs.is_zero() { return None; }
+        let (q, r) = divrem_mag(&self.limbs, &rhs.limbs);
+        Some((Self::from_limbs(self.negative != rhs.negative, q), Self::from_limbs(self.negative, r)))
+    }
+
+    /// Divide the magnitude by a small number, returning the quotient (with the sign
+    /// of `self`) and the remainder of the magnitude.
+    pub fn div_rem_small(&self, rhs: u32) -> (Self, u32) {
+        let (q, r) = divrem_small(&self.limbs, rhs);
+        (Self::from_limbs(self.negative, q), r)
+    }
+
+    /// `log10(|self|)` as a rough `f64`, or `0` for zero.
+    pub fn approx_log10(&self) -> f64 {
+        let top = self.limbs.iter().rev().take(2).fold(0.0, |acc, l| acc * BASE as f64 + *l as f64);
+        let rest = self.limbs.len().saturating_sub(2) * BASE_DIGITS;
+        if top == 0.0 { 0.0 } else { top.log10() + rest as f64 }
+    }
+
+    pub fn to_u64(&self) -> Option<u64> {
+        if self.negative || self.limbs.len() > 3 { return None; }
+        let mut result: u64 = 0;
+        for limb in self.limbs.iter().rev() {
+            result = result.checked_mul(BASE)?.checked_add(*limb as u64)?;
+        }
+        Some(result)
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        let magnitude = self.abs().to_u64()?;
+        if self.negative {
+            0i64.checked_sub_unsigned(magnitude)
+        }
+        else {
+            i64::try_from(magnitude).ok()
+        }
+    }
+
+    /// Parse digits in base `radix`, e.g. `"1f"` in base 16, without sign or prefix.
+    pub fn from_radix(digits: &str, radix: u32) -> Option<Self> {
+        if digits.is_empty() { return None; }
+        let mut limbs: Vec<u32> = vec![];
+        for c in digits.chars() {
+            let digit = c.to_digit(radix)?;
+            limbs = add_mag(&mul_small(&limbs, radix), &[digit]);
+        }
+        Some(Self::from_limbs(false, limbs))
+    }
+}
+
+impl From<i128> for BigInt {
+    fn from(value: i128) -> Self {
+        let mut magnitude = value.unsigned_abs();
+        let mut limbs = vec![];
+        while magnitude > 0 {
+            limbs.push((magnitude % BASE as u128) as u32);
+            magnitude /= BASE as u128;
+        }
+        Self::from_limbs(value < 0, limbs)
+    }
+}
+
+impl FromStr for BigInt {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(());
+        }
+        let limbs = digits.as_bytes()
+            .rchunks(BASE_DIGITS)
+            .map(|chunk| chunk.iter().fold(0, |acc, b| acc * 10 + (b - b'0') as u32))
+            .collect();
+        Ok(Self::from_limbs(negative, limbs))
+    }
+}
+
+impl Display for BigInt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut limbs = self.limbs.iter().rev();
+        let mut display = match limbs.next() {
+            None => return write!(f, "0"),
+            Some(top) => top.to_string(),
+        };
+        for limb in limbs {
+            display += &format!("{:09}", limb);
+        }
+        if self.negative { display.insert(0, '-'); }
+        write!(f, "{}", display)
+    }
+}
+
+#[test]
+fn test_bigint_display() {
+    let examples = vec![
+        "0", "1", "-1", "999999999", "1000000000", "-123456789012345678901234567890",
+    ];
+    for example in examples {
+        assert_eq!(example.parse::<BigInt>().unwrap().to_string(), example);
+    }
+    assert_eq!("-0".parse::<BigInt>().unwrap(), BigInt::zero());
+    assert_eq!("000120".parse::<BigInt>().unwrap().to_string(), "120");
+    assert!("1.5".parse::<BigInt>().is_err());
+    assert!("".parse::<BigInt>().is_err());
+
+    assert_eq!(BigInt::from_radix("1f", 16).unwrap().to_string(), "31");
+    assert_eq!(BigInt::from_radix("1011", 2).unwrap().to_string(), "11");
+    assert_eq!(BigInt::from_radix("ffffffffffffffffffffffffffffffff", 16).unwrap().to_string(),
+        "340282366920938463463374607431768211455");
+    assert_eq!(BigInt::from_radix("000", 8).unwrap(), BigInt::zero());
+    assert!(BigInt::from_radix("12", 2).is_none());
+    assert!(BigInt::from_radix("", 10).is_none());
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_mag(&self.limbs, &other.limbs),
+            (true, true) => cmp_mag(&other.limbs, &self.limbs),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Neg for BigInt {
+    type Output = BigInt;
+    fn neg(self) -> Self::Output {
+        Self::from_limbs(!self.negative, self.limbs)
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+    fn neg(self) -> Self::Output {
+        -self.clone()
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+    fn add(self, rhs: Self) -> Self::Output {
+        if self.negative == rhs.negative {
+            return BigInt::from_limbs(self.negative, add_mag(&self.limbs, &rhs.limbs));
+        }
+        match cmp_mag(&self.limbs, &rhs.limbs) {
+            Ordering::Less => BigInt::from_limbs(rhs.negative, sub_mag(&rhs.limbs, &self.limbs)),
+            _ => BigInt::from_limbs(self.negative, sub_mag(&self.limbs, &rhs.limbs)),
+        }
+    }
+}
+
+impl Sub for &BigInt {
+    type Output = BigInt;
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + &-rhs
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+    fn mul(self, rhs: Self) -> Self::Output {
+        BigInt::from_limbs(self.negative != rhs.negative, mul_mag(&self.limbs, &rhs.limbs))
+    }
+}
+
+// ---- bitwise operations ----
+//
+// On the two's complement of the numbers, as if they had infinitely many sign bits to the left,
+// so that e.g. `-6 & 3` is `2` and `!5` is `-6`, like on machine integers.
+
+const WORD: u32 = 1 << 16;
+
+impl BigInt {
+    /// The bits in 16-bit words, least significant first, and whether all the bits above them are set.
+    fn to_words(&self) -> (Vec<u16>, bool) {
+        // `-n` is `!(n - 1)`
+        let mut magnitude = if self.negative { &self.abs() - &Self::one() } else { self.clone() };
+        let mut words = vec![];
+        while !magnitude.is_zero() {
+            let (quotient, word) = magnitude.div_rem_small(WORD);
+            words.push(if self.negative { !(word as u16) } else { word as u16 });
+            magnitude = quotient;
+        }
+        (words, self.negative)
+    }
+
+    fn from_words(words: &[u16], negative: bool) -> Self {
+        let mut limbs: Vec<u32> = vec![];
+        for word in words.iter().rev() {
+            let word = if negative { !word } else { *word };
+            limbs = add_mag(&mul_small(&limbs, WORD), &[word as u32]);
+        }
+        let magnitude = Self::from_limbs(false, limbs);
+        if negative { -(&magnitude + &Self::one()) } else { magnitude }
+    }
+
+    fn bitwise(&self, rhs: &Self, op: fn(u16, u16) -> u16) -> Self {
+        let ((a, a_negative), (b, b_negative)) = (self.to_words(), rhs.to_words());
+        let fill = |negative: bool| if negative { u16::MAX } else { 0 };
+        let words: Vec<u16> = (0..a.len().max(b.len()))
+            .map(|i| op(*a.get(i).unwrap_or(&fill(a_negative)), *b.get(i).unwrap_or(&fill(b_negative))))
+            .collect();
+        Self::from_words(&words, op(fill(a_negative), fill(b_negative)) != 0)
+    }
+}
+
+impl BitAnd for &BigInt {
+    type Output = BigInt;
+    fn bitand(self, rhs: Self) -> Self::Output {
+        self.bitwise(rhs, |a, b| a & b)
+    }
+}
+
+impl BitOr for &BigInt {
+    type Output = BigInt;
+    fn bitor(self, rhs: Self) -> Self::Output {
+        self.bitwise(rhs, |a, b| a | b)
+    }
+}
+
+impl BitXor for &BigInt {
+    type Output = BigInt;
+    fn bitxor(self, rhs: Self) -> Self::Output {
+        self.bitwise(rhs, |a, b| a ^ b)
+    }
+}
+
+impl Not for &BigInt {
+    type Output = BigInt;
+    /// `-self - 1`
+    fn not(self) -> Self::Output {
+        -&(self + &BigInt::one())
+    }
+}
+
+impl Shl<u32> for &BigInt {
+    type Output = BigInt;
+    fn shl(self, n: u32) -> Self::Output {
+        self.mul_pow2(n)
+    }
+}
+
+impl Shr<u32> for &BigInt {
+    type Output = BigInt;
+    /// `self / 2^n`, rounded down, so a negative number stays negative.
+    fn shr(self, n: u32) -> Self::Output {
+        // beyond all its bits, only the sign is left
+        if n as usize > 4 * self.digits() {
+            return if self.negative { -BigInt::one() } else { BigInt::zero() };
+        }
+        let (quotient, rem) = self.div_rem(&BigInt::from(2).pow(n)).unwrap_or_default();
+        if self.negative && !rem.is_zero() { &quotient - &BigInt::one() } else { quotient }
+    }
+}
+
+// ---- operations on magnitudes ----
+
+fn cmp_mag(a: &[u32], b: &[u32]) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_mag(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut result = Vec::with_capacity(long.len() + 1);
+    let mut carry = 0;
+    for (i, limb) in long.iter().enumerate() {
+        let sum = *limb as u64 + *short.get(i).unwrap_or(&0) as u64 + carry;
+        result.push((sum % BASE) as u32);
+        carry = sum / BASE;
+    }
+    if carry > 0 { result.push(carry as u32); }
+    result
+}
+
+/// `a - b`, where `a >= b`
+fn sub_mag(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result = Vec::with_capacity(a.len());
+    let mut borrow = 0;
+    for (i, limb) in a.iter().enumerate() {
+        let mut diff = *limb as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        borrow = 0;
+        if diff < 0 {
+            diff += BASE as i64;
+            borrow = 1;
+        }
+        result.push(diff as u32);
+    }
+    result
+}
+
+fn mul_mag(a: &[u32], b: &[u32]) -> Vec<u32> {
+    if a.is_empty() || b.is_empty() { return vec![]; }
+    let mut result = vec![0u32; a.len() + b.len()];
+    for (i, x) in a.iter().enumerate() {
+        let mut carry = 0;
+        for (j, y) in b.iter().enumerate() {
+            let product = result[i + j] as u64 + *x as u64 * *y as u64 + carry;
+            result[i + j] = (product % BASE) as u32;
+            carry = product / BASE;
+        }
+        result[i + b.len()] = carry as u32;
+    }
+    result
+}
+
+fn mul_small(a: &[u32], b: u32) -> Vec<u32> {
+    let mut result = Vec::with_capacity(a.len() + 1);
+    let mut carry = 0;
+    for limb in a {
+        let product = *limb as u64 * b as u64 + carry;
+        result.push((product % BASE) as u32);
+        carry = product / BASE;
+    }
+    if carry > 0 { result.push(carry as u32); }
+    result
+}
+
+fn divrem_small(a: &[u32], b: u32) -> (Vec<u32>, u32) {
+    let mut quotient = vec![0u32; a.len()];
+    let mut rem = 0u64;
+    for i in (0..a.len()).rev() {
+        let current = rem * BASE + a[i] as u64;
+        quotient[i] = (current / b as u64) as u32;
+        rem = current % b as u64;
+    }
+    (quotient, rem as u32)
+}
+
+/// Long division (Knuth, TAOCP vol. 2, algorithm D), `b` must not be empty.
+fn divrem_mag(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if cmp_mag(a, b) == Ordering::Less { return (vec![], a.to_vec()); }
+    if b.len() == 1 {
+        let (q, r) = divrem_small(a, b[0]);
+        return (q, vec![r]);
+    }
+    // normalize so that the top limb of the divisor is at least BASE / 2
+    let d = (BASE / (*b.last().unwrap() as u64 + 1)) as u32;
+    let v = mul_small(b, d);
+    let mut u = mul_small(a, d);
+    u.resize(a.len() + 1, 0);
+    let n = v.len();
+    let m = u.len() - n - 1;
+    let mut quotient = vec![0u32; m + 1];
+    for j in (0..=m).rev() {
+        let numerator = u[j + n] as u64 * BASE + u[j + n - 1] as u64;
+        let mut qhat = numerator / v[n - 1] as u64;
+        let mut rhat = numerator % v[n - 1] as u64;
+        while qhat >= BASE || qhat * v[n - 2] as u64 > rhat * BASE + u[j + n - 2] as u64 {
+            qhat -= 1;
+            rhat += v[n - 1] as u64;
+            if rhat >= BASE { break; }
+        }
+        // u[j..=j+n] -= qhat * v
+        let mut borrow = 0i64;
+        let mut carry = 0u64;
+        for i in 0..n {
+            let product = qhat * v[i] as u64 + carry;
+            carry = product / BASE;
+            let mut diff = u[i + j] as i64 - (product % BASE) as i64 - borrow;
+            borrow = 0;
+            if diff < 0 {
+                diff += BASE as i64;
+                borrow = 1;
+            }
+            u[i + j] = diff as u32;
+        }
+        let top = u[j + n] as i64 - carry as i64 - borrow;
+        if top < 0 {
+            // qhat was one too large, add the divisor back
+            qhat -= 1;
+            let mut carry = 0u64;
+            for i in 0..n {
+                let sum = u[i + j] as u64 + v[i] as u64 + carry;
+                u[i + j] = (sum % BASE) as u32;
+                carry = sum / BASE;
+            }
+            u[j + n] = 0;
+        }
+        else {
+            u[j + n] = top as u32;
+        }
+        quotient[j] = qhat as u32;
+    }
+    let (remainder, _) = divrem_small(&u[..n], d);
+    (quotient, remainder)
+}
+
+#[cfg(test)]
+fn big(s: &str) -> BigInt {
+    s.parse().unwrap()
+}
+
+#[test]
+fn test_bigint_arithmetic() {
+    assert_eq!(&big("999999999999") + &big("1"), big("1000000000000"));
+    assert_eq!(&big("1000000000000") - &big("1"), big("999999999999"));
+    assert_eq!(&big("-5") + &big("3"), big("-2"));
+    assert_eq!(&big("5") - &big("5"), BigInt::zero());
+    assert_eq!(&big("-123456789123") * &big("987654321987"), big("-121932631355968601347401"));
+    assert_eq!(big("99999999999").pow(5), big("9999999999500000000009999999999900000000000499999999999"));
+    assert_eq!(big("-2").pow(3), big("-8"));
+    assert_eq!(big("7").pow(0), BigInt::one());
+    assert_eq!(big("12").mul_pow10(20), big("1200000000000000000000"));
+    assert_eq!(big("-1234567890123").div_pow10(4), big("-123456789"));
+    assert_eq!(big("1200000000000000000000").trailing_zeros(), 20);
+    assert_eq!(big("1200000000000000000000").digits(), 22);
+    assert!(big("-3") < big("2") && big("-3") < big("-2") && big("10000000000") > big("999999999"));
+}
+
+#[test]
+fn test_bigint_bitwise() {
+    assert_eq!(&big("12") & &big("10"), big("8"));
+    assert_eq!(&big("12") | &big("10"), big("14"));
+    assert_eq!(&big("12") ^ &big("10"), big("6"));
+    assert_eq!(&big("-6") & &big("3"), big("2"));
+    assert_eq!(&big("-6") | &big("3"), big("-5"));
+    assert_eq!(&big("-6") ^ &big("-3"), big("7"));
+    assert_eq!(!&big("5"), big("-6"));
+    assert_eq!(!&big("-1"), BigInt::zero());
+    assert_eq!(&big("1") << 100, big("1267650600228229401496703205376"));
+    assert_eq!(&big("1267650600228229401496703205377") >> 99, big("2"));
+    assert_eq!(&big("-7") >> 1, big("-4"));
+    assert_eq!(&big("-7") >> 1000, big("-1"));
+    assert_eq!(&big("7") >> 1000, BigInt::zero());
+    let a = big("123456789012345678901234567890");
+    assert_eq!(&(&a & &big("-1")) | &BigInt::zero(), a);
+    assert_eq!(&(&a ^ &big("987654321")) ^ &big("987654321"), a);
+    // cross-check against native integers
+    for (a, b) in [(-1000i128, 77), (123456789, -987654321), (-5, -9), (0, -1), (i64::MAX as i128, i64::MIN as i128)] {
+        assert_eq!(&BigInt::from(a) & &BigInt::from(b), BigInt::from(a & b), "{} & {}", a, b);
+        assert_eq!(&BigInt::from(a) | &BigInt::from(b), BigInt::from(a | b), "{} | {}", a, b);
+        assert_eq!(&BigInt::from(a) ^ &BigInt::from(b), BigInt::from(a ^ b), "{} ^ {}", a, b);
+    }
+}
+
+#[test]
+fn test_bigint_gcd_root() {
+    assert_eq!(big("-12").gcd(&big("18")), big("6"));
+    assert_eq!(big("0").gcd(&big("7")), big("7"));
+    assert_eq!(big("1000000000000000000000").nth_root(3), big("10000000"));
+    assert_eq!(big("999999999999999999999").nth_root(3), big("9999999"));
+    assert_eq!(big("2").mul_pow10(40).nth_root(2), big("141421356237309504880"));
+    assert_eq!(big("8").nth_root(1), big("8"));
+}
+
+#[test]
+fn test_bigint_div_rem() {
+    let (q, r) = big("121932631355968601347401").div_rem(&big("987654321987")).unwrap();
+    assert_eq!((q, r), (big("123456789123"), BigInt::zero()));
+    let (q, r) = big("-100000000000000000000000000007").div_rem(&big("300000000000000001")).unwrap();
+    assert_eq!((q.to_string(), r.to_string()), ("-333333333333".to_string(), "-99999666666666674".to_string()));
+    assert!(big("1").div_rem(&BigInt::zero()).is_none());
+
+    // cross-check against native integers
+    let mut seed: u64 = 0x2545F4914F6CDD1D;
+    let mut next = || { seed ^= seed << 13; seed ^= seed >> 7; seed ^= seed << 17; seed };
+    for _ in 0..10000 {
+        let a = ((next() as u128) << 64 | next() as u128) >> (1 + next() % 127);
+        let b = (next() as u128) >> (next() % 64) | 1;
+        let (q, r) = BigInt::from(a as i128).div_rem(&BigInt::from(b as i128)).unwrap();
+        assert_eq!(q, BigInt::from((a / b) as i128));
+        assert_eq!(r, BigInt::from((a % b) as i128));
+    }
+}
@@ -0,0 +1,197 @@
+use std::{fmt::Display, ops::Neg};
+
+use crate::{
+    error::CalcError,
+    number::{bigint::BigInt, context::{Context, RoundingMode}, decimal::Decimal, math}
+};
+
+/// A complex number `re + im i`, with decimal parts.
+///
+/// Addition, subtraction and multiplication are exact like on decimals; everything else is
+/// rounded to `ctx.precision` digits after the point, each part on its own.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Complex {
+    re: Decimal,
+    im: Decimal
+}
+
+impl Complex {
+    pub fn new(re: Decimal, im: Decimal) -> Self {
+        Self { re, im }
+    }
+
+    pub fn re(&self) -> &Decimal {
+        &self.re
+    }
+
+    pub fn im(&self) -> &Decimal {
+        &self.im
+    }
+
+    pub(crate) fn is_real(&self) -> bool {
+        self.im.is_zero()
+    }
+
+    pub fn conj(&self) -> Self {
+        Self { re: self.re.clone(), im: -&self.im }
+    }
+
+    /// The absolute value `|z|`, its distance from zero.
+    pub fn abs(&self, ctx: &Context) -> Result<Decimal, CalcError> {
+        math::hypot(&self.re, &self.im, ctx)
+    }
+
+    /// The argument, its angle from the positive real axis in `(-pi, pi]`.
+    pub fn arg(&self, ctx: &Context) -> Result<Decimal, CalcError> {
+        math::atan2(&self.im, &self.re, ctx)
+    }
+
+    /// Round both parts to `scale` digits after the point.
+    pub fn round(&self, scale: usize, mode: RoundingMode) -> Self {
+        Self { re: self.re.round(scale, mode), im: self.im.round(scale, mode) }
+    }
+
+    fn from_pair((re, im): (Decimal, Decimal)) -> Self {
+        Self { re, im }
+    }
+}
+
+impl From<Decimal> for Complex {
+    fn from(value: Decimal) -> Self {
+        Self { re: value, im: Decimal::zero() }
+    }
+}
+
+impl Complex {  // arithmetic, reporting numbers above the limits instead of panicking
+    pub fn checked_add(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError> {
+        Ok(Self { re: self.re.checked_add(&rhs.re, ctx)?, im: self.im.checked_add(&rhs.im, ctx)? })
+    }
+
+    pub fn checked_sub(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError> {
+        Ok(Self { re: self.re.checked_sub(&rhs.re, ctx)?, im: self.im.checked_sub(&rhs.im, ctx)? })
+    }
+
+    /// `(a + bi)(c + di) = (ac - bd) + (ad + bc)i`
+    pub fn checked_mul(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError> {
+        let (a, b, c, d) = (&self.re, &self.im, &rhs.re, &rhs.im);
+        let re = a.checked_mul(c, ctx)?.checked_sub(&b.checked_mul(d, ctx)?, ctx)?;
+        let im = a.checked_mul(d, ctx)?.checked_add(&b.checked_mul(c, ctx)?, ctx)?;
+        Ok(Self { re, im })
+    }
+
+    /// `(a + bi) / (c + di) = ((ac + bd) + (bc - ad)i) / (c^2 + d^2)`
+    pub fn checked_div(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError> {
+        let (a, b, c, d) = (&self.re, &self.im, &rhs.re, &rhs.im);
+        let square = c.checked_mul(c, ctx)?.checked_add(&d.checked_mul(d, ctx)?, ctx)?;
+        if square.is_zero() { return Err(CalcError::division_by_zero()); }
+        let re = a.checked_mul(c, ctx)?.checked_add(&b.checked_mul(d, ctx)?, ctx)?;
+        let im = b.checked_mul(c, ctx)?.checked_sub(&a.checked_mul(d, ctx)?, ctx)?;
+        Ok(Self { re: re.checked_div(&square, ctx)?, im: im.checked_div(&square, ctx)? })
+    }
+
+    /// The principal value of `self ^ rhs`, which is exact for an integer `rhs`, and for half of one
+    /// where the square root is, e.g. `(-4) ^ 1.5` is `-8i`.
+    pub fn pow(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError> {
+        if rhs.is_real() {
+            if let Some(exp) = rhs.re.to_integer().and_then(|n| n.to_i64()) {
+                return self.powi(exp, ctx);
+            }
+            let twice = rhs.re.checked_add(&rhs.re, ctx)?;
+            if let Some(exp) = twice.to_integer().and_then(|n| n.to_i64()) {
+                return self.sqrt(ctx)?.powi(exp, ctx);
+            }
+        }
+        math::pow_complex((&self.re, &self.im), (&rhs.re, &rhs.im), ctx).map(Self::from_pair)
+    }
+
+    /// `self ^ exp` by repeated squaring; too many digits stop it as soon as they appear.
+    fn powi(&self, exp: i64, ctx: &Context) -> Result<Self, CalcError> {
+        if exp < 0 {
+            return Self::from(Decimal::one()).checked_div(&self.powi(exp.checked_neg().unwrap_or(i64::MAX), ctx)?, ctx);
+        }
+        let (mut result, mut square, mut exp) = (Self::from(Decimal::one()), self.clone(), exp as u64);
+        while exp > 0 {
+            if exp % 2 == 1 { result = result.checked_mul(&square, ctx)?; }
+            exp /= 2;
+            if exp > 0 { square = square.checked_mul(&square, ctx)?; }
+        }
+        Ok(result)
+    }
+
+    /// The principal square root, the one with a real part that is not negative.
+    pub fn sqrt(&self, ctx: &Context) -> Result<Self, CalcError> {
+        math::sqrt_complex(&self.re, &self.im, ctx).map(Self::from_pair)
+    }
+
+    pub fn exp(&self, ctx: &Context) -> Result<Self, CalcError> {
+        math::exp_complex(&self.re, &self.im, ctx).map(Self::from_pair)
+    }
+
+    /// The principal natural logarithm, with an imaginary part in `(-pi, pi]`.
+    pub fn ln(&self, ctx: &Context) -> Result<Self, CalcError> {
+        math::ln_complex(&self.re, &self.im, ctx).map(Self::from_pair)
+    }
+
+    /// The built-in function `name` on complex `args`, or `None` if it has no complex version.
+    pub(crate) fn function(name: &str, args: &[Self], ctx: &Context) -> Option<Result<Self, CalcError>> {
+        let logarithm = |base: &Self, x: &Self| x.ln(ctx)?.checked_div(&base.ln(ctx)?, ctx);
+        Some(match (name, args) {
+            ("exp", [x]) => x.exp(ctx),
+            ("ln", [x]) => x.ln(ctx),
+            ("log10", [x]) => logarithm(&Self::from(Decimal::from_parts(BigInt::from(10), 0)), x),
+            ("log", [base, x]) => logarithm(base, x),
+            _ => return None,
+        })
+    }
+}
+
+impl Neg for Complex {
+    type Output = Self;
+    fn neg(self) -> Self::Output {
+        Self { re: -self.re, im: -self.im }
+    }
+}
+
+impl Display for Complex {
+    /// `3.0 - 1.5i`, or `2.0i` without a real part.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.re.is_zero() { return write!(f, "{}i", self.im); }
+        let sign = if self.im.is_negative() { '-' } else { '+' };
+        write!(f, "{} {} {}i", self.re, sign, self.im.abs())
+    }
+}
+
+#[cfg(test)]
+fn complex(re: &str, im: &str) -> Complex {
+    let dec = |s: &str| match s.strip_prefix('-') {
+        Some(s) => -s.parse::<Decimal>().unwrap(),
+        None => s.parse().unwrap(),
+    };
+    Complex::new(dec(re), dec(im))
+}
+
+#[test]
+fn test_complex_arithmetic() {
+    let ctx = Context::default();
+    let (z, w) = (complex("1", "2"), complex("3", "-1"));
+    assert_eq!(z.checked_add(&w, &ctx).unwrap().to_string(), "4.0 + 1.0i");
+    assert_eq!(z.checked_mul(&w, &ctx).unwrap().to_string(), "5.0 + 5.0i");
+    assert_eq!(z.checked_div(&w, &ctx).unwrap().to_string(), "0.1 + 0.7i");
+    assert!(matches!(z.checked_div(&complex("0", "0"), &ctx), Err(CalcError::DivisionByZero { .. })));
+    assert_eq!(complex("3", "4").abs(&ctx).unwrap().to_string(), "5.0");
+    assert_eq!(complex("-1", "0").arg(&ctx).unwrap().to_string(), "3.14159265358979323846");
+    assert_eq!(complex("0", "-2").arg(&ctx).unwrap().to_string(), "-1.57079632679489661923");
+    assert_eq!(z.conj().to_string(), "1.0 - 2.0i");
+    assert_eq!(complex("0", "1").pow(&complex("2", "0"), &ctx).unwrap().to_string(), "-1.0 + 0.0i");
+    assert_eq!(z.pow(&complex("-1", "0"), &ctx).unwrap().to_string(), "0.2 - 0.4i");
+    assert_eq!(complex("-4", "0").pow(&complex("1.5", "0"), &ctx).unwrap().to_string(), "-8.0i");
+    assert_eq!(complex("3", "4").sqrt(&ctx).unwrap().to_string(), "2.0 + 1.0i");
+    assert_eq!(complex("-9", "0").sqrt(&ctx).unwrap().to_string(), "3.0i");
+    // e^(i pi) = -1
+    let pi = Complex::new(Decimal::zero(), math::pi(&ctx).unwrap());
+    assert_eq!(pi.exp(&ctx).unwrap().to_string(), "-1.0 + 0.0i");
+    assert_eq!(complex("-1", "0").ln(&ctx).unwrap().to_string(), "3.14159265358979323846i");
+    assert_eq!(complex("0", "1").pow(&complex("0", "1"), &ctx).unwrap().to_string(), "0.20787957635076190855 + 0.0i");
+    assert!(matches!(complex("0", "0").pow(&complex("-1", "1"), &ctx), Err(CalcError::DivisionByZero { .. })));
+    assert!(matches!(complex("0", "0").ln(&ctx), Err(CalcError::Domain { .. })));
+}
@@ -0,0 +1,198 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::{error::CalcError, number::{decimal::Decimal, format::FormatOptions}};
+
+/// How to round a result that has more digits than the precision allows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RoundingMode {
+    /// To the nearest, ties to the even neighbour (banker's rounding).
+    #[default]
+    HalfEven,
+    /// To the nearest, ties away from zero.
+    HalfUp,
+    TowardZero,
+    Floor,
+    Ceiling,
+}
+
+impl FromStr for RoundingMode {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "half-even" => Ok(RoundingMode::HalfEven),
+            "half-up" => Ok(RoundingMode::HalfUp),
+            "toward-zero" => Ok(RoundingMode::TowardZero),
+            "floor" => Ok(RoundingMode::Floor),
+            "ceiling" => Ok(RoundingMode::Ceiling),
+            other => Err(format!(
+                "Unknown rounding mode '{}', expected one of half-even, half-up, toward-zero, floor, ceiling", other
+            )),
+        }
+    }
+}
+
+impl Display for RoundingMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            RoundingMode::HalfEven => "half-even",
+            RoundingMode::HalfUp => "half-up",
+            RoundingMode::TowardZero => "toward-zero",
+            RoundingMode::Floor => "floor",
+            RoundingMode::Ceiling => "ceiling",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[test]
+fn test_rounding_mode_from_str() {
+    for mode in ["half-even", "half-up", "toward-zero", "floor", "ceiling"] {
+        assert_eq!(mode.parse::<RoundingMode>().unwrap().to_string(), mode);
+    }
+    assert!("up".parse::<RoundingMode>().is_err());
+}
+
+/// Which numbers a calculation is done with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Arithmetic {
+    /// Decimals, dividing to `precision` digits.
+    #[default]
+    Decimal,
+    /// Exact fractions, e.g. `1/3 + 1/6` is `1/2`, turning into decimals only where
+    /// a result is irrational, e.g. `sqrt(2)`.
+    Rational,
+}
+
+impl FromStr for Arithmetic {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "decimal" => Ok(Arithmetic::Decimal),
+            "rational" => Ok(Arithmetic::Rational),
+            other => Err(format!("Unknown arithmetic '{}', expected one of decimal, rational", other)),
+        }
+    }
+}
+
+impl Display for Arithmetic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Arithmetic::Decimal => "decimal",
+            Arithmetic::Rational => "rational",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// How tightly a product written without `*` binds, e.g. `2x` or `2(3 + 4)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ImplicitMultiplication {
+    /// Tighter than `*` and `/`, so `1/2x` is `1/(2x)`.
+    #[default]
+    Tight,
+    /// The same as `*`, so `1/2x` is `(1/2)x`.
+    Strict,
+}
+
+impl FromStr for ImplicitMultiplication {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tight" => Ok(ImplicitMultiplication::Tight),
+            "strict" => Ok(ImplicitMultiplication::Strict),
+            other => Err(format!("Unknown implicit multiplication '{}', expected one of tight, strict", other)),
+        }
+    }
+}
+
+impl Display for ImplicitMultiplication {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ImplicitMultiplication::Tight => "tight",
+            ImplicitMultiplication::Strict => "strict",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The syntax the input is written in, for the tokenizer and the parser alike.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Dialect {
+    /// `^` is the power, `xor` the exclusive or, and `%` a percentage or the remainder.
+    #[default]
+    Standard,
+    /// As in C and Python: `**` is the power, `^` the exclusive or, and `%` only the remainder.
+    C,
+    /// As in a spreadsheet: a formula may start with `=`, `%` is only a percentage,
+    /// and arguments are separated by `;` as well as `,`, so only line breaks separate statements.
+    Spreadsheet,
+}
+
+impl FromStr for Dialect {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "standard" => Ok(Dialect::Standard),
+            "c" => Ok(Dialect::C),
+            "spreadsheet" => Ok(Dialect::Spreadsheet),
+            other => Err(format!("Unknown dialect '{}', expected one of standard, c, spreadsheet", other)),
+        }
+    }
+}
+
+impl Display for Dialect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Dialect::Standard => "standard",
+            Dialect::C => "c",
+            Dialect::Spreadsheet => "spreadsheet",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Settings of a calculation: which numbers it uses, their limits, and how the input is read.
+#[derive(Debug, Clone)]
+pub struct Context {
+    pub arithmetic: Arithmetic,
+    /// Digits kept after the point when a result cannot be exact, e.g. `1 / 3`.
+    pub precision: usize,
+    pub rounding: RoundingMode,
+    /// The most digits a number may have, before or after the point,
+    /// so that something like `9^9^9` fails instead of eating all the memory.
+    pub max_digits: usize,
+    /// The most calls of user functions inside each other, so that recursion cannot go on forever,
+    /// and the deepest an expression may be nested, e.g. in parentheses.
+    pub max_depth: usize,
+    /// Read full-width digits and symbols, e.g. `１２＋３`, as ASCII ones.
+    pub full_width: bool,
+    /// How tightly `2x` binds.
+    pub implicit: ImplicitMultiplication,
+    /// The syntax of the input.
+    pub dialect: Dialect,
+    /// How results are written.
+    pub format: FormatOptions
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        Self {
+            arithmetic: Arithmetic::default(), precision: 20, rounding: RoundingMode::default(),
+            max_digits: 10_000, max_depth: 256, full_width: true, implicit: ImplicitMultiplication::default(),
+            dialect: Dialect::default(), format: FormatOptions::default()
+        }
+    }
+}
+
+impl Context {
+    /// Fail when `value` is larger than the limits allow.
+    pub(crate) fn check(&self, value: Decimal) -> Result<Decimal, CalcError> {
+        if value.digits() > self.max_digits || value.scale() > self.max_digits {
+            return Err(CalcError::Overflow {
+                message: format!("Number has more than {} digits", self.max_digits),
+                span: None
+            });
+        }
+        Ok(value)
+    }
+}
@@ -1,13 +1,14 @@
-use std::{ops::{Add, Sub, Neg, Mul, Div}, fmt::Display, cmp::max};
+use std::{ops::Neg, fmt::Display, cmp::{max, Ordering}, str::FromStr};
+
+use crate::{
+    error::CalcError,
+    number::{bigint::BigInt, context::{Context, RoundingMode}, math}
+};
 
 pub fn simplify(origin: &Decimal) -> Decimal {
-    let mut int = origin.int;
-    let mut point = origin.point;
-    while int % 10 == 0 && point > 0 {
-        int /= 10;
-        point -= 1;
-    }
-    Decimal { int, point }
+    if origin.is_zero() { return Decimal { inexact: origin.inexact, ..Decimal::zero() }; }
+    let zeros = origin.int.trailing_zeros().min(origin.point);
+    Decimal { int: origin.int.div_pow10(zeros), point: origin.point - zeros, inexact: origin.inexact }
 }
 
 #[test]
@@ -16,21 +17,35 @@ fn test_decimal_simplify() {
     println!("{:?}\n{0}", &origin);
     let simplified = simplify(&origin);
     println!("{:?}\n{0}", &simplified);
+    assert_eq!(simplified.point, 2);
+    assert_eq!(simplify(&Decimal::_new(0, 7)).point, 0);
 }
 
-#[derive(Debug)]
+/// A decimal number, `int * 10^-point`.
+#[derive(Debug, Clone)]
 pub struct Decimal {
-    int: i128,
-    point: u8
+    int: BigInt,
+    point: usize,
+    /// Whether digits were rounded off on the way to this value, e.g. in `1 / 3` or `sqrt(2)`,
+    /// so that the digits past the precision of what it came from are noise.
+    inexact: bool
 }
 
 impl Decimal {
     pub fn zero() -> Self {
-        Self { int: 0, point: 0 }
+        Self { int: BigInt::zero(), point: 0, inexact: false }
+    }
+
+    pub fn one() -> Self {
+        Self { int: BigInt::one(), point: 0, inexact: false }
+    }
+
+    pub(crate) fn from_parts(int: BigInt, point: usize) -> Self {
+        Self { int, point, inexact: false }
     }
 
-    fn is_zero(&self) -> bool {
-        self.int == 0
+    pub(crate) fn is_zero(&self) -> bool {
+        self.int.is_zero()
     }
 
     fn is_integer(&self) -> bool {
@@ -38,88 +53,210 @@ impl Decimal {
     }
 
     fn is_nonnegative(&self) -> bool {
-        self.int >= 0
+        !self.int.is_negative()
+    }
+
+    pub(crate) fn is_negative(&self) -> bool {
+        self.int.is_negative()
+    }
+
+    pub(crate) fn abs(&self) -> Self {
+        Self { int: self.int.abs(), ..self.clone() }
+    }
+
+    /// The value as an integer, if it is one.
+    pub(crate) fn to_integer(&self) -> Option<BigInt> {
+        let simplified = simplify(self);
+        if simplified.is_integer() { Some(simplified.int) } else { None }
+    }
+
+    /// The value as the reduced fraction `(numerator, denominator)`.
+    pub(crate) fn to_fraction(&self) -> (BigInt, BigInt) {
+        let denominator = BigInt::one().mul_pow10(self.point);
+        let gcd = self.int.gcd(&denominator);
+        if gcd.is_zero() { return (BigInt::zero(), BigInt::one()); }
+        let (numerator, _) = self.int.div_rem(&gcd).unwrap_or_default();
+        let (denominator, _) = denominator.div_rem(&gcd).unwrap_or_default();
+        (numerator, denominator)
+    }
+
+    /// The power of ten of the first digit, e.g. `2` for `123.4` and `-2` for `0.01`.
+    pub(crate) fn exponent(&self) -> isize {
+        self.int.digits() as isize - self.point as isize - 1
+    }
+
+    /// `self * 10^n`, which is always exact.
+    pub(crate) fn mul_pow10(&self, n: isize) -> Self {
+        if n < 0 { return Self { point: self.point + n.unsigned_abs(), ..self.clone() }; }
+        let n = n as usize;
+        if n <= self.point { Self { point: self.point - n, ..self.clone() } }
+        else { Self { int: self.int.mul_pow10(n - self.point), point: 0, inexact: self.inexact } }
+    }
+
+    /// `log10(|self|)` as a rough `f64`, for estimating sizes.
+    pub(crate) fn approx_log10(&self) -> f64 {
+        self.int.approx_log10() - self.point as f64
+    }
+
+    pub(crate) fn to_f64(&self) -> f64 {
+        self.to_string().parse().unwrap_or(f64::NAN)
     }
 
-    fn _new(int: i128, point: u8) -> Self {
-        Self { int, point }
+    /// Number of digits of the mantissa.
+    pub fn digits(&self) -> usize {
+        self.int.digits()
+    }
+
+    /// Number of digits after the point.
+    pub fn scale(&self) -> usize {
+        self.point
+    }
+
+    /// Whether digits were rounded off on the way to this value.
+    pub fn is_inexact(&self) -> bool {
+        self.inexact
+    }
+
+    #[cfg(test)]
+    fn _new(int: i128, point: usize) -> Self {
+        Self { int: BigInt::from(int), point, inexact: false }
     }
 }
 
 impl Decimal {  // for `pow` function
-    pub fn pow(&self, rhs: &Self) -> Self {
-        match rhs {
-            r if r.is_nonnegative() && r.is_integer() => {
-                let int = self.int.pow(rhs.int as u32);
-                let point = self.point * r.int as u8;
-                Self { int, point }
+    /// Integer powers are exact (but negative ones are divided at `ctx.precision`), unless the exact power
+    /// has more digits than `ctx.max_digits` allows, e.g. `0.5 ^ 100000`, which is then rounded to
+    /// `ctx.precision` instead. Other exponents are treated as the fraction they are equal to.
+    pub fn pow(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError> {
+        let rhs = simplify(rhs);
+        if !rhs.is_integer() {
+            let (numerator, denominator) = rhs.to_fraction();
+            return math::pow_fraction(self, &numerator, &denominator, ctx);
+        }
+        let too_large = || CalcError::Overflow {
+            message: format!("Result has more than {} digits", ctx.max_digits),
+            span: None
+        };
+        let base = simplify(self);
+        let exp = rhs.int.abs().to_u64().ok_or_else(too_large)?;
+        // estimate first, so that we never build a number far above the limit
+        let magnitude = exp as f64 * base.approx_log10();
+        if !rhs.is_nonnegative() {
+            if self.is_zero() { return Err(CalcError::division_by_zero()); }
+            if magnitude > ctx.precision as f64 + 2.0 { return math::pow_rounded(&base, exp, true, ctx); }
+            return Decimal::one().checked_div(&self.pow(&-rhs, ctx)?, ctx);
+        }
+        if magnitude > ctx.max_digits as f64 + 1.0 { return Err(too_large()); }
+        let digits = exp as f64 * base.int.approx_log10();
+        let point = base.point as u64 * exp;
+        match u32::try_from(exp) {
+            Ok(exp) if digits <= ctx.max_digits as f64 + 1.0 && point <= ctx.max_digits as u64 => {
+                ctx.check(Self { int: base.int.pow(exp), point: point as usize, inexact: base.inexact })
             },
-            // todo: other kind of exponents
-            _ => panic!("Only non-negative integer exponents are supported")
+            _ => math::pow_rounded(&base, exp, false, ctx),
         }
     }
 }
 
 #[test]
 fn test_decimal_pow() {
+    let ctx = Context::default();
     let examples = vec![
-        Decimal::from("2".to_string()).pow(&Decimal::from("8".to_string())),
-        Decimal::from("0.1".to_string()).pow(&Decimal::from("4".to_string())),
-        Decimal::from("0.002".to_string()).pow(&Decimal::from("4".to_string())),
-        Decimal::from("2.1".to_string()).pow(&Decimal::from("4".to_string())),
-        Decimal::from("0.00".to_string()).pow(&Decimal::from("4".to_string())),
-        Decimal::from("0.233".to_string()).pow(&Decimal::from("1".to_string())),
-        Decimal::from("0.002".to_string()).pow(&Decimal::from("0".to_string())),
+        ("2", "8", "256.0"),
+        ("0.1", "4", "0.0001"),
+        ("0.002", "4", "0.000000000016"),
+        ("2.1", "4", "19.4481"),
+        ("0.00", "4", "0.0"),
+        ("0.233", "1", "0.233"),
+        ("0.002", "0", "1.0"),
+        ("99999999999", "5", "9999999999500000000009999999999900000000000499999999999.0"),
     ];
-    for example in examples {
-        println!("Result: {}", example);
+    for (base, exp, expected) in examples {
+        let result = dec(base).pow(&dec(exp), &ctx).unwrap();
+        assert_eq!(simplify(&result).to_string(), expected);
     }
+    assert!(matches!(dec("9").pow(&dec("1000000000"), &ctx), Err(CalcError::Overflow { .. })));
+    // too many digits to be exact, but small enough to round
+    assert_eq!(dec("0.5").pow(&dec("100000"), &ctx).unwrap().to_string(), "0.0");
+    assert_eq!((-dec("0.5")).pow(&dec("99999"), &ctx).unwrap().to_string(), "0.0");
+    let result = dec("1.0000000001").pow(&dec("1000000000000"), &ctx).unwrap();
+    assert_eq!(result.to_string(), "26881171283755497738294515689407855463755568.30629168459608042188");
+    assert_eq!(dec("0.999").pow(&dec("10000"), &ctx).unwrap().to_string(), "0.00004517334597704865");
+    assert_eq!(dec("2").pow(&Decimal::_new(-100000, 0), &ctx).unwrap().to_string(), "0.0");
+    assert_eq!(dec("1").pow(&dec("1000000000"), &ctx).unwrap(), dec("1"));
+
+    let examples = vec![
+        ("2", Decimal::_new(-1, 0), "0.5"),
+        ("4", Decimal::_new(-3, 0), "0.015625"),
+        ("3", Decimal::_new(-1, 0), "0.33333333333333333333"),
+        ("4", dec("0.5"), "2.0"),
+        ("2", dec("0.5"), "1.4142135623730950488"),
+        ("32", dec("0.2"), "2.0"),
+        ("-32", dec("0.2"), "-2.0"),
+        ("16", Decimal::_new(-25, 2), "0.5"),
+        ("2", dec("0.123"), "1.08899701533610638986"),
+        ("10", dec("2.5"), "316.22776601683793319989"),
+    ];
+    for (base, exp, expected) in examples {
+        let base = if let Some(b) = base.strip_prefix('-') { -dec(b) } else { dec(base) };
+        assert_eq!(base.pow(&exp, &ctx).unwrap().to_string(), expected, "{} ^ {}", base, exp);
+    }
+    assert!(matches!(dec("0").pow(&Decimal::_new(-1, 0), &ctx), Err(CalcError::DivisionByZero { .. })));
+    assert!(matches!((-dec("4")).pow(&dec("0.5"), &ctx), Err(CalcError::Domain { .. })));
+}
+
+#[cfg(test)]
+fn dec(s: &str) -> Decimal {
+    s.parse().unwrap()
 }
 
-impl From<String> for Decimal {
-    fn from(num_string: String) -> Self {
+impl FromStr for Decimal {
+    type Err = CalcError;
+    fn from_str(num_string: &str) -> Result<Self, Self::Err> {
+        let invalid = || CalcError::Lex {
+            message: format!("Invalid number '{}'", num_string), span: None
+        };
         let num_vec: Vec<&str> = num_string.split('.').collect();
-        let int: i128; let point: u8;
-        if num_vec.len() == 1 {
-            int = num_vec[0].parse().unwrap();
-            point = 0;
-        }
-        else {
-            int = (num_vec[0].to_string() + num_vec[1]).parse().unwrap();
-            point = num_vec[1].len() as u8;
+        let (int_part, frac_part) = match num_vec[..] {
+            [int_part] => (int_part, ""),
+            [int_part, frac_part] => (int_part, frac_part),
+            _ => return Err(invalid()),
         };
-        if int == 0 { return Decimal::zero(); }
-        simplify(&Decimal { int, point })
+        let digits = int_part.to_string() + frac_part;
+        if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        let int: BigInt = digits.parse().map_err(|_| invalid())?;
+        Ok(simplify(&Decimal { int, point: frac_part.len(), inexact: false }))
     }
 }
 
 #[test]
 fn test_decimal_from() {
     let examples = vec![
-        "0.0", "1.00", "1.14514", "2.333",
+        "0.0", "1.00", "1.14514", "2.333", ".5", "5.",
     ];
     for example in examples {
-        let dec = Decimal::from(example.to_string());
+        let dec = dec(example);
         println!("{:?}\n{0}\n", &dec);
     }
+    for example in ["1.2.3", ".", ""] {
+        assert!(matches!(example.parse::<Decimal>(), Err(CalcError::Lex { .. })));
+    }
 }
 
 impl Display for Decimal {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut int_string = self.int.to_string();
-        if self.int == 0 { return write!(f, "0.0"); }
+        if self.is_zero() { return write!(f, "0.0"); }
+        let mut int_string = self.int.abs().to_string();
         if self.point != 0 {
-            while int_string.len() < self.point as usize {
+            while int_string.len() <= self.point {
                 int_string.insert(0, '0');
             }
-            let index = int_string.len() - self.point as usize;
-            if index == 0 { int_string = "0.".to_string() + &int_string; }
-            else {
-                if self.int < 0 && index == 1 { int_string.insert_str(index, "0."); }
-                else { int_string.insert(index, '.'); }
-            }
+            int_string.insert(int_string.len() - self.point, '.');
         }
         else { int_string += ".0"; }
+        if self.int.is_negative() { int_string.insert(0, '-'); }
         write!(f, "{}", int_string)
     }
 }
@@ -127,137 +264,204 @@ impl Display for Decimal {
 #[test]
 fn test_decimal_display() {
     let examples = vec![
-        Decimal::_new(1, 2),
-        Decimal::_new(233, 2), 
-        Decimal::_new(-2333, 2),
-        Decimal::_new(233, 3),
-        Decimal::_new(-233, 3),
+        (Decimal::_new(1, 2), "0.01"),
+        (Decimal::_new(233, 2), "2.33"),
+        (Decimal::_new(-2333, 2), "-23.33"),
+        (Decimal::_new(233, 3), "0.233"),
+        (Decimal::_new(-233, 3), "-0.233"),
+        (Decimal::_new(-100, 0), "-100.0"),
     ];
-    for example in examples {
+    for (example, expected) in examples {
         println!("{:?}\n{0}\n", &example);
+        assert_eq!(example.to_string(), expected);
     }
 }
 
-impl Add for Decimal {
-    type Output = Self;
-    fn add(self, rhs: Self) -> Self::Output {
-        if self.point == rhs.point {
-            Decimal { int: self.int + rhs.int, point: self.point }
-        }
-        else {
-            let mut this_int = self.int;
-            let mut this_point = self.point;
-            let mut other_int = rhs.int;
-            let mut other_point = rhs.point;
-            while this_point != other_point {
-                if this_point < other_point {
-                    this_int *= 10;
-                    this_point += 1;
-                }
-                if this_point > other_point {
-                    other_int *= 10;
-                    other_point += 1;
-                }
+impl Decimal {  // arithmetic, reporting numbers above the limits instead of panicking
+    pub fn checked_add(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError> {
+        let point = max(self.point, rhs.point);
+        let this_int = self.int.mul_pow10(point - self.point);
+        let other_int = rhs.int.mul_pow10(point - rhs.point);
+        ctx.check(Decimal { int: &this_int + &other_int, point, inexact: self.inexact || rhs.inexact })
+    }
+
+    pub fn checked_sub(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError> {
+        self.checked_add(&-rhs, ctx)
+    }
+
+    pub fn checked_mul(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError> {
+        let int = &self.int * &rhs.int;
+        ctx.check(Decimal { int, point: self.point + rhs.point, inexact: self.inexact || rhs.inexact })
+    }
+
+    /// Division rounded to `ctx.precision` digits after the point.
+    pub fn checked_div(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError> {
+        if rhs.is_zero() { return Err(CalcError::division_by_zero()); }
+        // self / rhs = (self.int * 10^shift) / rhs.int * 10^-precision,
+        // where shift = rhs.point + precision - self.point
+        let shift = (rhs.point + ctx.precision) as isize - self.point as isize;
+        let numerator = self.int.mul_pow10(shift.max(0) as usize);
+        let denominator = rhs.int.mul_pow10((-shift).max(0) as usize);
+        let int = round_div(&numerator, &denominator, ctx.rounding);
+        let inexact = self.inexact || rhs.inexact || &int * &denominator != numerator;
+        ctx.check(simplify(&Decimal { int, point: ctx.precision, inexact }))
+    }
+
+    /// Round to `scale` digits after the point.
+    pub fn round(&self, scale: usize, mode: RoundingMode) -> Self {
+        if self.point <= scale { return self.clone(); }
+        let divisor = BigInt::one().mul_pow10(self.point - scale);
+        let int = round_div(&self.int, &divisor, mode);
+        let inexact = self.inexact || &int * &divisor != self.int;
+        Decimal { int, point: scale, inexact }
+    }
+}
+
+/// `numerator / denominator` rounded to an integer, `denominator` must not be zero.
+pub(crate) fn round_div(numerator: &BigInt, denominator: &BigInt, mode: RoundingMode) -> BigInt {
+    let (quotient, rem) = numerator.div_rem(denominator).unwrap_or_default();
+    if rem.is_zero() { return quotient; }
+    let negative = numerator.is_negative() != denominator.is_negative();
+    let away = match mode {
+        RoundingMode::TowardZero => false,
+        RoundingMode::Floor => negative,
+        RoundingMode::Ceiling => !negative,
+        RoundingMode::HalfUp | RoundingMode::HalfEven => {
+            let twice_rem = &rem.abs() * &BigInt::from(2);
+            match twice_rem.cmp(&denominator.abs()) {
+                std::cmp::Ordering::Less => false,
+                std::cmp::Ordering::Greater => true,
+                std::cmp::Ordering::Equal => mode == RoundingMode::HalfUp || !quotient.is_even(),
             }
-            Decimal { int: this_int + other_int, point: this_point }
+        },
+    };
+    if !away { return quotient; }
+    let step = BigInt::from(if negative { -1 } else { 1 });
+    &quotient + &step
+}
+
+#[test]
+fn test_decimal_round() {
+    let examples = vec![
+        // value, half-even, half-up, toward-zero, floor, ceiling
+        ("2.5", ["2", "3", "2", "2", "3"]),
+        ("3.5", ["4", "4", "3", "3", "4"]),
+        ("-2.5", ["-2", "-3", "-2", "-3", "-2"]),
+        ("2.51", ["3", "3", "2", "2", "3"]),
+        ("-2.49", ["-2", "-2", "-2", "-3", "-2"]),
+        ("7", ["7", "7", "7", "7", "7"]),
+    ];
+    let modes = [
+        RoundingMode::HalfEven, RoundingMode::HalfUp, RoundingMode::TowardZero,
+        RoundingMode::Floor, RoundingMode::Ceiling,
+    ];
+    for (value, expected) in examples {
+        let value = if let Some(v) = value.strip_prefix('-') { -dec(v) } else { dec(value) };
+        for (mode, expected) in modes.iter().zip(expected) {
+            let expected = if let Some(e) = expected.strip_prefix('-') { -dec(e) } else { dec(expected) };
+            assert_eq!(value.round(0, *mode), expected, "{} {}", value, mode);
         }
     }
+    assert_eq!(dec("1.23456").round(3, RoundingMode::HalfEven), dec("1.235"));
 }
 
 #[test]
 fn test_decimal_add() {
+    let ctx = Context::default();
     let examples = vec![
-        Decimal::_new(1140000, 4) + Decimal::_new(514, 3),
-        Decimal::_new(100, 1) + Decimal::_new(9, 1),
+        Decimal::_new(1140000, 4).checked_add(&Decimal::_new(514, 3), &ctx).unwrap(),
+        Decimal::_new(100, 1).checked_add(&Decimal::_new(9, 1), &ctx).unwrap(),
     ];
     for example in examples {
         println!("Data: {:?}\nResult: {0}\n", &example);
     }
+    let ctx = Context { max_digits: 5, ..Context::default() };
+    assert!(matches!(
+        Decimal::_new(99999, 0).checked_add(&Decimal::_new(1, 0), &ctx),
+        Err(CalcError::Overflow { .. })
+    ));
 }
 
 impl Neg for Decimal {
     type Output = Self;
     fn neg(self) -> Self::Output {
-        Decimal { int: -self.int, point: self.point }
+        Decimal { int: -self.int, ..self }
     }
 }
 
-impl Sub for Decimal {
-    type Output = Self;
-    fn sub(self, rhs: Self) -> Self::Output {
-        self + (-rhs)
+impl Neg for &Decimal {
+    type Output = Decimal;
+    fn neg(self) -> Self::Output {
+        -self.clone()
     }
 }
 
 #[test]
 fn test_decimal_sub() {
+    let ctx = Context::default();
     let examples = vec![
-        Decimal::_new(114514, 5) - Decimal::_new(114514, 5),
-        Decimal::_new(2333, 3) - Decimal::_new(003, 2),
-        Decimal::_new(100, 1) - Decimal::_new(9, 1),
+        Decimal::_new(114514, 5).checked_sub(&Decimal::_new(114514, 5), &ctx).unwrap(),
+        Decimal::_new(2333, 3).checked_sub(&Decimal::_new(3, 2), &ctx).unwrap(),
+        Decimal::_new(100, 1).checked_sub(&Decimal::_new(9, 1), &ctx).unwrap(),
     ];
     for example in examples {
         println!("Result: {}", &example);
     }
 }
 
-impl Mul for Decimal {
-    type Output = Self;
-    fn mul(self, rhs: Self) -> Self::Output {
-        let int = self.int * rhs.int;
-        let point = if self.is_integer() || rhs.is_integer() { max(self.point, rhs.point) }
-            else { self.point * rhs.point + 1 };
-        Decimal { int, point }
-    }
-}
-
 #[test]
 fn test_decimal_mul() {
+    let ctx = Context::default();
     let examples = vec![
-        Decimal::from(10.to_string()) * Decimal::from(2.333.to_string()),
+        (dec("10").checked_mul(&dec("2.333"), &ctx).unwrap(), dec("23.33")),
+        (dec("0.5").checked_mul(&dec("0.5"), &ctx).unwrap(), dec("0.25")),
+        (dec("0.25").checked_mul(&dec("0.25"), &ctx).unwrap(), dec("0.0625")),
+        (dec("1.5").checked_mul(&dec("0.02"), &ctx).unwrap(), dec("0.03")),
     ];
-    for example in examples {
+    for (example, expected) in examples {
         println!("Result: {}", &example);
+        assert_eq!(example, expected);
     }
-}
-
-impl Div for Decimal {
-    type Output = Self;
-    fn div(self, rhs: Self) -> Self::Output {
-        if rhs.is_zero() { panic!("Cannot divide by zero"); }
-        let mut this_int = self.int;
-        let mut this_point = self.point;
-        while this_int as f64 / rhs.int as f64 != (this_int / rhs.int) as f64 {
-            this_int *= 10;
-            this_point += 1;
-        }
-        let mut other_int = rhs.int;
-        let mut other_point = rhs.point;
-        while this_int as f64 / other_int as f64 != (this_int / other_int) as f64 {
-            other_int *= 10;
-            other_point += 1;
-        }
-        let int = this_int / other_int;
-        let point = this_point - other_point;
-        Decimal { int, point }
-    }
+    let big = dec("123456789012345678901234567890").checked_mul(&dec("987654321098765432109876543210"), &ctx);
+    assert_eq!(big.unwrap(), dec("121932631137021795226185032733622923332237463801111263526900"));
 }
 
 #[test]
 fn test_decimal_div() {
-    let _result = Decimal::from(1.14.to_string()) / Decimal::from(10.to_string());
-    println!("{}", _result);
-    let _result = Decimal::from(23.3.to_string()) / Decimal::from(0.1.to_string());
-    println!("{}", _result);
-    let _result = Decimal::from(2333.to_string()) / Decimal::from(0.2.to_string());
-    println!("{}", _result);
+    let ctx = Context::default();
+    let _result = dec("1.14").checked_div(&dec("10"), &ctx).unwrap();
+    assert_eq!(_result, dec("0.114"));
+    let _result = dec("23.3").checked_div(&dec("0.1"), &ctx).unwrap();
+    assert_eq!(_result, dec("233"));
+    let _result = dec("2333").checked_div(&dec("0.2"), &ctx).unwrap();
+    assert_eq!(_result, dec("11665"));
+    let _result = dec("10").checked_div(&dec("0.5"), &ctx).unwrap();
+    assert_eq!(_result, dec("20"));
+    let _result = (-dec("1")).checked_div(&dec("8"), &ctx).unwrap();
+    assert_eq!(_result, -dec("0.125"));
+    let _result = dec("2").checked_div(&dec("3"), &ctx).unwrap();
+    assert_eq!(_result, dec("0.66666666666666666667"));
+    assert!(_result.is_inexact() && !dec("1").checked_div(&dec("8"), &ctx).unwrap().is_inexact());
+
+    let ctx = Context { precision: 10, ..Context::default() };
+    let _result = dec("10").checked_div(&dec("3"), &ctx).unwrap();
+    assert_eq!(_result.to_string(), "3.3333333333");
+    let _result = (-dec("10")).checked_div(&dec("3"), &ctx).unwrap();
+    assert_eq!(_result.to_string(), "-3.3333333333");
+    let ctx = Context { precision: 10, rounding: RoundingMode::Ceiling, ..Context::default() };
+    let _result = dec("10").checked_div(&dec("3"), &ctx).unwrap();
+    assert_eq!(_result.to_string(), "3.3333333334");
+    let ctx = Context { precision: 2, ..Context::default() };
+    let _result = dec("0.001").checked_div(&dec("4"), &ctx).unwrap();
+    assert_eq!(_result.to_string(), "0.0");
+    let _result = dec("12345.6789").checked_div(&dec("0.001"), &ctx).unwrap();
+    assert_eq!(_result, dec("12345678.9"));
 }
 
 #[test]
-#[should_panic]
 fn test_decimal_div_zero() {
-    let _result = Decimal::from(1.to_string()) / Decimal::from(0.to_string());
-    println!("{}", _result);
+    let _result = dec("1").checked_div(&dec("0"), &Context::default());
+    assert!(matches!(_result, Err(CalcError::DivisionByZero { .. })));
 }
 
 impl PartialEq for Decimal {
@@ -270,18 +474,35 @@ impl PartialEq for Decimal {
     }
 }
 
+impl Eq for Decimal {}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let point = max(self.point, other.point);
+        self.int.mul_pow10(point - self.point).cmp(&other.int.mul_pow10(point - other.point))
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 #[test]
 fn test_decimal_partial_eq() {
-    let _result = Decimal::from("1.00".to_string()) == Decimal::from("1".to_string());
-    println!("Result: {}", _result);
+    let _result = dec("1.00") == dec("1");
+    assert!(_result);
+    assert!(dec("1.5") > dec("1.25") && -dec("1.5") < -dec("1.25") && dec("0.10") <= dec("0.1"));
 }
 
 #[test]
 fn test_decimal() {
+    let ctx = Context::default();
     let examples = vec![
-        Decimal::from(0.1.to_string()) + Decimal::from(0.1.to_string()) + Decimal::from(0.1.to_string()),
+        dec("0.1").checked_add(&dec("0.1"), &ctx).unwrap().checked_add(&dec("0.1"), &ctx).unwrap(),
     ];
     for example in examples {
         println!("Result: {}", &example);
     }
-}
\ No newline at end of file
+}
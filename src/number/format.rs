@@ -0,0 +1,445 @@
+use std::fmt::Display;
+
+use crate::{
+    error::CalcError,
+    number::{
+        bigint::BigInt,
+        complex::Complex,
+        context::{Context, RoundingMode},
+        decimal::{simplify, Decimal},
+        numeric::{Number, Numeric},
+        rational::Rational,
+        unit::Quantity
+    }
+};
+
+/// Where the exponent goes when a result is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Notation {
+    /// `1234.5`
+    #[default]
+    Plain,
+    /// `1.2345e3`, one digit before the point.
+    Scientific,
+    /// `1.2345e3`, an exponent that is a multiple of 3.
+    Engineering,
+}
+
+/// How many digits a result is written with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Digits {
+    /// As many as the result has.
+    #[default]
+    All,
+    /// This many after the point (of the mantissa, with an exponent).
+    Fixed(usize),
+    /// This many significant figures.
+    Significant(usize),
+}
+
+/// How exact fractions are written, in rational arithmetic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FractionStyle {
+    /// `7/2`
+    #[default]
+    Fraction,
+    /// `3 1/2`
+    Mixed,
+    /// `3.5`, as any other decimal.
+    Decimal,
+}
+
+/// How results are written, e.g. `1,234.50` or `1.2345e3`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormatOptions {
+    pub notation: Notation,
+    pub digits: Digits,
+    /// Put between groups of three digits before the point, e.g. `,` for `1,000,000`.
+    pub group_separator: Option<char>,
+    /// `.` by default, `,` in many locales.
+    pub decimal_mark: char,
+    /// Drop the zeros at the end of the fraction, and the point if nothing is left,
+    /// e.g. `2.50` for `2.5` and `3.0` for `3`.
+    pub trim: bool,
+    pub fraction: FractionStyle
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            notation: Notation::Plain, digits: Digits::All, group_separator: None, decimal_mark: '.', trim: false,
+            fraction: FractionStyle::Fraction
+        }
+    }
+}
+
+impl FormatOptions {
+    /// Change one option by name, as in the `:format` command of the shell:
+    /// `fixed 2`, `significant 3` (`off` for all digits), `notation scientific`,
+    /// `group ,` (`space` for a space, `none` for no separator), `decimal-mark ,`, `trim on`
+    /// and `fraction mixed`.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        let count = |value: &str| match value {
+            "off" => Ok(None),
+            n => n.parse().map(Some).map_err(|_| format!("Invalid number of digits '{}'", n)),
+        };
+        let single = |value: &str| {
+            let mut chars = value.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) if !c.is_ascii_digit() && c != '-' => Ok(c),
+                _ => Err(format!("Expected a single character that is not a digit, found '{}'", value)),
+            }
+        };
+        // `1,234,5` could be read either way
+        let distinct = |separator: Option<char>, mark: char| match separator {
+            Some(separator) if separator == mark => Err(format!(
+                "The group separator and the decimal mark cannot both be '{}'", mark
+            )),
+            _ => Ok(()),
+        };
+        match name {
+            "fixed" => self.digits = count(value)?.map_or(Digits::All, Digits::Fixed),
+            "significant" => {
+                self.digits = match count(value)? {
+                    Some(0) => return Err("Significant figures must be at least 1".to_string()),
+                    n => n.map_or(Digits::All, Digits::Significant),
+                }
+            },
+            "notation" => {
+                self.notation = match value {
+                    "plain" => Notation::Plain,
+                    "scientific" => Notation::Scientific,
+                    "engineering" => Notation::Engineering,
+                    other => return Err(format!(
+                        "Unknown notation '{}', expected one of plain, scientific, engineering", other
+                    )),
+                }
+            },
+            "group" => {
+                let separator = match value {
+                    "none" => None,
+                    "space" => Some(' '),
+                    other => Some(single(other)?),
+                };
+                distinct(separator, self.decimal_mark)?;
+                self.group_separator = separator;
+            },
+            "decimal-mark" => {
+                let mark = single(value)?;
+                distinct(self.group_separator, mark)?;
+                self.decimal_mark = mark;
+            },
+            "trim" => {
+                self.trim = match value {
+                    "on" => true,
+                    "off" => false,
+                    other => return Err(format!("Expected on or off, found '{}'", other)),
+                }
+            },
+            "fraction" => {
+                self.fraction = match value {
+                    "fraction" => FractionStyle::Fraction,
+                    "mixed" => FractionStyle::Mixed,
+                    "decimal" => FractionStyle::Decimal,
+                    other => return Err(format!(
+                        "Unknown fraction style '{}', expected one of fraction, mixed, decimal", other
+                    )),
+                }
+            },
+            other => return Err(format!(
+                "Unknown format option '{}', expected one of fixed, significant, notation, group, decimal-mark, trim, \
+                 fraction", other
+            )),
+        }
+        Ok(())
+    }
+
+    /// Write a result: a fraction as `fraction` asks, and a decimal (or a fraction written as one)
+    /// as `format` does, an inexact one only to `ctx.precision`.
+    pub(crate) fn format_number(&self, value: &Number, ctx: &Context) -> Result<String, CalcError> {
+        match (value, self.fraction) {
+            (Number::Rational(value), FractionStyle::Fraction) => Ok(self.write_fraction(value, false)),
+            (Number::Rational(value), FractionStyle::Mixed) => Ok(self.write_fraction(value, true)),
+            (Number::Complex(value), _) => Ok(self.write_complex(value, ctx)),
+            _ => Ok(self.format(&significant(&value.to_decimal(ctx)?, ctx), ctx.rounding)),
+        }
+    }
+
+    /// The number followed by its unit, e.g. `9.81 m/s^2`, with a complex one in parentheses.
+    pub(crate) fn format_quantity(&self, value: &Quantity<Number>, ctx: &Context) -> Result<String, CalcError> {
+        let number = self.format_number(value.value(), ctx)?;
+        Ok(match value.value() {
+            _ if value.unit().is_none() => number,
+            Number::Complex(z) if !z.re().is_zero() => format!("({}) {}", number, value.unit()),
+            _ => format!("{} {}", number, value.unit()),
+        })
+    }
+
+    /// `-7/2`, or `-3 1/2` if `mixed`; an integer is written alone.
+    fn write_fraction(&self, value: &Rational, mixed: bool) -> String {
+        let sign = if value.is_negative() { "-" } else { "" };
+        let (num, den) = (value.numerator().abs(), value.denominator());
+        if den == &BigInt::one() {
+            return format!("{}{}", sign, self.integer(&num));
+        }
+        let (whole, rem) = num.div_rem(den).unwrap_or_default();
+        if !mixed || whole.is_zero() {
+            return format!("{}{}/{}", sign, self.integer(&num), self.integer(den));
+        }
+        format!("{}{} {}/{}", sign, self.integer(&whole), self.integer(&rem), self.integer(den))
+    }
+
+    /// `1.5 - 2.0i`, each part written like a real number; `2.0i` without a real part.
+    fn write_complex(&self, value: &Complex, ctx: &Context) -> String {
+        let part = |value: &Decimal| self.format(&significant(value, ctx), ctx.rounding);
+        let im = format!("{}i", part(&value.im().abs()));
+        if value.re().is_zero() {
+            return if value.im().is_negative() { format!("-{}", im) } else { im };
+        }
+        let sign = if value.im().is_negative() { '-' } else { '+' };
+        format!("{} {} {}", part(value.re()), sign, im)
+    }
+
+    fn integer(&self, value: &BigInt) -> String {
+        let options = FormatOptions { trim: true, ..self.clone() };
+        options.plain((Decimal::from_parts(value.clone(), 0), None))
+    }
+
+    /// Write `value`, rounding the digits it cannot keep by `rounding`.
+    pub(crate) fn format(&self, value: &Decimal, rounding: RoundingMode) -> String {
+        let value = simplify(value);
+        let step = match self.notation {
+            Notation::Plain => return self.plain(self.round(&value, rounding)),
+            Notation::Scientific => 1,
+            Notation::Engineering => 3,
+        };
+        if value.is_zero() {
+            return format!("{}e0", self.plain(self.round(&value, rounding)));
+        }
+        let mut exponent = value.exponent().div_euclid(step) * step;
+        loop {
+            let (mantissa, scale) = self.round(&value.mul_pow10(-exponent), rounding);
+            // rounding up may reach the next power, e.g. 9.99 to 10.0
+            if mantissa.abs().exponent() >= step {
+                exponent += step;
+                continue;
+            }
+            return format!("{}e{}", self.plain((mantissa, scale)), exponent);
+        }
+    }
+
+    /// `value` rounded as `digits` asks, with how many digits to show after the point,
+    /// or `None` to show it as it is.
+    fn round(&self, value: &Decimal, rounding: RoundingMode) -> (Decimal, Option<usize>) {
+        let scale = match self.digits {
+            Digits::All => return (simplify(value), None),
+            Digits::Fixed(n) => n as isize,
+            Digits::Significant(n) => n as isize - 1 - value.exponent(),
+        };
+        let rounded = simplify(&round_to(value, scale, rounding));
+        // 9.99 to 2 figures is 10, not 10.0
+        let carried = matches!(self.digits, Digits::Significant(_))
+            && !value.is_zero() && rounded.exponent() > value.exponent();
+        let scale = if carried { scale - 1 } else { scale };
+        (rounded, Some(scale.max(0) as usize))
+    }
+
+    /// Write `value` without exponent, with `scale` digits after the point if given.
+    fn plain(&self, (value, scale): (Decimal, Option<usize>)) -> String {
+        let text = value.to_string();
+        let (sign, text) = match text.strip_prefix('-') {
+            Some(rest) => ("-", rest),
+            None => ("", text.as_str()),
+        };
+        let (int_part, frac_part) = text.split_once('.').unwrap_or((text, ""));
+        let mut frac_part = match scale {
+            // `value` has at most `scale` digits after the point, and `Display` adds `.0` to integers
+            Some(scale) => {
+                let digits = if value.scale() == 0 { "" } else { frac_part };
+                format!("{:0<width$}", digits, width = scale)
+            },
+            None => frac_part.to_string(),
+        };
+        if self.trim {
+            frac_part = frac_part.trim_end_matches('0').to_string();
+        }
+
+        let mut result = sign.to_string();
+        for (i, digit) in int_part.chars().enumerate() {
+            if i > 0 && (int_part.len() - i) % 3 == 0 {
+                result.extend(self.group_separator);
+            }
+            result.push(digit);
+        }
+        if !frac_part.is_empty() {
+            result.push(self.decimal_mark);
+            result += &frac_part;
+        }
+        result
+    }
+}
+
+/// `value` without the digits past `ctx.precision` if it is inexact, e.g. the product in `sqrt(2) * sqrt(2)`,
+/// which has twice as many digits as the roots, only as many of them right.
+fn significant(value: &Decimal, ctx: &Context) -> Decimal {
+    if value.is_inexact() { value.round(ctx.precision, ctx.rounding) } else { value.clone() }
+}
+
+/// `value` rounded to `scale` digits after the point, which may be negative to round to tens etc.
+fn round_to(value: &Decimal, scale: isize, rounding: RoundingMode) -> Decimal {
+    if scale >= 0 {
+        return value.round(scale as usize, rounding);
+    }
+    value.mul_pow10(scale).round(0, rounding).mul_pow10(-scale)
+}
+
+impl Display for FormatOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (fixed, significant) = match self.digits {
+            Digits::All => ("off".to_string(), "off".to_string()),
+            Digits::Fixed(n) => (n.to_string(), "off".to_string()),
+            Digits::Significant(n) => ("off".to_string(), n.to_string()),
+        };
+        let notation = match self.notation {
+            Notation::Plain => "plain",
+            Notation::Scientific => "scientific",
+            Notation::Engineering => "engineering",
+        };
+        let group = match self.group_separator {
+            None => "none".to_string(),
+            Some(' ') => "space".to_string(),
+            Some(c) => c.to_string(),
+        };
+        let fraction = match self.fraction {
+            FractionStyle::Fraction => "fraction",
+            FractionStyle::Mixed => "mixed",
+            FractionStyle::Decimal => "decimal",
+        };
+        write!(
+            f, "fixed {}, significant {}, notation {}, group {}, decimal-mark {}, trim {}, fraction {}",
+            fixed, significant, notation, group, self.decimal_mark, if self.trim { "on" } else { "off" }, fraction
+        )
+    }
+}
+
+#[test]
+fn test_format() {
+    let dec = |s: &str| match s.strip_prefix('-') {
+        Some(s) => -s.parse::<Decimal>().unwrap(),
+        None => s.parse::<Decimal>().unwrap(),
+    };
+    // value, settings, expected
+    type Example<'a> = (&'a str, &'a [(&'a str, &'a str)], &'a str);
+    let examples: Vec<Example> = vec![
+        ("1234.5", &[], "1234.5"),
+        ("3", &[], "3.0"),
+        ("3", &[("trim", "on")], "3"),
+        ("2.50", &[("trim", "on")], "2.5"),
+        ("1234567.891", &[("group", ","), ("fixed", "2")], "1,234,567.89"),
+        ("-1234567.891", &[("decimal-mark", ","), ("group", ".")], "-1.234.567,891"),
+        ("123", &[("group", " ")], "123.0"),
+        ("2.5", &[("fixed", "3")], "2.500"),
+        ("2.5", &[("fixed", "0")], "2"),
+        ("-0.001", &[("fixed", "2")], "0.00"),
+        ("3.14159", &[("significant", "3")], "3.14"),
+        ("9.99", &[("significant", "2")], "10"),
+        ("0.00012345", &[("significant", "2")], "0.00012"),
+        ("123456", &[("significant", "2")], "120000"),
+        ("1.5", &[("significant", "4")], "1.500"),
+        ("0.00000015", &[("notation", "scientific")], "1.5e-7"),
+        ("602000000000000000000000", &[("notation", "scientific")], "6.02e23"),
+        ("1000", &[("notation", "scientific")], "1.0e3"),
+        ("1000", &[("notation", "scientific"), ("trim", "on")], "1e3"),
+        ("9.996", &[("notation", "scientific"), ("significant", "3")], "1.00e1"),
+        ("-123456", &[("notation", "scientific"), ("fixed", "2")], "-1.23e5"),
+        ("0", &[("notation", "scientific")], "0.0e0"),
+        ("123456", &[("notation", "engineering")], "123.456e3"),
+        ("0.0015", &[("notation", "engineering")], "1.5e-3"),
+        ("999999", &[("notation", "engineering"), ("significant", "3")], "1.00e6"),
+        ("123456", &[("notation", "engineering"), ("significant", "2")], "120e3"),
+    ];
+    for (value, settings, expected) in examples {
+        let mut options = FormatOptions::default();
+        for (name, setting) in settings {
+            options.set(name, setting).unwrap();
+        }
+        assert_eq!(options.format(&dec(value), RoundingMode::HalfEven), expected, "{} {:?}", value, settings);
+    }
+    let mut options = FormatOptions { digits: Digits::Fixed(0), ..FormatOptions::default() };
+    assert_eq!(options.format(&dec("2.5"), RoundingMode::HalfUp), "3");
+    assert!(options.set("significant", "0").is_err());
+    assert!(options.set("notation", "fancy").is_err());
+    assert!(options.set("group", "1").is_err());
+    // the group separator and the decimal mark must differ, whichever is set first
+    assert!(options.set("group", ".").is_err());
+    options.set("group", ",").unwrap();
+    let err = options.set("decimal-mark", ",").unwrap_err();
+    assert_eq!(err, "The group separator and the decimal mark cannot both be ','");
+    options.set("decimal-mark", ";").unwrap();
+    assert!(options.set("group", ";").is_err());
+    assert_eq!((options.group_separator, options.decimal_mark), (Some(','), ';'));
+    options.set("group", "none").unwrap();
+    options.set("decimal-mark", ".").unwrap();
+    assert!(options.set("colour", "on").is_err());
+    options.set("fixed", "off").unwrap();
+    assert_eq!(
+        options.to_string(),
+        "fixed off, significant off, notation plain, group none, decimal-mark ., trim off, fraction fraction"
+    );
+}
+
+#[test]
+fn test_format_fraction() {
+    let ctx = Context::default();
+    let ratio = |num: i128, den: i128| Number::Rational(Rational::new(BigInt::from(num), BigInt::from(den)).unwrap());
+    // value, style, expected
+    let examples = vec![
+        (ratio(1, 2), "fraction", "1/2"),
+        (ratio(-7, 2), "fraction", "-7/2"),
+        (ratio(6, 2), "fraction", "3"),
+        (ratio(7, 2), "mixed", "3 1/2"),
+        (ratio(-7, 2), "mixed", "-3 1/2"),
+        (ratio(-1, 2), "mixed", "-1/2"),
+        (ratio(7, 2), "decimal", "3.5"),
+        (ratio(1, 3), "decimal", "0.33333333333333333333"),
+        (ratio(1_000_001, 1000), "fraction", "1,000,001/1,000"),
+    ];
+    for (value, style, expected) in examples {
+        let mut options = FormatOptions { group_separator: Some(','), ..FormatOptions::default() };
+        options.set("fraction", style).unwrap();
+        assert_eq!(options.format_number(&value, &ctx).unwrap(), expected, "{} {}", value, style);
+    }
+    // a decimal stays a decimal
+    let options = FormatOptions::default();
+    assert_eq!(options.format_number(&Number::Decimal("2.5".parse().unwrap()), &ctx).unwrap(), "2.5");
+    assert!(FormatOptions::default().set("fraction", "egyptian").is_err());
+}
+
+#[test]
+fn test_format_complex() {
+    let ctx = Context::default();
+    let complex = |re: &str, im: &str| Number::Complex(Complex::new(re.parse().unwrap(), im.parse().unwrap()));
+    let mut options = FormatOptions::default();
+    assert_eq!(options.format_number(&complex("5", "5").conj(), &ctx).unwrap(), "5.0 - 5.0i");
+    assert_eq!(options.format_number(&-complex("0", "2"), &ctx).unwrap(), "-2.0i");
+    options.set("fixed", "2").unwrap();
+    assert_eq!(options.format_number(&complex("0.125", "1.5"), &ctx).unwrap(), "0.12 + 1.50i");
+}
+
+#[test]
+fn test_format_inexact() {
+    use crate::number::math;
+    let ctx = Context::default();
+    let options = FormatOptions::default();
+    let root = math::sqrt(&Decimal::from_parts(BigInt::from(2), 0), &ctx).unwrap();
+    let square = Number::Decimal(root.checked_mul(&root, &ctx).unwrap());
+    assert_eq!(square.to_string(), "1.99999999999999999999522356663907438144");
+    assert_eq!(options.format_number(&square, &ctx).unwrap(), "2.0");
+    let pi = math::pi(&ctx).unwrap();
+    let square = Number::Decimal(pi.checked_mul(&pi, &ctx).unwrap());
+    assert_eq!(options.format_number(&square, &ctx).unwrap(), "9.86960440108935861882");
+    // exact digits are all kept, however many there are
+    let exact: Decimal = "1.23456789012345678901".parse().unwrap();
+    let product = Number::Decimal(exact.checked_mul(&"1.1".parse().unwrap(), &ctx).unwrap());
+    assert_eq!(options.format_number(&product, &ctx).unwrap(), "1.358024679135802467911");
+}
@@ -0,0 +1,69 @@
+use crate::{
+    error::CalcError,
+    number::{bigint::BigInt, context::Context, decimal::{simplify, Decimal}}
+};
+
+/// The value of a number literal in any form the tokenizer accepts:
+/// `12.5`, `1_000`, `1.5e-7`, `6.02E23`, `0x1F`, `0o17` and `0b1011`.
+pub fn parse_literal(text: &str, ctx: &Context) -> Result<Decimal, CalcError> {
+    let invalid = || CalcError::Lex { message: format!("Invalid number '{}'", text), span: None };
+    let too_large = || CalcError::Overflow {
+        message: format!("Number has more than {} digits", ctx.max_digits), span: None
+    };
+    let lower = text.replace('_', "").to_ascii_lowercase();
+    let radix = match lower.get(..2) {
+        Some("0x") => 16,
+        Some("0o") => 8,
+        Some("0b") => 2,
+        _ => 10,
+    };
+    if radix != 10 {
+        let digits = &lower[2..];
+        if digits.len() as f64 * (radix as f64).log10() > ctx.max_digits as f64 + 1.0 {
+            return Err(too_large());
+        }
+        let int = BigInt::from_radix(digits, radix).ok_or_else(invalid)?;
+        return ctx.check(Decimal::from_parts(int, 0));
+    }
+
+    let (mantissa, exponent) = match lower.split_once('e') {
+        Some((mantissa, exponent)) => {
+            let digits = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+            if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(invalid());
+            }
+            // only digits are left, so it can only fail by being too long
+            (mantissa, exponent.parse::<i64>().map_err(|_| too_large())?)
+        },
+        None => (lower.as_str(), 0),
+    };
+    let value: Decimal = mantissa.parse().map_err(|_| invalid())?;
+    if value.is_zero() || exponent == 0 { return ctx.check(value); }
+    // `1e999999999` would build a billion digits before the check
+    let first_digit = (value.exponent() as i64).saturating_add(exponent);
+    let scale = (value.scale() as i64).saturating_sub(exponent);
+    if first_digit >= ctx.max_digits as i64 || scale > ctx.max_digits as i64 {
+        return Err(too_large());
+    }
+    ctx.check(simplify(&value.mul_pow10(exponent as isize)))
+}
+
+#[test]
+fn test_parse_literal() {
+    let ctx = Context::default();
+    let examples = vec![
+        ("12.5", "12.5"), ("1_000_000", "1000000.0"), (".5", "0.5"), ("5.", "5.0"),
+        ("1.5e-7", "0.00000015"), ("6.02E23", "602000000000000000000000.0"), ("1e+3", "1000.0"),
+        ("2.5e0", "2.5"), ("0e99999999999", "0.0"),
+        ("0x1F", "31.0"), ("0XfF", "255.0"), ("0o17", "15.0"), ("0b1011", "11.0"),
+    ];
+    for (literal, expected) in examples {
+        assert_eq!(parse_literal(literal, &ctx).unwrap().to_string(), expected, "{}", literal);
+    }
+    for literal in ["1e99999", "1e-99999", "1e99999999999999999999", "9e9223372036854775807"] {
+        assert!(matches!(parse_literal(literal, &ctx), Err(CalcError::Overflow { .. })), "{}", literal);
+    }
+    for literal in ["1.2.3", "0x", "0b12", "1e", "1e+-5", "1e1.5", ""] {
+        assert!(matches!(parse_literal(literal, &ctx), Err(CalcError::Lex { .. })), "{}", literal);
+    }
+}
@@ -0,0 +1,591 @@
+use crate::{
+    error::CalcError,
+    number::{bigint::BigInt, context::{Context, RoundingMode}, decimal::{simplify, Decimal}}
+};
+
+/// Digits carried beyond the requested precision in intermediate results.
+const GUARD_DIGITS: usize = 10;
+/// Roots above this degree are computed through `exp` and `ln` instead.
+const MAX_ROOT_DEGREE: u64 = 64;
+/// The most digits before the point that a power through `exp` and `ln` may have.
+const MAX_SERIES_DIGITS: usize = 1000;
+
+/// A context for intermediate results, `extra` digits more precise than `ctx`.
+pub(crate) fn working(ctx: &Context, extra: usize) -> Context {
+    let precision = ctx.precision + GUARD_DIGITS + extra;
+    let max_digits = ctx.max_digits + 2 * precision;
+    Context { precision, rounding: RoundingMode::HalfEven, max_digits, ..ctx.clone() }
+}
+
+/// Round an intermediate result to what `ctx` asks for.
+fn finish(value: &Decimal, ctx: &Context) -> Result<Decimal, CalcError> {
+    ctx.check(simplify(&value.round(ctx.precision, ctx.rounding)))
+}
+
+/// A positive number below anything `ctx` can show, which still rounds up under `Ceiling`.
+fn tiny(ctx: &Context) -> Decimal {
+    Decimal::from_parts(BigInt::one(), ctx.precision + 2)
+}
+
+fn mul(a: &Decimal, b: &Decimal, w: &Context) -> Result<Decimal, CalcError> {
+    Ok(a.checked_mul(b, w)?.round(w.precision, RoundingMode::HalfEven))
+}
+
+fn int(n: i128) -> Decimal {
+    Decimal::from_parts(BigInt::from(n), 0)
+}
+
+/// `z + s z^3/3 + s^2 z^5/5 + ...`, which is `atanh(z)` for `s = 1` and `atan(z)` for `s = -1`.
+/// It converges quickly for small `|z|`.
+fn odd_series(z: &Decimal, alternating: bool, w: &Context) -> Result<Decimal, CalcError> {
+    let z2 = mul(z, z, w)?;
+    let z2 = if alternating { -z2 } else { z2 };
+    let mut power = z.clone();
+    let mut sum = Decimal::zero();
+    let mut k = 1;
+    loop {
+        let term = power.checked_div(&int(k), w)?;
+        if term.is_zero() { break; }
+        sum = sum.checked_add(&term, w)?;
+        power = mul(&power, &z2, w)?;
+        k += 2;
+    }
+    Ok(sum)
+}
+
+fn atanh(z: &Decimal, w: &Context) -> Result<Decimal, CalcError> {
+    odd_series(z, false, w)
+}
+
+/// `ln(2) = 2 atanh(1/3)`
+fn ln2(w: &Context) -> Result<Decimal, CalcError> {
+    let third = int(1).checked_div(&int(3), w)?;
+    atanh(&third, w)?.checked_mul(&int(2), w)
+}
+
+/// `ln(10) = 3 ln(2) + ln(1.25) = 3 ln(2) + 2 atanh(1/9)`
+fn ln10(w: &Context) -> Result<Decimal, CalcError> {
+    let ninth = int(1).checked_div(&int(9), w)?;
+    let ln_125 = atanh(&ninth, w)?.checked_mul(&int(2), w)?;
+    ln2(w)?.checked_mul(&int(3), w)?.checked_add(&ln_125, w)
+}
+
+/// `ln(x)` to `w.precision` digits, for `x > 0`.
+fn ln_raw(x: &Decimal, w: &Context) -> Result<Decimal, CalcError> {
+    // x = m * 2^j * 10^k with 1 <= m < 2
+    let k = x.exponent();
+    let mut m = x.mul_pow10(-k);
+    let mut j = 0;
+    let two = int(2);
+    let half = Decimal::from_parts(BigInt::from(5), 1);
+    while m >= two {
+        m = m.checked_mul(&half, w)?;
+        j += 1;
+    }
+    let z = m.checked_sub(&int(1), w)?.checked_div(&m.checked_add(&int(1), w)?, w)?;
+    let mut result = atanh(&z, w)?.checked_mul(&two, w)?;
+    // the constants are multiplied by `k`, so they need its digits on top
+    let wc = Context { precision: w.precision + k.unsigned_abs().to_string().len(), ..w.clone() };
+    if j > 0 {
+        result = result.checked_add(&ln2(&wc)?.checked_mul(&int(j), w)?, w)?;
+    }
+    if k != 0 {
+        result = result.checked_add(&ln10(&wc)?.checked_mul(&int(k as i128), w)?, w)?;
+    }
+    Ok(result.round(w.precision, RoundingMode::HalfEven))
+}
+
+/// `e^x` to `w.precision` digits after the point, if it has at most the `max_digits` of `ctx`.
+fn exp_raw(x: &Decimal, ctx: &Context, w: &Context) -> Result<Decimal, CalcError> {
+    if x.is_zero() { return Ok(int(1)); }
+    // x = n ln(2) + r, with |r| <= ln(2) / 2
+    let estimate = x.to_f64() / std::f64::consts::LN_2;
+    let digits = estimate * std::f64::consts::LOG10_2;
+    if digits > ctx.max_digits as f64 {
+        return Err(CalcError::Overflow {
+            message: format!("Result has more than {} digits", ctx.max_digits), span: None
+        });
+    }
+    if digits < -(w.precision as f64 + 2.0) { return Ok(tiny(w)); }
+    let n = estimate.round() as i64;
+    // 2^n scales the error of e^r as well, so carry its digits too
+    let extra = digits.max(0.0).ceil() as usize + n.unsigned_abs().to_string().len() + 3;
+    let wx = Context { precision: w.precision + extra, ..w.clone() };
+    let r = x.checked_sub(&ln2(&wx)?.checked_mul(&int(n as i128), &wx)?, &wx)?;
+
+    // e^r = (e^(r / 256))^256, where the series converges fast
+    const HALVINGS: usize = 8;
+    let r = r.checked_mul(&Decimal::from_parts(BigInt::from(390625), 8), &wx)?;
+    let mut sum = int(1);
+    let mut term = int(1);
+    let mut k = 1;
+    loop {
+        term = mul(&term, &r, &wx)?.checked_div(&int(k), &wx)?;
+        if term.is_zero() { break; }
+        sum = sum.checked_add(&term, &wx)?;
+        k += 1;
+    }
+    for _ in 0..HALVINGS {
+        sum = mul(&sum, &sum, &wx)?;
+    }
+
+    let power = BigInt::from(2).pow(n.unsigned_abs() as u32);
+    let result = if n >= 0 {
+        sum.checked_mul(&Decimal::from_parts(power, 0), &wx)?
+    }
+    else {
+        sum.checked_div(&Decimal::from_parts(power, 0), &wx)?
+    };
+    Ok(result.round(w.precision, RoundingMode::HalfEven))
+}
+
+/// `base ^ exp`, or `base ^ -exp` if `reciprocal`, rounded to `ctx.precision`,
+/// for when the exact power has too many digits, e.g. `0.5 ^ 100000` or `2 ^ -100000`.
+pub(crate) fn pow_rounded(base: &Decimal, exp: u64, reciprocal: bool, ctx: &Context) -> Result<Decimal, CalcError> {
+    let negative = base.is_negative() && exp % 2 == 1;
+    let magnitude = exp as f64 * base.approx_log10() * if reciprocal { -1.0 } else { 1.0 };
+    if magnitude < -(ctx.precision as f64 + 2.0) {
+        return finish(&if negative { -tiny(ctx) } else { tiny(ctx) }, ctx);
+    }
+    // each product rounds off at most one unit of the last digit, which the later ones multiply
+    let extra = magnitude.abs().ceil() as usize + exp.to_string().len() + 2;
+    let w = working(ctx, extra);
+    let mut result = int(1);
+    let mut power = base.round(w.precision, RoundingMode::HalfEven);
+    let mut n = exp;
+    while n > 0 {
+        if n % 2 == 1 { result = mul(&result, &power, &w)?; }
+        n /= 2;
+        if n > 0 { power = mul(&power, &power, &w)?; }
+    }
+    if reciprocal { result = int(1).checked_div(&result, &w)?; }
+    finish(&result, ctx)
+}
+
+/// `base ^ (numerator / denominator)`.
+///
+/// Small roots are taken exactly when possible, so that `8 ^ (1 / 3)` is `2`;
+/// the others go through `exp(y ln(base))`.
+pub fn pow_fraction(
+    base: &Decimal, numerator: &BigInt, denominator: &BigInt, ctx: &Context
+) -> Result<Decimal, CalcError> {
+    if denominator.is_zero() { return Err(CalcError::division_by_zero()); }
+    let gcd = numerator.gcd(denominator);
+    let (mut p, _) = numerator.div_rem(&gcd).unwrap_or_default();
+    let (mut q, _) = denominator.div_rem(&gcd).unwrap_or_default();
+    if q.is_negative() { (p, q) = (-p, -q); }
+    if q == BigInt::one() { return base.pow(&Decimal::from_parts(p, 0), ctx); }
+
+    if base.is_zero() {
+        if p.is_negative() { return Err(CalcError::division_by_zero()); }
+        return Ok(Decimal::zero());
+    }
+    if base.is_negative() {
+        if q.is_even() {
+            return Err(CalcError::domain(format!(
+                "Negative base to the power of {}/{} has no real value", p, q
+            )));
+        }
+        // an odd root of a negative number is negative, so round the other way
+        let rounding = match ctx.rounding {
+            RoundingMode::Floor => RoundingMode::Ceiling,
+            RoundingMode::Ceiling => RoundingMode::Floor,
+            other => other,
+        };
+        let result = pow_fraction(&-base, &p, &q, &Context { rounding, ..ctx.clone() })?;
+        return Ok(if p.is_even() { result } else { -result });
+    }
+
+    let exponent = Decimal::from_parts(p.clone(), 0).to_f64() / Decimal::from_parts(q.clone(), 0).to_f64();
+    let digits = exponent * base.approx_log10();
+    if digits > ctx.max_digits as f64 {
+        return Err(CalcError::Overflow {
+            message: format!("Result has more than {} digits", ctx.max_digits), span: None
+        });
+    }
+    if digits < -(ctx.precision as f64 + 2.0) { return finish(&tiny(ctx), ctx); }
+
+    let extra = digits.max(0.0).ceil() as usize + exponent.abs().log10().max(0.0).ceil() as usize + 2;
+    if let (Some(p_abs), Some(degree)) = (p.abs().to_u64(), q.to_u64()) {
+        if degree <= MAX_ROOT_DEGREE {
+            // the power comes first while it fits, so that exact roots stay exact
+            if p_abs as f64 * base.approx_log10().abs() <= ctx.max_digits as f64 {
+                return root(base, p_abs, degree as u32, p.is_negative(), ctx);
+            }
+            let w = working(ctx, extra + p_abs.to_string().len());
+            return root(base, 1, degree as u32, false, &w)?.pow(&Decimal::from_parts(p, 0), ctx);
+        }
+    }
+
+    // the series cost grows much faster than the digits they are asked for
+    if extra > MAX_SERIES_DIGITS {
+        return Err(CalcError::Overflow {
+            message: format!(
+                "Powers with more than {} digits need a root of degree at most {}", MAX_SERIES_DIGITS, MAX_ROOT_DEGREE
+            ),
+            span: None
+        });
+    }
+    let w = working(ctx, extra);
+    let y = Decimal::from_parts(p, 0).checked_div(&Decimal::from_parts(q, 0), &w)?;
+    let product = mul(&y, &ln_raw(base, &w)?, &w)?;
+    finish(&exp_raw(&product, ctx, &w)?, ctx)
+}
+
+/// `base ^ (±p / q)` through the integer `q`-th root, for a positive `base`.
+fn root(base: &Decimal, p: u64, q: u32, reciprocal: bool, ctx: &Context) -> Result<Decimal, CalcError> {
+    let w = working(ctx, 0);
+    let powered = simplify(&base.pow(&Decimal::from_parts(BigInt::from(p as i128), 0), &w)?);
+    // root(x) * 10^digits = root(x * 10^(q * digits)), an integer once digits >= point / q
+    let digits = w.precision.max(powered.scale().div_ceil(q as usize));
+    let radicand = powered.mul_pow10((q as usize * digits) as isize).to_integer().unwrap_or_default();
+    let root = radicand.nth_root(q);
+    let value = if root.pow(q) == radicand {
+        Decimal::from_parts(root, digits)
+    }
+    else {
+        // a last digit of 1 tells the rounding that the true value is a bit larger
+        Decimal::from_parts(&root.mul_pow10(1) + &BigInt::one(), digits + 1)
+    };
+    if reciprocal {
+        return finish(&int(1).checked_div(&value, &w)?, ctx);
+    }
+    finish(&value, ctx)
+}
+
+/// `pi = 16 atan(1/5) - 4 atan(1/239)`
+fn pi_raw(w: &Context) -> Result<Decimal, CalcError> {
+    let a = odd_series(&int(1).checked_div(&int(5), w)?, true, w)?;
+    let b = odd_series(&int(1).checked_div(&int(239), w)?, true, w)?;
+    a.checked_mul(&int(16), w)?.checked_sub(&b.checked_mul(&int(4), w)?, w)
+}
+
+/// `sqrt(x)` truncated to `w.precision` digits, for `x >= 0`.
+fn sqrt_raw(x: &Decimal, w: &Context) -> Decimal {
+    let radicand = x.mul_pow10(2 * w.precision as isize).round(0, RoundingMode::TowardZero);
+    Decimal::from_parts(radicand.to_integer().unwrap_or_default().nth_root(2), w.precision)
+}
+
+fn atan_raw(x: &Decimal, w: &Context) -> Result<Decimal, CalcError> {
+    if x.is_negative() { return Ok(-atan_raw(&-x, w)?); }
+    if *x > int(1) {
+        // atan(x) = pi/2 - atan(1/x)
+        let half_pi = pi_raw(w)?.checked_div(&int(2), w)?;
+        return half_pi.checked_sub(&atan_raw(&int(1).checked_div(x, w)?, w)?, w);
+    }
+    // atan(x) = 2 atan(x / (1 + sqrt(1 + x^2))), three times to get below tan(pi/32)
+    const HALVINGS: u32 = 3;
+    let mut x = x.clone();
+    for _ in 0..HALVINGS {
+        let root = sqrt_raw(&int(1).checked_add(&mul(&x, &x, w)?, w)?, w);
+        x = x.checked_div(&int(1).checked_add(&root, w)?, w)?;
+    }
+    odd_series(&x, true, w)?.checked_mul(&int(1 << HALVINGS), w)
+}
+
+/// `x` minus the nearest multiple of `2 pi`, so that `|x| <= pi`.
+fn reduce_angle(x: &Decimal, w: &Context) -> Result<Decimal, CalcError> {
+    // the multiple of pi has as many more digits as `x` has before the point
+    let extra = x.exponent().max(0) as usize + 1;
+    let wx = Context { precision: w.precision + extra, ..w.clone() };
+    let two_pi = pi_raw(&wx)?.checked_mul(&int(2), &wx)?;
+    let turns = x.checked_div(&two_pi, &Context { precision: 0, ..wx.clone() })?;
+    let reduced = x.checked_sub(&turns.checked_mul(&two_pi, &wx)?, &wx)?;
+    Ok(reduced.round(w.precision, RoundingMode::HalfEven))
+}
+
+/// `sin(x)` (`phase = 1`) or `cos(x)` (`phase = 0`) by the Taylor series, for `|x| <= pi`.
+fn sin_cos_series(x: &Decimal, phase: i128, w: &Context) -> Result<Decimal, CalcError> {
+    let x2 = -mul(x, x, w)?;
+    let mut term = if phase == 1 { x.clone() } else { int(1) };
+    let mut sum = Decimal::zero();
+    let mut k = phase;
+    while !term.is_zero() {
+        sum = sum.checked_add(&term, w)?;
+        term = mul(&term, &x2, w)?.checked_div(&int((k + 1) * (k + 2)), w)?;
+        k += 2;
+    }
+    Ok(sum)
+}
+
+pub fn sqrt(x: &Decimal, ctx: &Context) -> Result<Decimal, CalcError> {
+    if x.is_negative() {
+        return Err(CalcError::domain("Square root of a negative number has no real value"));
+    }
+    pow_fraction(x, &BigInt::one(), &BigInt::from(2), ctx)
+}
+
+pub fn ln(x: &Decimal, ctx: &Context) -> Result<Decimal, CalcError> {
+    if x.is_negative() || x.is_zero() {
+        return Err(CalcError::domain("Logarithm of a number that is not positive"));
+    }
+    finish(&ln_raw(x, &working(ctx, 0))?, ctx)
+}
+
+pub fn log10(x: &Decimal, ctx: &Context) -> Result<Decimal, CalcError> {
+    // powers of ten are exact, whatever the rounding
+    let exponent = x.exponent();
+    if *x == int(1).mul_pow10(exponent) { return Ok(int(exponent as i128)); }
+    log(&int(10), x, ctx)
+}
+
+/// The logarithm of `x` to the base `base`.
+pub fn log(base: &Decimal, x: &Decimal, ctx: &Context) -> Result<Decimal, CalcError> {
+    if base.is_negative() || base.is_zero() || *base == int(1) {
+        return Err(CalcError::domain("Logarithm to a base that is not positive, or is 1"));
+    }
+    if x.is_negative() || x.is_zero() {
+        return Err(CalcError::domain("Logarithm of a number that is not positive"));
+    }
+    let w = working(ctx, 0);
+    let ln_base = ln_raw(base, &w)?;
+    // a base close to 1 has a small logarithm, which loses digits in the division
+    let w = working(ctx, (-ln_base.exponent()).max(0) as usize + x.approx_log10().abs().log10().max(0.0) as usize);
+    finish(&ln_raw(x, &w)?.checked_div(&ln_raw(base, &w)?, &w)?, ctx)
+}
+
+pub fn exp(x: &Decimal, ctx: &Context) -> Result<Decimal, CalcError> {
+    finish(&exp_raw(x, ctx, &working(ctx, 0))?, ctx)
+}
+
+pub fn pi(ctx: &Context) -> Result<Decimal, CalcError> {
+    finish(&pi_raw(&working(ctx, 0))?, ctx)
+}
+
+pub fn sin(x: &Decimal, ctx: &Context) -> Result<Decimal, CalcError> {
+    let w = working(ctx, 0);
+    finish(&sin_cos_series(&reduce_angle(x, &w)?, 1, &w)?, ctx)
+}
+
+pub fn cos(x: &Decimal, ctx: &Context) -> Result<Decimal, CalcError> {
+    let w = working(ctx, 0);
+    finish(&sin_cos_series(&reduce_angle(x, &w)?, 0, &w)?, ctx)
+}
+
+pub fn tan(x: &Decimal, ctx: &Context) -> Result<Decimal, CalcError> {
+    let mut w = working(ctx, 0);
+    loop {
+        let r = reduce_angle(x, &w)?;
+        let cos = sin_cos_series(&r, 0, &w)?;
+        if cos.is_zero() {
+            return Err(CalcError::domain("Tangent of an odd multiple of pi/2 is undefined"));
+        }
+        // dividing by a small cosine multiplies the error, so it needs more digits
+        let lost = (-cos.exponent()).max(0) as usize;
+        if lost > w.precision - ctx.precision - GUARD_DIGITS {
+            w = working(ctx, lost);
+            continue;
+        }
+        return finish(&sin_cos_series(&r, 1, &w)?.checked_div(&cos, &w)?, ctx);
+    }
+}
+
+pub fn asin(x: &Decimal, ctx: &Context) -> Result<Decimal, CalcError> {
+    if x.abs() > int(1) { return Err(CalcError::domain("Arcsine of a number outside [-1, 1]")); }
+    let w = working(ctx, 0);
+    finish(&asin_raw(x, &w)?, ctx)
+}
+
+/// `asin(x) = 2 atan(x / (1 + sqrt(1 - x^2)))`, which stays accurate next to `|x| = 1`.
+fn asin_raw(x: &Decimal, w: &Context) -> Result<Decimal, CalcError> {
+    let root = sqrt_raw(&int(1).checked_sub(&mul(x, x, w)?, w)?, w);
+    let half = x.checked_div(&int(1).checked_add(&root, w)?, w)?;
+    atan_raw(&half, w)?.checked_mul(&int(2), w)
+}
+
+pub fn acos(x: &Decimal, ctx: &Context) -> Result<Decimal, CalcError> {
+    if x.abs() > int(1) { return Err(CalcError::domain("Arccosine of a number outside [-1, 1]")); }
+    let w = working(ctx, 0);
+    let half_pi = pi_raw(&w)?.checked_div(&int(2), &w)?;
+    finish(&half_pi.checked_sub(&asin_raw(x, &w)?, &w)?, ctx)
+}
+
+pub fn atan(x: &Decimal, ctx: &Context) -> Result<Decimal, CalcError> {
+    finish(&atan_raw(x, &working(ctx, 0))?, ctx)
+}
+
+/// The angle of the point `(x, y)` from the positive x axis, in `(-pi, pi]`, which is the argument of `x + yi`.
+pub fn atan2(y: &Decimal, x: &Decimal, ctx: &Context) -> Result<Decimal, CalcError> {
+    finish(&atan2_raw(y, x, &working(ctx, 0))?, ctx)
+}
+
+fn atan2_raw(y: &Decimal, x: &Decimal, w: &Context) -> Result<Decimal, CalcError> {
+    if x.is_zero() {
+        if y.is_zero() { return Ok(Decimal::zero()); }
+        let half_pi = pi_raw(w)?.checked_div(&int(2), w)?;
+        return Ok(if y.is_negative() { -half_pi } else { half_pi });
+    }
+    let angle = atan_raw(&y.checked_div(x, w)?, w)?;
+    if !x.is_negative() { return Ok(angle); }
+    if y.is_negative() { angle.checked_sub(&pi_raw(w)?, w) } else { angle.checked_add(&pi_raw(w)?, w) }
+}
+
+/// `sqrt(x^2 + y^2)`, which is the absolute value of `x + yi`, exact when it can be, e.g. `5` for `3` and `4`.
+pub fn hypot(x: &Decimal, y: &Decimal, ctx: &Context) -> Result<Decimal, CalcError> {
+    let w = working(ctx, 0);
+    sqrt(&x.checked_mul(x, &w)?.checked_add(&y.checked_mul(y, &w)?, &w)?, ctx)
+}
+
+/// The square root of `x + yi` with a real part that is not negative, as `(re, im)`.
+pub fn sqrt_complex(x: &Decimal, y: &Decimal, ctx: &Context) -> Result<(Decimal, Decimal), CalcError> {
+    if y.is_zero() {
+        if x.is_negative() { return Ok((Decimal::zero(), sqrt(&-x, ctx)?)); }
+        return Ok((sqrt(x, ctx)?, Decimal::zero()));
+    }
+    let w = working(ctx, 0);
+    // the larger part is sqrt((|z| + |x|) / 2), and dividing y by twice it gives the other one
+    // without subtracting numbers close to each other
+    let modulus = hypot(x, y, &w)?;
+    let large = sqrt(&modulus.checked_add(&x.abs(), &w)?.checked_div(&int(2), &w)?, &w)?;
+    let small = y.abs().checked_div(&large.checked_mul(&int(2), &w)?, &w)?;
+    let (re, im) = if x.is_negative() { (small, large) } else { (large, small) };
+    Ok((finish(&re, ctx)?, finish(&if y.is_negative() { -im } else { im }, ctx)?))
+}
+
+/// `ln(x + yi) = ln|x + yi| + i arg(x + yi)`, the principal value.
+fn ln_complex_raw(x: &Decimal, y: &Decimal, w: &Context) -> Result<(Decimal, Decimal), CalcError> {
+    if x.is_zero() && y.is_zero() {
+        return Err(CalcError::domain("Logarithm of zero is undefined"));
+    }
+    let square = x.checked_mul(x, w)?.checked_add(&y.checked_mul(y, w)?, w)?;
+    Ok((ln_raw(&square, w)?.checked_div(&int(2), w)?, atan2_raw(y, x, w)?))
+}
+
+pub fn ln_complex(x: &Decimal, y: &Decimal, ctx: &Context) -> Result<(Decimal, Decimal), CalcError> {
+    let (re, im) = ln_complex_raw(x, y, &working(ctx, 0))?;
+    Ok((finish(&re, ctx)?, finish(&im, ctx)?))
+}
+
+/// `e^(x + yi) = e^x (cos y + i sin y)`.
+fn exp_complex_raw(x: &Decimal, y: &Decimal, ctx: &Context, w: &Context) -> Result<(Decimal, Decimal), CalcError> {
+    let magnitude = exp_raw(x, ctx, w)?;
+    if y.is_zero() { return Ok((magnitude, Decimal::zero())); }
+    let angle = reduce_angle(y, w)?;
+    let cos = sin_cos_series(&angle, 0, w)?;
+    let sin = sin_cos_series(&angle, 1, w)?;
+    Ok((mul(&magnitude, &cos, w)?, mul(&magnitude, &sin, w)?))
+}
+
+pub fn exp_complex(x: &Decimal, y: &Decimal, ctx: &Context) -> Result<(Decimal, Decimal), CalcError> {
+    let (re, im) = exp_complex_raw(x, y, ctx, &working(ctx, 0))?;
+    Ok((finish(&re, ctx)?, finish(&im, ctx)?))
+}
+
+/// `(a + bi) ^ (c + di) = e^((c + di) ln(a + bi))`, the principal value.
+pub fn pow_complex(
+    (a, b): (&Decimal, &Decimal), (c, d): (&Decimal, &Decimal), ctx: &Context
+) -> Result<(Decimal, Decimal), CalcError> {
+    if a.is_zero() && b.is_zero() {
+        if c.is_negative() || c.is_zero() { return Err(CalcError::division_by_zero()); }
+        return Ok((Decimal::zero(), Decimal::zero()));
+    }
+    // estimate the size of the result first, as it decides how many digits are needed
+    let (af, bf, cf, df) = (a.to_f64(), b.to_f64(), c.to_f64(), d.to_f64());
+    let (ln_abs, arg) = (af.hypot(bf).ln(), bf.atan2(af));
+    let (re, im) = (cf * ln_abs - df * arg, cf * arg + df * ln_abs);
+    let digits = re / std::f64::consts::LN_10;
+    if digits > ctx.max_digits as f64 {
+        return Err(CalcError::Overflow {
+            message: format!("Result has more than {} digits", ctx.max_digits), span: None
+        });
+    }
+    let extra = digits.max(0.0).ceil() as usize + re.abs().max(im.abs()).log10().max(0.0).ceil() as usize + 2;
+    let w = working(ctx, extra);
+    let (ln_re, ln_im) = ln_complex_raw(a, b, &w)?;
+    let re = mul(c, &ln_re, &w)?.checked_sub(&mul(d, &ln_im, &w)?, &w)?;
+    let im = mul(c, &ln_im, &w)?.checked_add(&mul(d, &ln_re, &w)?, &w)?;
+    let (x, y) = exp_complex_raw(&re, &im, ctx, &w)?;
+    Ok((finish(&x, ctx)?, finish(&y, ctx)?))
+}
+
+#[cfg(test)]
+fn dec(s: &str) -> Decimal {
+    match s.strip_prefix('-') {
+        Some(s) => -s.parse::<Decimal>().unwrap(),
+        None => s.parse().unwrap(),
+    }
+}
+
+#[test]
+fn test_math_ln_exp() {
+    let ctx = Context::default();
+    assert_eq!(ln(&dec("2"), &ctx).unwrap().to_string(), "0.69314718055994530942");
+    assert_eq!(ln(&dec("10"), &ctx).unwrap().to_string(), "2.30258509299404568402");
+    assert_eq!(ln(&dec("1"), &ctx).unwrap().to_string(), "0.0");
+    assert_eq!(ln(&dec("0.001"), &ctx).unwrap().to_string(), "-6.90775527898213705205");
+    assert_eq!(exp(&dec("1"), &ctx).unwrap().to_string(), "2.71828182845904523536");
+    assert_eq!(exp(&dec("-1"), &ctx).unwrap().to_string(), "0.3678794411714423216");
+    assert_eq!(exp(&dec("100"), &ctx).unwrap().to_string(),
+        "26881171418161354484126255515800135873611118.77374192241519160862");
+    assert_eq!(exp(&dec("-100"), &ctx).unwrap().to_string(), "0.0");
+    assert!(matches!(ln(&dec("0"), &ctx), Err(CalcError::Domain { .. })));
+    let err = exp(&dec("100000"), &ctx).unwrap_err();
+    assert!(matches!(err, CalcError::Overflow { .. }));
+    assert_eq!(err.message(), format!("Result has more than {} digits", ctx.max_digits));
+    let err = exp_complex(&dec("100000"), &dec("1"), &ctx).unwrap_err();
+    assert_eq!(err.message(), format!("Result has more than {} digits", ctx.max_digits));
+}
+
+#[test]
+fn test_math_pow_fraction() {
+    let ctx = Context::default();
+    let pow = |base: &str, p: i128, q: i128| {
+        pow_fraction(&dec(base), &BigInt::from(p), &BigInt::from(q), &ctx).map(|r| r.to_string())
+    };
+    assert_eq!(pow("8", 1, 3).unwrap(), "2.0");
+    assert_eq!(pow("8", 4, 6).unwrap(), "4.0");
+    assert_eq!(pow("-8", 1, 3).unwrap(), "-2.0");
+    assert_eq!(pow("-8", 2, 3).unwrap(), "4.0");
+    assert_eq!(pow("8", -1, 3).unwrap(), "0.5");
+    assert_eq!(pow("2", 1, 3).unwrap(), "1.25992104989487316477");
+    assert_eq!(pow("0.001", 1, 3).unwrap(), "0.1");
+    assert_eq!(pow("2", 1, 1000).unwrap(), "1.00069338746258063254");
+    assert_eq!(pow("0", 1, 3).unwrap(), "0.0");
+    assert_eq!(pow("1.0001", 20001, 2).unwrap(), "2.71828183072405359025");
+    let big = Decimal::from_parts(BigInt::one(), 0).mul_pow10(9999);
+    let root = sqrt(&big, &ctx).unwrap().to_string();
+    assert_eq!((root.len(), &root[..12], &root[root.len() - 12..]), (5021, "316227766016", "710655998265"));
+    let big = big.checked_add(&dec("1"), &ctx).unwrap();
+    let root = pow_fraction(&big, &BigInt::from(3), &BigInt::from(4), &ctx).unwrap().to_string();
+    assert_eq!((root.len(), &root[..12], &root[root.len() - 12..]), (7521, "177827941003", "341193956016"));
+    let slow = pow_fraction(&big, &BigInt::from(100), &BigInt::from(101), &ctx);
+    assert!(matches!(slow, Err(CalcError::Overflow { .. })));
+    assert!(matches!(pow("-8", 1, 2), Err(CalcError::Domain { .. })));
+    assert!(matches!(pow("0", -1, 3), Err(CalcError::DivisionByZero { .. })));
+    assert!(matches!(pow("8", 1, 0), Err(CalcError::DivisionByZero { .. })));
+
+    let ctx = Context { precision: 5, rounding: RoundingMode::Ceiling, ..Context::default() };
+    let root = pow_fraction(&dec("2"), &BigInt::one(), &BigInt::from(2), &ctx).unwrap();
+    assert_eq!(root.to_string(), "1.41422");
+    let root = pow_fraction(&dec("-2"), &BigInt::one(), &BigInt::from(3), &ctx).unwrap();
+    assert_eq!(root.to_string(), "-1.25992");
+}
+
+#[test]
+fn test_math_functions() {
+    let ctx = Context::default();
+    type Function = fn(&Decimal, &Context) -> Result<Decimal, CalcError>;
+    let call = |f: Function, x: &str| f(&dec(x), &ctx).map(|r| r.to_string());
+    assert_eq!(call(sqrt, "2").unwrap(), "1.4142135623730950488");
+    assert_eq!(call(sqrt, "0.0004").unwrap(), "0.02");
+    assert_eq!(call(log10, "0.001").unwrap(), "-3.0");
+    assert_eq!(call(log10, "2").unwrap(), "0.30102999566398119521");
+    assert_eq!(log(&dec("1.0001"), &dec("2"), &ctx).unwrap().to_string(), "6931.81837341379535519597");
+    assert_eq!(call(exp, "2.5").unwrap(), "12.18249396070347343807");
+    assert_eq!(pi(&ctx).unwrap().to_string(), "3.14159265358979323846");
+    assert_eq!(call(sin, "1").unwrap(), "0.84147098480789650665");
+    assert_eq!(call(sin, "100").unwrap(), "-0.50636564110975879366");
+    assert_eq!(call(cos, "1000000").unwrap(), "0.93675212753314478694");
+    assert_eq!(call(tan, "1.5707963").unwrap(), "37320539.58671654132004064247");
+    assert_eq!(call(asin, "0.9999").unwrap(), "1.55665407331738374164");
+    assert_eq!(call(acos, "-1").unwrap(), "3.14159265358979323846");
+    assert_eq!(call(atan, "1000").unwrap(), "1.56979632712822975256");
+    assert_eq!(call(atan, "-0.2").unwrap(), "-0.19739555984988075837");
+    assert!(matches!(call(sqrt, "-1"), Err(CalcError::Domain { .. })));
+    assert!(matches!(call(asin, "1.5"), Err(CalcError::Domain { .. })));
+    assert!(matches!(log(&dec("1"), &dec("2"), &ctx), Err(CalcError::Domain { .. })));
+
+    let ctx = Context { precision: 60, ..Context::default() };
+    assert_eq!(sin(&dec("12345.678"), &ctx).unwrap().to_string(),
+        "-0.704081313753298916640886018839884896712938983224253779109608");
+}
@@ -1,3 +1,15 @@
-/** mod of number */
+/* mod of number */
 
-pub(crate) mod decimal;
\ No newline at end of file
+pub(crate) mod bigint;
+pub(crate) mod complex;
+pub(crate) mod context;
+pub(crate) mod decimal;
+pub(crate) mod format;
+pub(crate) mod literal;
+pub(crate) mod math;
+pub(crate) mod numeric;
+pub(crate) mod rational;
+pub(crate) mod unit;
+
+#[cfg(test)]
+mod property;
@@ -0,0 +1,699 @@
+use std::{cmp::Ordering, fmt::{Debug, Display}, ops::Neg};
+
+use crate::{
+    error::CalcError,
+    number::{
+        bigint::BigInt,
+        complex::Complex,
+        context::{Arithmetic, Context, RoundingMode},
+        decimal::{round_div, Decimal},
+        math,
+        rational::Rational
+    }
+};
+
+/// The operations the interpreter needs from a type of numbers, so that it can calculate with any of them:
+/// `Decimal`, `Number` (fractions where they can be), `f64` (fast, but binary and limited to 17 digits)
+/// or `BigInt` (integers, rounding every result by `ctx.rounding`).
+///
+/// What a type cannot compute itself, e.g. `ln`, is computed on decimals and converted.
+pub trait Numeric: Clone + Debug + Display + PartialOrd + Neg<Output = Self> {
+    /// `value` as this type, e.g. a number literal or the result of `ln`, which may lose digits.
+    fn from_decimal(value: Decimal, ctx: &Context) -> Result<Self, CalcError>;
+    fn to_decimal(&self, ctx: &Context) -> Result<Decimal, CalcError>;
+    /// The value as an integer, if it is one.
+    fn to_integer(&self) -> Option<BigInt>;
+
+    fn checked_add(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError>;
+    fn checked_sub(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError>;
+    fn checked_mul(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError>;
+    fn checked_div(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError>;
+    fn pow(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError>;
+
+    /// `self // rhs`, the quotient rounded down to an integer.
+    fn div_floor(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError> {
+        Ok(self.checked_div(rhs, ctx)?.round(0, RoundingMode::Floor))
+    }
+
+    /// `self mod rhs`, what is left after taking `self // rhs` times `rhs`, which has the sign of `rhs`,
+    /// e.g. `-7 mod 3` is `2`.
+    fn rem_floor(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError> {
+        self.checked_sub(&rhs.checked_mul(&self.div_floor(rhs, ctx)?, ctx)?, ctx)
+    }
+
+    /// `self ^ (p / q)` for an exponent written as a fraction of integers, e.g. `8 ^ (1 / 3)`,
+    /// so that it is not rounded before the power is taken.
+    fn pow_fraction(&self, p: &BigInt, q: &BigInt, ctx: &Context) -> Result<Self, CalcError> {
+        Self::from_decimal(math::pow_fraction(&self.to_decimal(ctx)?, p, q, ctx)?, ctx)
+    }
+
+    /// `value * i`, for the types that have complex numbers.
+    fn from_imaginary(_value: Decimal, _: &Context) -> Result<Self, CalcError> {
+        Err(CalcError::domain("Complex numbers need decimal or rational arithmetic"))
+    }
+
+    /// The absolute value, which is the distance from zero for a complex number.
+    fn abs(&self, ctx: &Context) -> Result<Self, CalcError>;
+    /// Round to `scale` digits after the point.
+    fn round(&self, scale: usize, mode: RoundingMode) -> Self;
+
+    fn sqrt(&self, ctx: &Context) -> Result<Self, CalcError> {
+        Self::from_decimal(math::sqrt(&self.to_decimal(ctx)?, ctx)?, ctx)
+    }
+
+    /// `self * num / den`, which converts between units, e.g. from `km` to `mi`.
+    fn scale(&self, num: &Decimal, den: &Decimal, ctx: &Context) -> Result<Self, CalcError> {
+        let (num, den) = (Self::from_decimal(num.clone(), ctx)?, Self::from_decimal(den.clone(), ctx)?);
+        self.checked_mul(&num, ctx)?.checked_div(&den, ctx)
+    }
+
+    /// The argument, the angle from the positive real axis: `0` for a positive number, `pi` for a negative one.
+    fn arg(&self, ctx: &Context) -> Result<Self, CalcError> {
+        let angle = if self.to_decimal(ctx)?.is_negative() { math::pi(ctx)? } else { Decimal::zero() };
+        Self::from_decimal(angle, ctx)
+    }
+
+    /// The complex conjugate, which is the number itself for a real one.
+    fn conj(&self) -> Self {
+        self.clone()
+    }
+
+    /// The real part.
+    fn re(&self) -> Self {
+        self.clone()
+    }
+
+    /// The imaginary part, as a real number.
+    fn im(&self, ctx: &Context) -> Result<Self, CalcError> {
+        Self::from_decimal(Decimal::zero(), ctx)
+    }
+
+    /// The built-in function `name`, e.g. `sin`, which `function` computes on decimals.
+    fn function(
+        _name: &str, function: fn(&[Decimal], &Context) -> Result<Decimal, CalcError>, args: &[Self], ctx: &Context
+    ) -> Result<Self, CalcError> {
+        through_decimals(function, args, ctx)
+    }
+}
+
+/// `function` on `args` converted to decimals, with the result converted back.
+fn through_decimals<N: Numeric>(
+    function: fn(&[Decimal], &Context) -> Result<Decimal, CalcError>, args: &[N], ctx: &Context
+) -> Result<N, CalcError> {
+    let args = args.iter().map(|arg| arg.to_decimal(ctx)).collect::<Result<Vec<_>, _>>()?;
+    N::from_decimal(function(&args, ctx)?, ctx)
+}
+
+impl Numeric for Decimal {
+    fn from_decimal(value: Decimal, _: &Context) -> Result<Self, CalcError> {
+        Ok(value)
+    }
+
+    fn to_decimal(&self, _: &Context) -> Result<Decimal, CalcError> {
+        Ok(self.clone())
+    }
+
+    fn to_integer(&self) -> Option<BigInt> {
+        Decimal::to_integer(self)
+    }
+
+    fn checked_add(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError> {
+        Decimal::checked_add(self, rhs, ctx)
+    }
+
+    fn checked_sub(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError> {
+        Decimal::checked_sub(self, rhs, ctx)
+    }
+
+    fn checked_mul(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError> {
+        Decimal::checked_mul(self, rhs, ctx)
+    }
+
+    fn checked_div(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError> {
+        Decimal::checked_div(self, rhs, ctx)
+    }
+
+    fn pow(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError> {
+        Decimal::pow(self, rhs, ctx)
+    }
+
+    /// Exact, even where `self / rhs` would round up to the next integer.
+    fn div_floor(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError> {
+        let quotient = Rational::from(self).div_floor(&Rational::from(rhs)).ok_or_else(CalcError::division_by_zero)?;
+        ctx.check(Decimal::from_parts(quotient, 0))
+    }
+
+    fn pow_fraction(&self, p: &BigInt, q: &BigInt, ctx: &Context) -> Result<Self, CalcError> {
+        math::pow_fraction(self, p, q, ctx)
+    }
+
+    fn abs(&self, _: &Context) -> Result<Self, CalcError> {
+        Ok(Decimal::abs(self))
+    }
+
+    fn round(&self, scale: usize, mode: RoundingMode) -> Self {
+        Decimal::round(self, scale, mode)
+    }
+}
+
+/// A value of rational arithmetic: an exact fraction as long as it can be one, a decimal otherwise.
+///
+/// Operations on two fractions give a fraction, except for powers that are irrational, e.g. `2 ^ (1/2)`.
+/// As soon as one operand is a decimal, the other one is turned into a decimal too.
+/// In decimal arithmetic, every value is a decimal.
+///
+/// In both, a value with an imaginary part is a complex number, which comes from `i`,
+/// or from a root or logarithm of a negative number, e.g. `sqrt(-4)`.
+/// A complex result without an imaginary part is a decimal again.
+#[derive(Debug, Clone)]
+pub enum Number {
+    Decimal(Decimal),
+    Rational(Rational),
+    Complex(Complex),
+}
+
+impl Number {
+    /// The exact fraction, which every real decimal is.
+    pub(crate) fn to_rational(&self) -> Option<Rational> {
+        match self {
+            Number::Decimal(value) => Some(Rational::from(value)),
+            Number::Rational(value) => Some(value.clone()),
+            Number::Complex(_) => None,
+        }
+    }
+
+    fn exact(&self) -> Option<&Rational> {
+        match self {
+            Number::Rational(value) => Some(value),
+            Number::Decimal(_) | Number::Complex(_) => None,
+        }
+    }
+
+    fn is_negative(&self) -> bool {
+        match self {
+            Number::Decimal(value) => value.is_negative(),
+            Number::Rational(value) => value.is_negative(),
+            Number::Complex(_) => false,
+        }
+    }
+
+    fn to_complex(&self, ctx: &Context) -> Result<Complex, CalcError> {
+        match self {
+            Number::Complex(value) => Ok(value.clone()),
+            _ => Ok(Complex::from(self.to_decimal(ctx)?)),
+        }
+    }
+
+    /// `value`, as a decimal if it has no imaginary part.
+    fn complex(value: Complex) -> Self {
+        if value.is_real() { Number::Decimal(value.re().clone()) } else { Number::Complex(value) }
+    }
+
+    /// `operation` on both operands as complex numbers, if one of them is one.
+    fn on_complex(
+        &self, rhs: &Self, ctx: &Context, operation: fn(&Complex, &Complex, &Context) -> Result<Complex, CalcError>
+    ) -> Option<Result<Self, CalcError>> {
+        if !matches!((self, rhs), (Number::Complex(_), _) | (_, Number::Complex(_))) { return None; }
+        Some(self.to_complex(ctx).and_then(|a| operation(&a, &rhs.to_complex(ctx)?, ctx)).map(Number::complex))
+    }
+}
+
+impl Numeric for Number {
+    /// A fraction in rational arithmetic, and a decimal otherwise.
+    fn from_decimal(value: Decimal, ctx: &Context) -> Result<Self, CalcError> {
+        Ok(match ctx.arithmetic {
+            Arithmetic::Decimal => Number::Decimal(value),
+            Arithmetic::Rational => Number::Rational(Rational::from(&value)),
+        })
+    }
+
+    fn to_decimal(&self, ctx: &Context) -> Result<Decimal, CalcError> {
+        match self {
+            Number::Decimal(value) => Ok(value.clone()),
+            Number::Rational(value) => value.to_decimal(ctx),
+            Number::Complex(_) => Err(CalcError::domain("Expected a real number, found a complex one")),
+        }
+    }
+
+    fn to_integer(&self) -> Option<BigInt> {
+        match self {
+            Number::Decimal(value) => value.to_integer(),
+            Number::Rational(value) => value.to_integer(),
+            Number::Complex(_) => None,
+        }
+    }
+
+    fn checked_add(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError> {
+        if let Some(result) = self.on_complex(rhs, ctx, Complex::checked_add) { return result; }
+        match (self, rhs) {
+            (Number::Rational(a), Number::Rational(b)) => a.checked_add(b, ctx).map(Number::Rational),
+            _ => self.to_decimal(ctx)?.checked_add(&rhs.to_decimal(ctx)?, ctx).map(Number::Decimal),
+        }
+    }
+
+    fn checked_sub(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError> {
+        if let Some(result) = self.on_complex(rhs, ctx, Complex::checked_sub) { return result; }
+        match (self, rhs) {
+            (Number::Rational(a), Number::Rational(b)) => a.checked_sub(b, ctx).map(Number::Rational),
+            _ => self.to_decimal(ctx)?.checked_sub(&rhs.to_decimal(ctx)?, ctx).map(Number::Decimal),
+        }
+    }
+
+    fn checked_mul(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError> {
+        if let Some(result) = self.on_complex(rhs, ctx, Complex::checked_mul) { return result; }
+        match (self, rhs) {
+            (Number::Rational(a), Number::Rational(b)) => a.checked_mul(b, ctx).map(Number::Rational),
+            _ => self.to_decimal(ctx)?.checked_mul(&rhs.to_decimal(ctx)?, ctx).map(Number::Decimal),
+        }
+    }
+
+    fn checked_div(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError> {
+        if let Some(result) = self.on_complex(rhs, ctx, Complex::checked_div) { return result; }
+        match (self, rhs) {
+            (Number::Rational(a), Number::Rational(b)) => a.checked_div(b, ctx).map(Number::Rational),
+            _ => self.to_decimal(ctx)?.checked_div(&rhs.to_decimal(ctx)?, ctx).map(Number::Decimal),
+        }
+    }
+
+    /// A fraction to an integer power is a fraction, and so is a root that comes out even,
+    /// e.g. `(4/9) ^ (3/2)`; anything else is a decimal, or complex for a negative base, e.g. `(-4) ^ 0.5`.
+    fn pow(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError> {
+        if let Some(result) = self.on_complex(rhs, ctx, Complex::pow) { return result; }
+        if let (Some(base), Some(exp)) = (self.exact(), rhs.to_integer()) {
+            return base.pow(&exp, ctx).map(Number::Rational);
+        }
+        match rhs {
+            // the exponent as the fraction it is, not a rounded decimal
+            Number::Rational(exp) => self.pow_fraction(exp.numerator(), exp.denominator(), ctx),
+            Number::Decimal(exp) => match self.to_decimal(ctx)?.pow(exp, ctx) {
+                Err(CalcError::Domain { .. }) if self.is_negative() => {
+                    self.to_complex(ctx)?.pow(&exp.clone().into(), ctx).map(Number::complex)
+                },
+                result => result.map(Number::Decimal),
+            },
+            Number::Complex(_) => unreachable!("complex exponents are handled above"),
+        }
+    }
+
+    /// Exact on real numbers, a fraction if both are fractions.
+    fn div_floor(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError> {
+        let (Some(a), Some(b)) = (self.to_rational(), rhs.to_rational()) else {
+            return Err(CalcError::domain("Complex numbers have no integer division or remainder"));
+        };
+        let quotient = a.div_floor(&b).ok_or_else(CalcError::division_by_zero)?;
+        let quotient = ctx.check(Decimal::from_parts(quotient, 0))?;
+        Ok(match (self, rhs) {
+            (Number::Rational(_), Number::Rational(_)) => Number::Rational(Rational::from(&quotient)),
+            _ => Number::Decimal(quotient),
+        })
+    }
+
+    fn pow_fraction(&self, p: &BigInt, q: &BigInt, ctx: &Context) -> Result<Self, CalcError> {
+        if let Some(base) = self.exact() {
+            let root = q.to_u64().and_then(|q| u32::try_from(q).ok()).and_then(|q| base.root(q));
+            if let Some(root) = root {
+                return root.pow(p, ctx).map(Number::Rational);
+            }
+        }
+        match math::pow_fraction(&self.to_decimal(ctx)?, p, q, ctx) {
+            // an even root of a negative number
+            Err(CalcError::Domain { .. }) if self.is_negative() => {
+                let exp = Decimal::from_parts(p.clone(), 0).checked_div(&Decimal::from_parts(q.clone(), 0), ctx)?;
+                self.to_complex(ctx)?.pow(&exp.into(), ctx).map(Number::complex)
+            },
+            result => result.map(Number::Decimal),
+        }
+    }
+
+    fn from_imaginary(value: Decimal, _: &Context) -> Result<Self, CalcError> {
+        Ok(Number::complex(Complex::new(Decimal::zero(), value)))
+    }
+
+    fn abs(&self, ctx: &Context) -> Result<Self, CalcError> {
+        Ok(match self {
+            Number::Decimal(value) => Number::Decimal(value.abs()),
+            Number::Rational(value) => Number::Rational(value.abs()),
+            Number::Complex(value) => Number::Decimal(value.abs(ctx)?),
+        })
+    }
+
+    fn round(&self, scale: usize, mode: RoundingMode) -> Self {
+        match self {
+            Number::Decimal(value) => Number::Decimal(value.round(scale, mode)),
+            Number::Rational(value) => Number::Rational(value.round(scale, mode)),
+            Number::Complex(value) => Number::complex(value.round(scale, mode)),
+        }
+    }
+
+    /// The exact square root of a fraction if there is one, e.g. `sqrt(4/9)`, a decimal otherwise,
+    /// and a complex number for a negative one.
+    fn sqrt(&self, ctx: &Context) -> Result<Self, CalcError> {
+        if let Some(root) = self.exact().and_then(|value| value.root(2)) {
+            return Ok(Number::Rational(root));
+        }
+        if self.is_negative() || matches!(self, Number::Complex(_)) {
+            return self.to_complex(ctx)?.sqrt(ctx).map(Number::complex);
+        }
+        math::sqrt(&self.to_decimal(ctx)?, ctx).map(Number::Decimal)
+    }
+
+    fn arg(&self, ctx: &Context) -> Result<Self, CalcError> {
+        self.to_complex(ctx)?.arg(ctx).map(Number::Decimal)
+    }
+
+    fn conj(&self) -> Self {
+        match self {
+            Number::Complex(value) => Number::Complex(value.conj()),
+            _ => self.clone(),
+        }
+    }
+
+    fn re(&self) -> Self {
+        match self {
+            Number::Complex(value) => Number::Decimal(value.re().clone()),
+            _ => self.clone(),
+        }
+    }
+
+    fn im(&self, ctx: &Context) -> Result<Self, CalcError> {
+        match self {
+            Number::Complex(value) => Ok(Number::Decimal(value.im().clone())),
+            _ => Self::from_decimal(Decimal::zero(), ctx),
+        }
+    }
+
+    /// Irrational in general, so always a decimal, or a complex number for a logarithm of a negative
+    /// number, e.g. `ln(-1)`, and for the functions that have a complex version, e.g. `exp(i)`.
+    fn function(
+        name: &str, function: fn(&[Decimal], &Context) -> Result<Decimal, CalcError>, args: &[Self], ctx: &Context
+    ) -> Result<Self, CalcError> {
+        let complex = args.iter().any(|arg| matches!(arg, Number::Complex(_)));
+        let error = if complex {
+            CalcError::domain(format!("'{}' of a complex number is not supported", name))
+        } else {
+            let decimals = args.iter().map(|arg| arg.to_decimal(ctx)).collect::<Result<Vec<_>, _>>()?;
+            match function(&decimals, ctx) {
+                Err(error @ CalcError::Domain { .. }) if args.iter().any(Number::is_negative) => error,
+                result => return result.map(Number::Decimal),
+            }
+        };
+        let args = args.iter().map(|arg| arg.to_complex(ctx)).collect::<Result<Vec<_>, _>>()?;
+        Complex::function(name, &args, ctx).unwrap_or(Err(error)).map(Number::complex)
+    }
+}
+
+impl From<Decimal> for Number {
+    fn from(value: Decimal) -> Self {
+        Number::Decimal(value)
+    }
+}
+
+impl Neg for Number {
+    type Output = Self;
+    fn neg(self) -> Self::Output {
+        match self {
+            Number::Decimal(value) => Number::Decimal(-value),
+            Number::Rational(value) => Number::Rational(-value),
+            Number::Complex(value) => Number::Complex(-value),
+        }
+    }
+}
+
+impl PartialEq for Number {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Number::Complex(a), Number::Complex(b)) => a == b,
+            _ => self.partial_cmp(other) == Some(Ordering::Equal),
+        }
+    }
+}
+
+impl PartialOrd for Number {
+    /// Compared exactly, a decimal as the fraction it is; a complex number has no order.
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Number::Decimal(a), Number::Decimal(b)) => Some(a.cmp(b)),
+            _ => Some(self.to_rational()?.cmp(&other.to_rational()?)),
+        }
+    }
+}
+
+impl Display for Number {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Number::Decimal(value) => write!(f, "{}", value),
+            Number::Rational(value) => write!(f, "{}", value),
+            Number::Complex(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// `value`, if it is a finite number: `NaN` comes from a domain error, an infinity from an overflow.
+fn finite(value: f64, what: &str) -> Result<f64, CalcError> {
+    if value.is_nan() { return Err(CalcError::domain(format!("{} is not a real number", what))); }
+    if value.is_infinite() { return Err(CalcError::overflow()); }
+    Ok(value)
+}
+
+impl Numeric for f64 {
+    fn from_decimal(value: Decimal, _: &Context) -> Result<Self, CalcError> {
+        finite(value.to_f64(), "Number")
+    }
+
+    fn to_decimal(&self, _: &Context) -> Result<Decimal, CalcError> {
+        // `Display` of a finite `f64` writes all its digits, without an exponent
+        let value: Decimal = finite(*self, "Number")?.abs().to_string().parse()?;
+        Ok(if self.is_sign_negative() { -value } else { value })
+    }
+
+    fn to_integer(&self) -> Option<BigInt> {
+        if !self.is_finite() || self.fract() != 0.0 { return None; }
+        format!("{:.0}", self).parse().ok()
+    }
+
+    fn checked_add(&self, rhs: &Self, _: &Context) -> Result<Self, CalcError> {
+        finite(self + rhs, "Result")
+    }
+
+    fn checked_sub(&self, rhs: &Self, _: &Context) -> Result<Self, CalcError> {
+        finite(self - rhs, "Result")
+    }
+
+    fn checked_mul(&self, rhs: &Self, _: &Context) -> Result<Self, CalcError> {
+        finite(self * rhs, "Result")
+    }
+
+    fn checked_div(&self, rhs: &Self, _: &Context) -> Result<Self, CalcError> {
+        if *rhs == 0.0 { return Err(CalcError::division_by_zero()); }
+        finite(self / rhs, "Result")
+    }
+
+    fn pow(&self, rhs: &Self, _: &Context) -> Result<Self, CalcError> {
+        if *self == 0.0 && *rhs < 0.0 { return Err(CalcError::division_by_zero()); }
+        let result = if rhs.fract() == 0.0 && f64::abs(*rhs) <= i32::MAX as f64 { self.powi(*rhs as i32) }
+            else { self.powf(*rhs) };
+        finite(result, "Power of a negative number to a fraction")
+    }
+
+    /// An odd root of a negative number is real, e.g. `(-8) ^ (1/3)` is `-2`.
+    fn pow_fraction(&self, p: &BigInt, q: &BigInt, ctx: &Context) -> Result<Self, CalcError> {
+        let (p, q) = (p.to_i64().unwrap_or(i64::MAX) as f64, q.to_i64().unwrap_or(i64::MAX) as f64);
+        if *self >= 0.0 || q % 2.0 == 0.0 {
+            return self.pow(&(p / q), ctx);
+        }
+        let magnitude = (-self).pow(&(p / q), ctx)?;
+        Ok(if p % 2.0 == 0.0 { magnitude } else { -magnitude })
+    }
+
+    fn abs(&self, _: &Context) -> Result<Self, CalcError> {
+        Ok(f64::abs(*self))
+    }
+
+    fn round(&self, scale: usize, mode: RoundingMode) -> Self {
+        if scale > 0 {
+            let value = Numeric::to_decimal(self, &Context::default());
+            return value.map_or(*self, |value| value.round(scale, mode).to_f64());
+        }
+        match mode {
+            RoundingMode::HalfEven => self.round_ties_even(),
+            RoundingMode::HalfUp => f64::round(*self),
+            RoundingMode::TowardZero => self.trunc(),
+            RoundingMode::Floor => self.floor(),
+            RoundingMode::Ceiling => self.ceil(),
+        }
+    }
+
+    fn sqrt(&self, _: &Context) -> Result<Self, CalcError> {
+        if *self < 0.0 { return Err(CalcError::domain("Square root of a negative number has no real value")); }
+        Ok(f64::sqrt(*self))
+    }
+
+    /// The functions of `f64` itself, which are much faster than the decimal ones.
+    fn function(
+        name: &str, function: fn(&[Decimal], &Context) -> Result<Decimal, CalcError>, args: &[Self], ctx: &Context
+    ) -> Result<Self, CalcError> {
+        let x = args.first().copied().unwrap_or_default();
+        let logarithm = |base: f64, x: f64| {
+            if x <= 0.0 { return Err(CalcError::domain("Logarithm of a number that is not positive")); }
+            if base <= 0.0 || base == 1.0 {
+                return Err(CalcError::domain("Logarithm to a base that is not positive, or is 1"));
+            }
+            Ok(x.ln() / base.ln())
+        };
+        let result = match name {
+            "pi" | "π" => std::f64::consts::PI,
+            "e" => std::f64::consts::E,
+            "ln" => logarithm(std::f64::consts::E, x)?,
+            "log10" => logarithm(10.0, x)?,
+            "log" => logarithm(x, args[1])?,
+            "exp" => x.exp(),
+            "sin" => x.sin(),
+            "cos" => x.cos(),
+            "tan" => x.tan(),
+            "asin" | "acos" if x.abs() > 1.0 => {
+                return Err(CalcError::domain(format!("'{}' of a number outside [-1, 1]", name)));
+            },
+            "asin" => x.asin(),
+            "acos" => x.acos(),
+            "atan" => x.atan(),
+            _ => return through_decimals(function, args, ctx),
+        };
+        finite(result, "Result")
+    }
+}
+
+impl Numeric for BigInt {
+    /// Rounded to an integer by `ctx.rounding`.
+    fn from_decimal(value: Decimal, ctx: &Context) -> Result<Self, CalcError> {
+        Ok(value.round(0, ctx.rounding).to_integer().unwrap_or_default())
+    }
+
+    fn to_decimal(&self, _: &Context) -> Result<Decimal, CalcError> {
+        Ok(Decimal::from_parts(self.clone(), 0))
+    }
+
+    fn to_integer(&self) -> Option<BigInt> {
+        Some(self.clone())
+    }
+
+    fn checked_add(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError> {
+        integer(self + rhs, ctx)
+    }
+
+    fn checked_sub(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError> {
+        integer(self - rhs, ctx)
+    }
+
+    fn checked_mul(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError> {
+        integer(self * rhs, ctx)
+    }
+
+    /// Rounded to an integer by `ctx.rounding`, e.g. `7 / 2` is `4` with `half-even`.
+    fn checked_div(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError> {
+        if rhs.is_zero() { return Err(CalcError::division_by_zero()); }
+        Ok(round_div(self, rhs, ctx.rounding))
+    }
+
+    fn div_floor(&self, rhs: &Self, _: &Context) -> Result<Self, CalcError> {
+        if rhs.is_zero() { return Err(CalcError::division_by_zero()); }
+        Ok(round_div(self, rhs, RoundingMode::Floor))
+    }
+
+    fn pow(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError> {
+        Self::from_decimal(self.to_decimal(ctx)?.pow(&rhs.to_decimal(ctx)?, ctx)?, ctx)
+    }
+
+    fn abs(&self, _: &Context) -> Result<Self, CalcError> {
+        Ok(BigInt::abs(self))
+    }
+
+    fn round(&self, _: usize, _: RoundingMode) -> Self {
+        self.clone()
+    }
+
+    /// On decimals, so that only the result is rounded, not `num` and `den`.
+    fn scale(&self, num: &Decimal, den: &Decimal, ctx: &Context) -> Result<Self, CalcError> {
+        Self::from_decimal(self.to_decimal(ctx)?.checked_mul(num, ctx)?.checked_div(den, ctx)?, ctx)
+    }
+}
+
+/// Fail when `value` has more digits than the limit allows.
+fn integer(value: BigInt, ctx: &Context) -> Result<BigInt, CalcError> {
+    ctx.check(Decimal::from_parts(value.clone(), 0))?;
+    Ok(value)
+}
+
+#[test]
+fn test_number() {
+    let ctx = Context { arithmetic: Arithmetic::Rational, ..Context::default() };
+    let num = |s: &str| match s.strip_prefix('-') {
+        Some(s) => -Number::from_decimal(s.parse().unwrap(), &ctx).unwrap(),
+        None => Number::from_decimal(s.parse().unwrap(), &ctx).unwrap(),
+    };
+    let third = num("1").checked_div(&num("3"), &ctx).unwrap();
+    let sixth = num("1").checked_div(&num("6"), &ctx).unwrap();
+    assert_eq!(third.checked_add(&sixth, &ctx).unwrap().to_string(), "1/2");
+    assert_eq!(num("0.1").checked_mul(&num("3"), &ctx).unwrap().to_string(), "3/10");
+    assert_eq!(third.pow(&num("-2"), &ctx).unwrap().to_string(), "9");
+    let half = num("0.5");
+    assert_eq!(num("4").checked_div(&num("9"), &ctx).unwrap().pow(&half, &ctx).unwrap().to_string(), "2/3");
+    assert_eq!(num("2").pow(&half, &ctx).unwrap().to_string(), "1.4142135623730950488");
+    assert_eq!(num("-8").pow(&third, &ctx).unwrap().to_string(), "-2");
+    assert_eq!(num("-4").pow(&half, &ctx).unwrap().to_string(), "2.0i");
+    // a decimal turns the other operand into a decimal
+    let root = num("2").sqrt(&ctx).unwrap();
+    assert_eq!(root.checked_mul(&third, &ctx).unwrap().to_string(), "0.471404520791031682928619288125423016504");
+    assert_eq!(num("0.25").sqrt(&ctx).unwrap().to_string(), "1/2");
+    assert!(third < Number::Decimal("0.34".parse().unwrap()));
+    assert_eq!(Number::Decimal("0.5".parse().unwrap()), half);
+
+    // a complex number turns into a decimal without its imaginary part
+    let i = Number::from_imaginary(Decimal::one(), &ctx).unwrap();
+    assert_eq!(i.checked_mul(&i, &ctx).unwrap().to_string(), "-1.0");
+    let z = num("3").checked_add(&i.checked_mul(&num("4"), &ctx).unwrap(), &ctx).unwrap();
+    assert_eq!(z.to_string(), "3.0 + 4.0i");
+    assert_eq!(z.abs(&ctx).unwrap().to_string(), "5.0");
+    assert_eq!(z.conj().im(&ctx).unwrap().to_string(), "-4.0");
+    assert_eq!(num("-9").sqrt(&ctx).unwrap().to_string(), "3.0i");
+    assert_eq!(num("-2").arg(&ctx).unwrap().to_string(), "3.14159265358979323846");
+    assert!(z.partial_cmp(&num("5")).is_none());
+    assert_ne!(z, z.conj());
+}
+
+#[test]
+fn test_numeric_f64() {
+    let ctx = Context::default();
+    let num = |s: &str| f64::from_decimal(s.parse().unwrap(), &ctx).unwrap();
+    assert_eq!(num("0.1").checked_add(&num("0.2"), &ctx).unwrap(), 0.30000000000000004);
+    assert_eq!(num("1").checked_div(&num("4"), &ctx).unwrap(), 0.25);
+    assert!(matches!(num("1").checked_div(&num("0"), &ctx), Err(CalcError::DivisionByZero { .. })));
+    assert!(matches!(num("10").pow(&num("400"), &ctx), Err(CalcError::Overflow { .. })));
+    assert!(matches!((-num("4")).pow(&num("0.5"), &ctx), Err(CalcError::Domain { .. })));
+    assert!(((-num("8")).pow_fraction(&BigInt::from(2), &BigInt::from(3), &ctx).unwrap() - 4.0).abs() < 1e-12);
+    assert_eq!(Numeric::round(&num("2.5"), 0, RoundingMode::HalfEven), 2.0);
+    assert_eq!(Numeric::round(&num("1.25"), 1, RoundingMode::HalfUp), 1.3);
+    assert_eq!((-num("1.5")).to_decimal(&ctx).unwrap().to_string(), "-1.5");
+    assert_eq!(num("3").to_integer(), Some(BigInt::from(3)));
+    assert_eq!(num("3.5").to_integer(), None);
+    let unused = |_: &[Decimal], _: &Context| unreachable!();
+    assert!(matches!(f64::function("ln", unused, &[0.0], &ctx), Err(CalcError::Domain { .. })));
+    assert!((f64::function("log", unused, &[2.0, 1024.0], &ctx).unwrap() - 10.0).abs() < 1e-12);
+    // functions `f64` has not are computed on decimals
+    let cube = |x: &[Decimal], ctx: &Context| x[0].pow(&"3".parse().unwrap(), ctx);
+    assert_eq!(f64::function("cube", cube, &[1.5], &ctx).unwrap(), 3.375);
+}
+
+#[test]
+fn test_numeric_bigint() {
+    let ctx = Context::default();
+    let int = |n: i128| BigInt::from(n);
+    assert_eq!(int(7).checked_div(&int(2), &ctx).unwrap(), int(4));
+    let floor = Context { rounding: RoundingMode::Floor, ..ctx.clone() };
+    assert_eq!(int(-7).checked_div(&int(2), &floor).unwrap(), int(-4));
+    assert_eq!(Numeric::pow(&int(2), &int(100), &ctx).unwrap().to_string(), "1267650600228229401496703205376");
+    assert_eq!(Numeric::pow(&int(2), &int(-1), &ctx).unwrap(), int(0));
+    assert_eq!(int(10).sqrt(&ctx).unwrap(), int(3));
+    assert_eq!(BigInt::from_decimal("2.5".parse().unwrap(), &ctx).unwrap(), int(2));
+    let small = Context { max_digits: 3, ..ctx.clone() };
+    assert!(matches!(int(999).checked_add(&int(1), &small), Err(CalcError::Overflow { .. })));
+}
@@ -0,0 +1,203 @@
+/* Property tests of `Decimal`, checked against a plain fraction implementation.
+ *
+ * The number of cases per property is 200000, or `CALC_PROPERTY_CASES` if set.
+ */
+
+use std::cmp::Ordering;
+
+use crate::number::{
+    bigint::BigInt,
+    context::{Context, RoundingMode},
+    decimal::{simplify, Decimal}
+};
+
+/// xorshift64*, good enough to pick operands.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545F4914F6CDD1D)
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    /// A decimal with up to `max_digits` digits, of which up to `max_point` after the point.
+    fn decimal(&mut self, max_digits: u64, max_point: u64) -> Decimal {
+        let len = 1 + self.below(max_digits);
+        let digits: String = (0..len).map(|_| char::from(b'0' + self.below(10) as u8)).collect();
+        let point = self.below(max_point + 1).min(len) as usize;
+        let text = format!("{}.{}", &digits[..digits.len() - point], &digits[digits.len() - point..]);
+        let value: Decimal = text.parse().unwrap();
+        if self.below(2) == 0 { -value } else { value }
+    }
+}
+
+fn cases() -> usize {
+    std::env::var("CALC_PROPERTY_CASES").ok().and_then(|n| n.parse().ok()).unwrap_or(200_000)
+}
+
+/// The reference: `num / den` with `den > 0`, not necessarily reduced.
+#[derive(Debug, Clone)]
+struct Ratio {
+    num: BigInt,
+    den: BigInt
+}
+
+impl Ratio {
+    /// Read the printed form of a decimal, so that the reference does not
+    /// share any code with `Decimal` but `Display`.
+    fn of(value: &Decimal) -> Self {
+        let text = value.to_string();
+        let (int_part, frac_part) = text.split_once('.').unwrap();
+        let num = (int_part.to_string() + frac_part).parse().unwrap();
+        Ratio { num, den: BigInt::one().mul_pow10(frac_part.len()) }
+    }
+
+    fn add(&self, rhs: &Self) -> Self {
+        Ratio { num: &(&self.num * &rhs.den) + &(&rhs.num * &self.den), den: &self.den * &rhs.den }
+    }
+
+    fn sub(&self, rhs: &Self) -> Self {
+        self.add(&Ratio { num: -&rhs.num, den: rhs.den.clone() })
+    }
+
+    fn mul(&self, rhs: &Self) -> Self {
+        Ratio { num: &self.num * &rhs.num, den: &self.den * &rhs.den }
+    }
+
+    fn div(&self, rhs: &Self) -> Self {
+        let (num, den) = (&self.num * &rhs.den, &self.den * &rhs.num);
+        if den.is_negative() { Ratio { num: -num, den: -den } } else { Ratio { num, den } }
+    }
+
+    fn scale(&self, digits: usize) -> Self {
+        Ratio { num: self.num.mul_pow10(digits), den: self.den.clone() }
+    }
+
+    fn int(n: &BigInt) -> Self {
+        Ratio { num: n.clone(), den: BigInt::one() }
+    }
+
+    fn half() -> Self {
+        Ratio { num: BigInt::one(), den: BigInt::from(2) }
+    }
+
+    fn cmp(&self, rhs: &Self) -> Ordering {
+        (&self.num * &rhs.den).cmp(&(&rhs.num * &self.den))
+    }
+
+    fn eq(&self, rhs: &Self) -> bool {
+        self.cmp(rhs) == Ordering::Equal
+    }
+}
+
+#[test]
+fn test_property_add_sub() {
+    let ctx = Context::default();
+    let mut rng = Rng(0x9E3779B97F4A7C15);
+    for _ in 0..cases() {
+        let (a, b) = (rng.decimal(30, 15), rng.decimal(30, 15));
+        let sum = a.checked_add(&b, &ctx).unwrap();
+        assert!(Ratio::of(&sum).eq(&Ratio::of(&a).add(&Ratio::of(&b))), "{} + {} = {}", a, b, sum);
+        let diff = a.checked_sub(&b, &ctx).unwrap();
+        assert!(Ratio::of(&diff).eq(&Ratio::of(&a).sub(&Ratio::of(&b))), "{} - {} = {}", a, b, diff);
+    }
+}
+
+#[test]
+fn test_property_mul() {
+    let ctx = Context::default();
+    let mut rng = Rng(0xD1B54A32D192ED03);
+    for _ in 0..cases() {
+        let (a, b) = (rng.decimal(30, 15), rng.decimal(30, 15));
+        let product = a.checked_mul(&b, &ctx).unwrap();
+        assert!(Ratio::of(&product).eq(&Ratio::of(&a).mul(&Ratio::of(&b))), "{} * {} = {}", a, b, product);
+    }
+}
+
+#[test]
+fn test_property_div() {
+    let modes = [
+        RoundingMode::HalfEven, RoundingMode::HalfUp, RoundingMode::TowardZero,
+        RoundingMode::Floor, RoundingMode::Ceiling,
+    ];
+    let mut rng = Rng(0x8CB92BA72F3D8DD7);
+    for _ in 0..cases() {
+        let (a, b) = (rng.decimal(25, 12), rng.decimal(12, 6));
+        if Ratio::of(&b).num.is_zero() { continue; }
+        let precision = rng.below(16) as usize;
+        let rounding = modes[rng.below(5) as usize];
+        let ctx = Context { precision, rounding, ..Context::default() };
+        let quotient = a.checked_div(&b, &ctx).unwrap();
+
+        // `quotient * 10^precision` must be an integer `n` next to the exact `x`
+        let n = Ratio::of(&quotient).scale(precision);
+        let (n_int, n_rem) = n.num.div_rem(&n.den).unwrap();
+        assert!(n_rem.is_zero(), "{} / {} = {} has too many digits", a, b, quotient);
+        let x = Ratio::of(&a).div(&Ratio::of(&b)).scale(precision);
+        let distance = x.sub(&n);
+        let one = Ratio::int(&BigInt::one());
+        let zero = Ratio::int(&BigInt::zero());
+        let ok = match rounding {
+            RoundingMode::Floor => distance.cmp(&zero).is_ge() && distance.cmp(&one).is_lt(),
+            RoundingMode::Ceiling => distance.cmp(&zero).is_le() && distance.cmp(&zero.sub(&one)).is_gt(),
+            RoundingMode::TowardZero => {
+                let toward = if x.num.is_negative() { zero.sub(&distance) } else { distance.clone() };
+                toward.cmp(&zero).is_ge() && toward.cmp(&one).is_lt()
+            },
+            RoundingMode::HalfUp | RoundingMode::HalfEven => {
+                let abs = if distance.num.is_negative() { zero.sub(&distance) } else { distance.clone() };
+                match abs.cmp(&Ratio::half()) {
+                    Ordering::Less => true,
+                    Ordering::Greater => false,
+                    // a tie goes away from zero, or to the even neighbour
+                    Ordering::Equal => if rounding == RoundingMode::HalfUp {
+                        distance.num.is_negative() != x.num.is_negative()
+                    }
+                    else {
+                        n_int.is_even()
+                    },
+                }
+            },
+        };
+        assert!(ok, "{} / {} = {} with precision {} and {}", a, b, quotient, precision, rounding);
+    }
+}
+
+#[test]
+fn test_property_pow() {
+    let ctx = Context::default();
+    let mut rng = Rng(0xA0761D6478BD642F);
+    for _ in 0..cases() / 10 {
+        let base = rng.decimal(8, 4);
+        let exp = rng.below(13);
+        let result = base.pow(&exp.to_string().parse().unwrap(), &ctx).unwrap();
+        let mut expected = Ratio::int(&BigInt::one());
+        for _ in 0..exp {
+            expected = expected.mul(&Ratio::of(&base));
+        }
+        assert!(Ratio::of(&result).eq(&expected), "{} ^ {} = {}", base, exp, result);
+    }
+}
+
+#[test]
+fn test_property_simplify() {
+    let mut rng = Rng(0xE7037ED1A0B428DB);
+    for _ in 0..cases() {
+        let value = rng.decimal(20, 20);
+        let simplified = simplify(&value);
+        assert!(Ratio::of(&simplified).eq(&Ratio::of(&value)), "{} => {}", value, simplified);
+        // nothing left to remove: no trailing zero after the point
+        let text = simplified.to_string();
+        assert!(text.ends_with(".0") || !text.ends_with('0'), "{} => {}", value, simplified);
+        assert_eq!(simplified, value);
+        let reparsed: Decimal = text.trim_start_matches('-').parse().unwrap();
+        let reparsed = if text.starts_with('-') { -reparsed } else { reparsed };
+        assert_eq!(reparsed, value);
+    }
+}
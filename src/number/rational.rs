@@ -0,0 +1,218 @@
+use std::{cmp::Ordering, fmt::Display, ops::Neg};
+
+use crate::{
+    error::CalcError,
+    number::{bigint::BigInt, context::{Context, RoundingMode}, decimal::{round_div, Decimal}}
+};
+
+/// An exact fraction `num / den`, always reduced and with `den > 0`,
+/// so that equal values have equal parts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rational {
+    num: BigInt,
+    den: BigInt
+}
+
+impl Rational {
+    /// `num / den` reduced, or `None` if `den` is zero.
+    pub fn new(num: BigInt, den: BigInt) -> Option<Self> {
+        if den.is_zero() { return None; }
+        let gcd = num.gcd(&den);
+        let (mut num, _) = num.div_rem(&gcd).unwrap_or_default();
+        let (mut den, _) = den.div_rem(&gcd).unwrap_or_default();
+        if den.is_negative() {
+            num = -num;
+            den = -den;
+        }
+        Some(Self { num, den })
+    }
+
+    pub fn from_integer(n: BigInt) -> Self {
+        Self { num: n, den: BigInt::one() }
+    }
+
+    pub fn zero() -> Self {
+        Self::from_integer(BigInt::zero())
+    }
+
+    pub fn numerator(&self) -> &BigInt {
+        &self.num
+    }
+
+    pub fn denominator(&self) -> &BigInt {
+        &self.den
+    }
+
+    pub(crate) fn is_zero(&self) -> bool {
+        self.num.is_zero()
+    }
+
+    pub(crate) fn is_negative(&self) -> bool {
+        self.num.is_negative()
+    }
+
+    pub(crate) fn abs(&self) -> Self {
+        Self { num: self.num.abs(), den: self.den.clone() }
+    }
+
+    /// The value as an integer, if it is one.
+    pub(crate) fn to_integer(&self) -> Option<BigInt> {
+        if self.den == BigInt::one() { Some(self.num.clone()) } else { None }
+    }
+
+    /// The value divided out to `ctx.precision` digits after the point.
+    pub fn to_decimal(&self, ctx: &Context) -> Result<Decimal, CalcError> {
+        Decimal::from_parts(self.num.clone(), 0).checked_div(&Decimal::from_parts(self.den.clone(), 0), ctx)
+    }
+
+    /// Fail when a part of `value` has more digits than the limit allows.
+    fn check(value: Self, ctx: &Context) -> Result<Self, CalcError> {
+        if value.num.digits() > ctx.max_digits || value.den.digits() > ctx.max_digits {
+            return Err(CalcError::Overflow {
+                message: format!("Fraction has more than {} digits", ctx.max_digits),
+                span: None
+            });
+        }
+        Ok(value)
+    }
+}
+
+impl From<&Decimal> for Rational {
+    /// Every decimal is a fraction, exactly.
+    fn from(value: &Decimal) -> Self {
+        let (num, den) = value.to_fraction();
+        Self { num, den }
+    }
+}
+
+impl Rational {  // arithmetic, reporting numbers above the limits instead of panicking
+    pub fn checked_add(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError> {
+        let num = &(&self.num * &rhs.den) + &(&rhs.num * &self.den);
+        Self::check(Self::new(num, &self.den * &rhs.den).unwrap_or_else(Self::zero), ctx)
+    }
+
+    pub fn checked_sub(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError> {
+        self.checked_add(&-rhs, ctx)
+    }
+
+    pub fn checked_mul(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError> {
+        Self::check(Self::new(&self.num * &rhs.num, &self.den * &rhs.den).unwrap_or_else(Self::zero), ctx)
+    }
+
+    /// Division, which is exact.
+    pub fn checked_div(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError> {
+        let quotient = Self::new(&self.num * &rhs.den, &self.den * &rhs.num).ok_or_else(CalcError::division_by_zero)?;
+        Self::check(quotient, ctx)
+    }
+
+    /// `self ^ exp` for an integer `exp`, which is exact.
+    pub fn pow(&self, exp: &BigInt, ctx: &Context) -> Result<Self, CalcError> {
+        if exp.is_negative() {
+            if self.is_zero() { return Err(CalcError::division_by_zero()); }
+            let reciprocal = Self::new(self.den.clone(), self.num.clone()).unwrap_or_else(Self::zero);
+            return reciprocal.pow(&-exp, ctx);
+        }
+        let too_large = || CalcError::Overflow {
+            message: format!("Result has more than {} digits", ctx.max_digits),
+            span: None
+        };
+        let exp = exp.to_u64().and_then(|e| u32::try_from(e).ok()).ok_or_else(too_large)?;
+        // estimate first, so that we never build a number far above the limit
+        let digits = exp as f64 * self.num.approx_log10().max(self.den.approx_log10());
+        if digits > ctx.max_digits as f64 + 1.0 {
+            return Err(too_large());
+        }
+        Self::check(Self { num: self.num.pow(exp), den: self.den.pow(exp) }, ctx)
+    }
+
+    /// `self // rhs`, the quotient rounded down, or `None` if `rhs` is zero.
+    pub(crate) fn div_floor(&self, rhs: &Self) -> Option<BigInt> {
+        if rhs.is_zero() { return None; }
+        Some(round_div(&(&self.num * &rhs.den), &(&self.den * &rhs.num), RoundingMode::Floor))
+    }
+
+    /// The exact `k`-th root, if both parts are `k`-th powers.
+    pub(crate) fn root(&self, k: u32) -> Option<Self> {
+        if k == 0 || (self.is_negative() && k.is_multiple_of(2)) { return None; }
+        let exact = |n: &BigInt| Some(n.nth_root(k)).filter(|root| root.pow(k) == *n);
+        let num = exact(&self.num.abs())?;
+        let den = exact(&self.den)?;
+        Some(Self { num: if self.is_negative() { -num } else { num }, den })
+    }
+
+    /// Round to `scale` digits after the point, which keeps it a fraction, e.g. `1/8` to `1/10` with scale 1.
+    pub fn round(&self, scale: usize, mode: RoundingMode) -> Self {
+        let power = BigInt::one().mul_pow10(scale);
+        let num = round_div(&(&self.num * &power), &self.den, mode);
+        Self::new(num, power).unwrap_or_else(Self::zero)
+    }
+}
+
+impl Neg for Rational {
+    type Output = Self;
+    fn neg(self) -> Self::Output {
+        Self { num: -self.num, den: self.den }
+    }
+}
+
+impl Neg for &Rational {
+    type Output = Rational;
+    fn neg(self) -> Self::Output {
+        -self.clone()
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.num * &other.den).cmp(&(&other.num * &self.den))
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for Rational {
+    /// `1/2`, or `3` for an integer.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.den == BigInt::one() { write!(f, "{}", self.num) }
+        else { write!(f, "{}/{}", self.num, self.den) }
+    }
+}
+
+#[cfg(test)]
+fn ratio(num: i128, den: i128) -> Rational {
+    Rational::new(BigInt::from(num), BigInt::from(den)).unwrap()
+}
+
+#[test]
+fn test_rational_arithmetic() {
+    let ctx = Context::default();
+    assert_eq!(ratio(2, -4).to_string(), "-1/2");
+    assert_eq!(ratio(6, 3).to_string(), "2");
+    assert_eq!(ratio(0, -5), Rational::zero());
+    assert!(Rational::new(BigInt::one(), BigInt::zero()).is_none());
+    assert_eq!(ratio(1, 3).checked_add(&ratio(1, 6), &ctx).unwrap(), ratio(1, 2));
+    assert_eq!(ratio(1, 3).checked_sub(&ratio(1, 2), &ctx).unwrap(), ratio(-1, 6));
+    assert_eq!(ratio(2, 3).checked_mul(&ratio(9, 4), &ctx).unwrap(), ratio(3, 2));
+    assert_eq!(ratio(2, 3).checked_div(&ratio(-4, 9), &ctx).unwrap(), ratio(-3, 2));
+    assert!(matches!(ratio(1, 3).checked_div(&Rational::zero(), &ctx), Err(CalcError::DivisionByZero { .. })));
+    assert_eq!(ratio(2, 3).pow(&BigInt::from(3), &ctx).unwrap(), ratio(8, 27));
+    assert_eq!(ratio(-2, 3).pow(&BigInt::from(-2), &ctx).unwrap(), ratio(9, 4));
+    assert!(matches!(Rational::zero().pow(&BigInt::from(-1), &ctx), Err(CalcError::DivisionByZero { .. })));
+    assert!(matches!(ratio(1, 3).pow(&BigInt::from(100_000), &ctx), Err(CalcError::Overflow { .. })));
+    assert_eq!(ratio(7, 2).div_floor(&ratio(1, 3)), Some(BigInt::from(10)));
+    assert_eq!(ratio(-7, 2).div_floor(&ratio(1, 3)), Some(BigInt::from(-11)));
+    assert_eq!(ratio(7, 2).div_floor(&Rational::zero()), None);
+    assert_eq!(ratio(4, 9).root(2), Some(ratio(2, 3)));
+    assert_eq!(ratio(-8, 27).root(3), Some(ratio(-2, 3)));
+    assert_eq!(ratio(2, 9).root(2), None);
+    assert_eq!(ratio(-4, 9).root(2), None);
+    assert!(ratio(1, 3) > ratio(-1, 2) && ratio(1, 3) < ratio(1, 2));
+    assert_eq!(ratio(1, 3).to_decimal(&ctx).unwrap().to_string(), "0.33333333333333333333");
+    assert_eq!(Rational::from(&"0.125".parse::<Decimal>().unwrap()), ratio(1, 8));
+    assert_eq!(ratio(1, 8).round(2, RoundingMode::HalfEven), ratio(3, 25));
+    assert_eq!(ratio(-7, 2).round(0, RoundingMode::Floor), ratio(-4, 1));
+}
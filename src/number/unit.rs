@@ -0,0 +1,485 @@
+use std::{cmp::Ordering, fmt::{Debug, Display}, ops::Neg};
+
+use crate::{
+    error::CalcError,
+    number::{bigint::BigInt, context::Context, decimal::Decimal, literal::parse_literal, math, numeric::Numeric}
+};
+
+/// How many of each SI base unit make up a unit: m, kg, s, A, K, mol and cd.
+type Dimension = [i32; 7];
+
+/// A unit of the registry, e.g. the newton.
+struct Definition {
+    /// How it may be written; the first one is how results are written.
+    symbols: &'static [&'static str],
+    /// How many SI base units it is.
+    factor: fn(&Context) -> Result<Decimal, CalcError>,
+    dimension: Dimension,
+    /// Whether it takes SI prefixes, e.g. `km`.
+    prefixes: bool
+}
+
+const LENGTH: Dimension = [1, 0, 0, 0, 0, 0, 0];
+const MASS: Dimension = [0, 1, 0, 0, 0, 0, 0];
+const TIME: Dimension = [0, 0, 1, 0, 0, 0, 0];
+const ENERGY: Dimension = [2, 1, -2, 0, 0, 0, 0];
+const PRESSURE: Dimension = [-1, 1, -2, 0, 0, 0, 0];
+const NONE: Dimension = [0; 7];
+
+fn one(_: &Context) -> Result<Decimal, CalcError> {
+    Ok(Decimal::one())
+}
+
+/// The units known by name. The gram stands in for the kilogram, so that prefixes go on it.
+const UNITS: &[Definition] = &[
+    Definition { symbols: &["m"], factor: one, dimension: LENGTH, prefixes: true },
+    Definition { symbols: &["g"], factor: |ctx| parse_literal("0.001", ctx), dimension: MASS, prefixes: true },
+    Definition { symbols: &["s"], factor: one, dimension: TIME, prefixes: true },
+    Definition { symbols: &["A"], factor: one, dimension: [0, 0, 0, 1, 0, 0, 0], prefixes: true },
+    Definition { symbols: &["K"], factor: one, dimension: [0, 0, 0, 0, 1, 0, 0], prefixes: true },
+    Definition { symbols: &["mol"], factor: one, dimension: [0, 0, 0, 0, 0, 1, 0], prefixes: true },
+    Definition { symbols: &["cd"], factor: one, dimension: [0, 0, 0, 0, 0, 0, 1], prefixes: true },
+    Definition { symbols: &["Hz"], factor: one, dimension: [0, 0, -1, 0, 0, 0, 0], prefixes: true },
+    Definition { symbols: &["N"], factor: one, dimension: [1, 1, -2, 0, 0, 0, 0], prefixes: true },
+    Definition { symbols: &["Pa"], factor: one, dimension: PRESSURE, prefixes: true },
+    Definition { symbols: &["J"], factor: one, dimension: ENERGY, prefixes: true },
+    Definition { symbols: &["W"], factor: one, dimension: [2, 1, -3, 0, 0, 0, 0], prefixes: true },
+    Definition { symbols: &["C"], factor: one, dimension: [0, 0, 1, 1, 0, 0, 0], prefixes: true },
+    Definition { symbols: &["V"], factor: one, dimension: [2, 1, -3, -1, 0, 0, 0], prefixes: true },
+    Definition { symbols: &["Ω", "ohm"], factor: one, dimension: [2, 1, -3, -2, 0, 0, 0], prefixes: true },
+    Definition { symbols: &["L", "l"], factor: |ctx| parse_literal("0.001", ctx), dimension: [3, 0, 0, 0, 0, 0, 0],
+        prefixes: true },
+    Definition { symbols: &["t"], factor: |ctx| parse_literal("1000", ctx), dimension: MASS, prefixes: true },
+    Definition { symbols: &["eV"], factor: |ctx| parse_literal("1.602176634e-19", ctx), dimension: ENERGY,
+        prefixes: true },
+    Definition { symbols: &["Wh"], factor: |ctx| parse_literal("3600", ctx), dimension: ENERGY, prefixes: true },
+    Definition { symbols: &["cal"], factor: |ctx| parse_literal("4.184", ctx), dimension: ENERGY, prefixes: true },
+    Definition { symbols: &["bar"], factor: |ctx| parse_literal("100000", ctx), dimension: PRESSURE, prefixes: true },
+    Definition { symbols: &["rad"], factor: one, dimension: NONE, prefixes: true },
+    Definition { symbols: &["deg", "°"], factor: |ctx| math::pi(ctx)?.checked_div(&parse_literal("180", ctx)?, ctx),
+        dimension: NONE, prefixes: false },
+    Definition { symbols: &["min"], factor: |ctx| parse_literal("60", ctx), dimension: TIME, prefixes: false },
+    Definition { symbols: &["h"], factor: |ctx| parse_literal("3600", ctx), dimension: TIME, prefixes: false },
+    Definition { symbols: &["day"], factor: |ctx| parse_literal("86400", ctx), dimension: TIME, prefixes: false },
+    Definition { symbols: &["week"], factor: |ctx| parse_literal("604800", ctx), dimension: TIME, prefixes: false },
+    // the Julian year, of 365.25 days
+    Definition { symbols: &["yr"], factor: |ctx| parse_literal("31557600", ctx), dimension: TIME, prefixes: false },
+    Definition { symbols: &["inch"], factor: |ctx| parse_literal("0.0254", ctx), dimension: LENGTH, prefixes: false },
+    Definition { symbols: &["ft"], factor: |ctx| parse_literal("0.3048", ctx), dimension: LENGTH, prefixes: false },
+    Definition { symbols: &["yd"], factor: |ctx| parse_literal("0.9144", ctx), dimension: LENGTH, prefixes: false },
+    Definition { symbols: &["mi"], factor: |ctx| parse_literal("1609.344", ctx), dimension: LENGTH, prefixes: false },
+    Definition { symbols: &["nmi"], factor: |ctx| parse_literal("1852", ctx), dimension: LENGTH, prefixes: false },
+    Definition { symbols: &["au"], factor: |ctx| parse_literal("149597870700", ctx), dimension: LENGTH,
+        prefixes: false },
+    Definition { symbols: &["ha"], factor: |ctx| parse_literal("10000", ctx), dimension: [2, 0, 0, 0, 0, 0, 0],
+        prefixes: false },
+    Definition { symbols: &["lb"], factor: |ctx| parse_literal("0.45359237", ctx), dimension: MASS, prefixes: false },
+    Definition { symbols: &["oz"], factor: |ctx| parse_literal("0.028349523125", ctx), dimension: MASS,
+        prefixes: false },
+    Definition { symbols: &["atm"], factor: |ctx| parse_literal("101325", ctx), dimension: PRESSURE, prefixes: false },
+];
+
+/// The SI prefixes, as powers of ten.
+const PREFIXES: &[(&str, isize)] = &[
+    ("Q", 30), ("R", 27), ("Y", 24), ("Z", 21), ("E", 18), ("P", 15), ("T", 12), ("G", 9), ("M", 6), ("k", 3),
+    ("h", 2), ("da", 1), ("d", -1), ("c", -2), ("m", -3), ("µ", -6), ("μ", -6), ("u", -6), ("n", -9), ("p", -12),
+    ("f", -15), ("a", -18), ("z", -21), ("y", -24), ("r", -27), ("q", -30),
+];
+
+/// A unit of the registry with a prefix, to a power, e.g. `km^2`.
+#[derive(Clone, PartialEq)]
+struct Term {
+    prefix: &'static str,
+    power: isize,
+    symbol: &'static str,
+    /// Where it is in `UNITS`.
+    index: usize,
+    exponent: i32
+}
+
+impl Term {
+    fn definition(&self) -> &'static Definition {
+        &UNITS[self.index]
+    }
+
+    fn is_same_unit(&self, other: &Term) -> bool {
+        self.prefix == other.prefix && self.symbol == other.symbol
+    }
+}
+
+impl Display for Term {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.prefix, self.symbol)?;
+        if self.exponent != 1 { write!(f, "^{}", self.exponent)?; }
+        Ok(())
+    }
+}
+
+/// A product of units to integer powers, e.g. `kg m/s^2`, or none at all for a plain number.
+#[derive(Clone, PartialEq, Default)]
+pub struct Unit {
+    terms: Vec<Term>
+}
+
+impl Unit {
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    pub fn is_none(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// The unit written as `name`, e.g. `km` or `ohm`, if the registry knows it.
+    pub fn find(name: &str) -> Option<Self> {
+        let unit = |prefix: &'static str, power: isize, index: usize, symbol: &'static str| Self {
+            terms: vec![Term { prefix, power, symbol, index, exponent: 1 }]
+        };
+        let symbol = |name: &str, index: usize| UNITS[index].symbols.iter().find(|symbol| **symbol == name).copied();
+        if let Some((index, symbol)) = (0..UNITS.len()).find_map(|index| symbol(name, index).map(|s| (index, s))) {
+            return Some(unit("", 0, index, symbol));
+        }
+        PREFIXES.iter().find_map(|(prefix, power)| {
+            let rest = name.strip_prefix(prefix)?;
+            let index = (0..UNITS.len()).find(|index| UNITS[*index].prefixes && symbol(rest, *index).is_some())?;
+            Some(unit(prefix, *power, index, symbol(rest, index)?))
+        })
+    }
+
+    fn dimension(&self) -> Dimension {
+        let mut dimension = NONE;
+        for term in &self.terms {
+            for (total, base) in dimension.iter_mut().zip(term.definition().dimension) {
+                *total += base * term.exponent;
+            }
+        }
+        dimension
+    }
+
+    /// Whether it measures the same thing as `other`, e.g. `km` and `mi`.
+    pub fn is_compatible(&self, other: &Self) -> bool {
+        self.dimension() == other.dimension()
+    }
+
+    /// Whether a number with it is a plain number, only scaled, e.g. `deg`, or `km/m` which is 1000.
+    fn is_dimensionless(&self) -> bool {
+        self.dimension() == NONE
+    }
+
+    /// How many SI base units it is, as `numerator / denominator`, so that a conversion divides only once.
+    /// They carry guard digits, so that e.g. `180 deg` is `pi` to the last digit.
+    fn factor(&self, ctx: &Context) -> Result<(Decimal, Decimal), CalcError> {
+        let ctx = &math::working(ctx, 0);
+        let (mut numerator, mut denominator) = (Decimal::one(), Decimal::one());
+        for term in &self.terms {
+            let size = (term.definition().factor)(ctx)?.mul_pow10(term.power);
+            let power = size.pow(&Decimal::from_parts(BigInt::from(term.exponent.unsigned_abs() as i128), 0), ctx)?;
+            if term.exponent > 0 { numerator = numerator.checked_mul(&power, ctx)?; }
+            else { denominator = denominator.checked_mul(&power, ctx)?; }
+        }
+        Ok((numerator, denominator))
+    }
+
+    /// The product of the units, with the powers of the same unit added up, e.g. `m/s` times `s` is `m`.
+    pub(crate) fn checked_mul(&self, rhs: &Self) -> Result<Self, CalcError> {
+        let mut terms = self.terms.clone();
+        for term in &rhs.terms {
+            match terms.iter_mut().find(|own| own.is_same_unit(term)) {
+                Some(own) => own.exponent = own.exponent.checked_add(term.exponent).ok_or_else(too_large)?,
+                None => terms.push(term.clone()),
+            }
+        }
+        terms.retain(|term| term.exponent != 0);
+        Ok(Self { terms })
+    }
+
+    pub(crate) fn checked_div(&self, rhs: &Self) -> Result<Self, CalcError> {
+        self.checked_mul(&rhs.powi(-1)?)
+    }
+
+    pub(crate) fn powi(&self, n: i32) -> Result<Self, CalcError> {
+        let terms = self.terms.iter().filter(|_| n != 0)
+            .map(|term| Ok(Term { exponent: term.exponent.checked_mul(n).ok_or_else(too_large)?, ..term.clone() }))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { terms })
+    }
+
+    /// The `k`-th root, if every power is a multiple of `k`, e.g. `m` for `m^2` and `2`.
+    pub(crate) fn root(&self, k: i32) -> Option<Self> {
+        if k == 0 || self.terms.iter().any(|term| term.exponent % k != 0) { return None; }
+        let terms = self.terms.iter().map(|term| Term { exponent: term.exponent / k, ..term.clone() }).collect();
+        Some(Self { terms })
+    }
+
+    /// How it is called in error messages.
+    fn describe(&self) -> String {
+        if self.is_none() { "a number without a unit".to_string() } else { format!("'{}'", self) }
+    }
+}
+
+fn too_large() -> CalcError {
+    CalcError::Overflow { message: "Power of a unit is too large".to_string(), span: None }
+}
+
+impl Display for Unit {
+    /// `kg m/s^2`, `kg/(m s^2)`, or `s^-1` without anything above the line.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let join = |terms: Vec<Term>| terms.iter().map(Term::to_string).collect::<Vec<_>>().join(" ");
+        let (above, below): (Vec<Term>, Vec<Term>) = self.terms.iter().cloned().partition(|term| term.exponent > 0);
+        if above.is_empty() { return write!(f, "{}", join(below)); }
+        write!(f, "{}", join(above))?;
+        let below: Vec<Term> = below.into_iter().map(|term| Term { exponent: -term.exponent, ..term }).collect();
+        match below.len() {
+            0 => Ok(()),
+            1 => write!(f, "/{}", join(below)),
+            _ => write!(f, "/({})", join(below)),
+        }
+    }
+}
+
+impl Debug for Unit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Unit(\"{}\")", self)
+    }
+}
+
+/// A number with a unit, e.g. `9.81 m/s^2`, or without one, like most numbers.
+///
+/// The number is kept in the unit it was written in; operations convert to the unit of their left operand,
+/// e.g. `1 km + 300 m` is `1.3 km`.
+#[derive(Debug, Clone)]
+pub struct Quantity<N> {
+    value: N,
+    unit: Unit
+}
+
+impl<N> Quantity<N> {
+    pub fn new(value: N, unit: Unit) -> Self {
+        Self { value, unit }
+    }
+
+    pub fn value(&self) -> &N {
+        &self.value
+    }
+
+    pub fn unit(&self) -> &Unit {
+        &self.unit
+    }
+
+    pub fn into_value(self) -> N {
+        self.value
+    }
+
+    /// The value, if it is a plain number without a unit.
+    pub(crate) fn plain(&self) -> Option<&N> {
+        if self.unit.is_none() { Some(&self.value) } else { None }
+    }
+}
+
+impl<N> From<N> for Quantity<N> {
+    fn from(value: N) -> Self {
+        Self { value, unit: Unit::none() }
+    }
+}
+
+impl<N: Numeric> Quantity<N> {
+    /// The value in `unit`, which must measure the same thing, e.g. `mi` for `5 km`.
+    pub(crate) fn value_in(&self, unit: &Unit, ctx: &Context) -> Result<N, CalcError> {
+        if self.unit == *unit { return Ok(self.value.clone()); }
+        if !self.unit.is_compatible(unit) {
+            return Err(CalcError::dimension(format!(
+                "Cannot convert {} to {}", self.unit.describe(), unit.describe()
+            )));
+        }
+        let (from_num, from_den) = self.unit.factor(ctx)?;
+        let (to_num, to_den) = unit.factor(ctx)?;
+        self.value.scale(&from_num.checked_mul(&to_den, ctx)?, &from_den.checked_mul(&to_num, ctx)?, ctx)
+    }
+
+    /// The value as a plain number, which `what` needs; an angle in degrees is turned into radians.
+    pub(crate) fn number(&self, what: &str, ctx: &Context) -> Result<N, CalcError> {
+        if !self.unit.is_dimensionless() {
+            return Err(CalcError::dimension(format!(
+                "{} must be a number without a unit, found '{}'", what, self.unit
+            )));
+        }
+        self.value_in(&Unit::none(), ctx)
+    }
+
+    /// The same quantity in `unit`, e.g. `5 km to mi`.
+    pub fn to(&self, unit: &Unit, ctx: &Context) -> Result<Self, CalcError> {
+        Ok(Self { value: self.value_in(unit, ctx)?, unit: unit.clone() })
+    }
+
+    /// `value` in `unit`, as a plain number if the units cancel out, e.g. `km/m` is `1000`.
+    fn simplified(value: N, unit: Unit, ctx: &Context) -> Result<Self, CalcError> {
+        let cancelled = unit.is_dimensionless() && unit.terms.iter().any(|term| term.definition().dimension != NONE);
+        let quantity = Self { value, unit };
+        if cancelled { return Ok(Self::from(quantity.value_in(&Unit::none(), ctx)?)); }
+        Ok(quantity)
+    }
+
+    /// `rhs` in the unit of `self`, which it must have the dimension of.
+    fn matching(&self, rhs: &Self, verb: &str, ctx: &Context) -> Result<N, CalcError> {
+        if !self.unit.is_compatible(&rhs.unit) {
+            return Err(CalcError::dimension(format!(
+                "Cannot {} {} and {}", verb, self.unit.describe(), rhs.unit.describe()
+            )));
+        }
+        rhs.value_in(&self.unit, ctx)
+    }
+
+    pub(crate) fn checked_add(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError> {
+        let rhs = self.matching(rhs, "add", ctx)?;
+        Ok(Self { value: self.value.checked_add(&rhs, ctx)?, unit: self.unit.clone() })
+    }
+
+    pub(crate) fn checked_sub(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError> {
+        let rhs = self.matching(rhs, "subtract", ctx)?;
+        Ok(Self { value: self.value.checked_sub(&rhs, ctx)?, unit: self.unit.clone() })
+    }
+
+    pub(crate) fn checked_mul(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError> {
+        Self::simplified(self.value.checked_mul(&rhs.value, ctx)?, self.unit.checked_mul(&rhs.unit)?, ctx)
+    }
+
+    pub(crate) fn checked_div(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError> {
+        Self::simplified(self.value.checked_div(&rhs.value, ctx)?, self.unit.checked_div(&rhs.unit)?, ctx)
+    }
+
+    /// `self // rhs`, a plain number when both measure the same thing, e.g. `1 h // 25 min` is `2`.
+    pub(crate) fn div_floor(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError> {
+        if self.unit.is_compatible(&rhs.unit) {
+            return Ok(Self::from(self.value.div_floor(&rhs.value_in(&self.unit, ctx)?, ctx)?));
+        }
+        Ok(Self { value: self.value.div_floor(&rhs.value, ctx)?, unit: self.unit.checked_div(&rhs.unit)? })
+    }
+
+    /// `self mod rhs`, in the unit of `self`, e.g. `1 m mod 30 cm` is `0.1 m`.
+    pub(crate) fn rem_floor(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError> {
+        let rhs = self.matching(rhs, "take the remainder of", ctx)?;
+        Ok(Self { value: self.value.rem_floor(&rhs, ctx)?, unit: self.unit.clone() })
+    }
+
+    /// The value as an integer without a unit, which an operand of `symbol` must be, e.g. of `&`.
+    pub(crate) fn integer(&self, symbol: &str, ctx: &Context) -> Result<BigInt, CalcError> {
+        let value = self.number(&format!("An operand of '{}'", symbol), ctx)?;
+        value.to_integer().ok_or_else(|| {
+            CalcError::domain(format!("An operand of '{}' must be an integer, found {}", symbol, value))
+        })
+    }
+
+    pub(crate) fn from_integer(value: BigInt, ctx: &Context) -> Result<Self, CalcError> {
+        Ok(Self::from(N::from_decimal(ctx.check(Decimal::from_parts(value, 0))?, ctx)?))
+    }
+
+    /// Compared in the unit of `self`, which `rhs` must have the dimension of.
+    pub(crate) fn compare(&self, rhs: &Self, ctx: &Context) -> Result<Option<Ordering>, CalcError> {
+        Ok(self.value.partial_cmp(&self.matching(rhs, "compare", ctx)?))
+    }
+
+    /// Whether both are the same amount, in the unit of `self`, so `1 km` equals `1000 m`.
+    pub(crate) fn equals(&self, rhs: &Self, ctx: &Context) -> Result<bool, CalcError> {
+        Ok(self.value == self.matching(rhs, "compare", ctx)?)
+    }
+
+    /// A power with a plain exponent, which must be an integer when the base has a unit, e.g. `(3 m) ^ 2`.
+    pub(crate) fn pow(&self, rhs: &Self, ctx: &Context) -> Result<Self, CalcError> {
+        let exp = rhs.number("An exponent", ctx)?;
+        if self.unit.is_dimensionless() {
+            return Ok(Self::from(self.number("A base", ctx)?.pow(&exp, ctx)?));
+        }
+        let n = exp.to_integer().and_then(|n| n.to_i64()).and_then(|n| i32::try_from(n).ok()).ok_or_else(|| {
+            CalcError::dimension(format!("A number with a unit, '{}', needs an integer power", self.unit))
+        })?;
+        Ok(Self { value: self.value.pow(&exp, ctx)?, unit: self.unit.powi(n)? })
+    }
+
+    /// `self ^ (p / q)`, for a unit that has a `q`-th root, e.g. `(8 m^3) ^ (1/3)` is `2 m`.
+    pub(crate) fn pow_fraction(
+        &self, p: &BigInt, q: &BigInt, ctx: &Context
+    ) -> Result<Self, CalcError> {
+        if self.unit.is_dimensionless() {
+            return Ok(Self::from(self.number("A base", ctx)?.pow_fraction(p, q, ctx)?));
+        }
+        let small = |n: &BigInt| n.to_i64().and_then(|n| i32::try_from(n).ok());
+        let unit = small(p).zip(small(q)).map(|(p, q)| self.unit.powi(p).map(|unit| unit.root(q))).transpose()?
+            .flatten()
+            .ok_or_else(|| CalcError::dimension(format!("The unit '{}' has no power {}/{}", self.unit, p, q)))?;
+        Ok(Self { value: self.value.pow_fraction(p, q, ctx)?, unit })
+    }
+
+    pub(crate) fn sqrt(&self, ctx: &Context) -> Result<Self, CalcError> {
+        if self.unit.is_dimensionless() {
+            return Ok(Self::from(self.number("A square root", ctx)?.sqrt(ctx)?));
+        }
+        let unit = self.unit.root(2).ok_or_else(|| {
+            CalcError::dimension(format!("The unit '{}' has no square root", self.unit))
+        })?;
+        Ok(Self { value: self.value.sqrt(ctx)?, unit })
+    }
+}
+
+impl<N: Neg<Output = N>> Neg for Quantity<N> {
+    type Output = Self;
+    fn neg(self) -> Self::Output {
+        Self { value: -self.value, unit: self.unit }
+    }
+}
+
+impl<N: Display> Display for Quantity<N> {
+    /// `9.81 m/s^2`, or the number alone without a unit.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.unit.is_none() { write!(f, "{}", self.value) } else { write!(f, "{} {}", self.value, self.unit) }
+    }
+}
+
+#[test]
+fn test_unit() {
+    let unit = |name: &str| Unit::find(name).unwrap();
+    assert_eq!(unit("km").to_string(), "km");
+    assert_eq!(unit("ohm").to_string(), "ohm");
+    assert_eq!(unit("min").to_string(), "min");
+    assert_eq!(unit("µs").to_string(), "µs");
+    assert!(Unit::find("kft").is_none() && Unit::find("x").is_none());
+    let acceleration = unit("m").checked_div(&unit("s").powi(2).unwrap()).unwrap();
+    assert_eq!(acceleration.to_string(), "m/s^2");
+    let force = unit("kg").checked_mul(&acceleration).unwrap();
+    assert_eq!(force.to_string(), "kg m/s^2");
+    assert!(force.is_compatible(&unit("N")));
+    assert!(!force.is_compatible(&unit("J")));
+    assert_eq!(unit("kg").checked_div(&unit("m").checked_mul(&unit("s").powi(2).unwrap()).unwrap()).unwrap()
+        .to_string(), "kg/(m s^2)");
+    assert_eq!(unit("Hz").powi(-1).unwrap().to_string(), "Hz^-1");
+    assert_eq!(unit("m").powi(4).unwrap().root(2).unwrap().to_string(), "m^2");
+    assert!(unit("m").powi(3).unwrap().root(2).is_none());
+    assert!(unit("m").checked_div(&unit("m")).unwrap().is_none());
+}
+
+#[test]
+fn test_quantity() {
+    let ctx = Context::default();
+    let quantity = |value: &str, unit: &str| {
+        Quantity::new(value.parse::<Decimal>().unwrap(), Unit::find(unit).unwrap())
+    };
+    let distance = quantity("5", "km");
+    assert_eq!(distance.to(&Unit::find("mi").unwrap(), &ctx).unwrap().to_string(), "3.10685596118666984809 mi");
+    assert_eq!(distance.checked_add(&quantity("300", "m"), &ctx).unwrap().to_string(), "5.3 km");
+    let err = distance.checked_add(&quantity("2", "s"), &ctx).unwrap_err();
+    assert_eq!(err.to_string(), "dimension error: Cannot add 'km' and 's'");
+    assert!(matches!(distance.checked_sub(&Quantity::from(Decimal::one()), &ctx), Err(CalcError::Dimension { .. })));
+    // units that cancel out leave a plain number
+    assert_eq!(distance.checked_div(&quantity("1", "m"), &ctx).unwrap().to_string(), "5000.0");
+    let speed = distance.checked_div(&quantity("0.5", "h"), &ctx).unwrap();
+    assert_eq!(speed.to_string(), "10.0 km/h");
+    assert_eq!(speed.to(&Unit::find("m").unwrap().checked_div(&Unit::find("s").unwrap()).unwrap(), &ctx).unwrap()
+        .to_string(), "2.77777777777777777778 m/s");
+    let area = distance.pow(&Quantity::from("2".parse::<Decimal>().unwrap()), &ctx).unwrap();
+    assert_eq!(area.to_string(), "25.0 km^2");
+    assert_eq!(area.sqrt(&ctx).unwrap().to_string(), "5.0 km");
+    assert!(matches!(distance.sqrt(&ctx), Err(CalcError::Dimension { .. })));
+    let half = Quantity::from("0.5".parse::<Decimal>().unwrap());
+    assert!(matches!(distance.pow(&half, &ctx), Err(CalcError::Dimension { .. })));
+    assert_eq!(quantity("180", "deg").number("An angle", &ctx).unwrap().to_string(), "3.14159265358979323846");
+    assert_eq!(distance.compare(&quantity("4999", "m"), &ctx).unwrap(), Some(Ordering::Greater));
+}